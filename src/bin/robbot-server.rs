@@ -0,0 +1,90 @@
+//! Local stand-in for the SWOQ game server.
+//!
+//! Serves `GameService` on top of the offline simulator so the bot can play
+//! without network access: run `robbot-server` and point `SWOQ_HOST` at it.
+//!
+//! Environment variables:
+//! - `SWOQ_SERVER_ADDR`: listen address (default `127.0.0.1:5001`)
+//! - `SWOQ_SERVER_LEVELS`: level file or directory of `level-<n>.txt` files (default `./levels/`)
+//! - `SWOQ_SERVER_USERS`: comma separated user ids allowed to play (default: everyone)
+//! - `SWOQ_SERVER_GENERATE`: seed to generate levels 0 to 24 from instead of loading them
+
+use dotenv::dotenv;
+use std::env;
+use std::path::Path;
+use tonic::transport::Server;
+use tracing_subscriber::{EnvFilter, FmtSubscriber};
+
+use robbot::sim::{Level, ServerConfig, SimServer, generate_level, load_levels};
+use robbot::swoq_interface::game_service_server::GameServiceServer;
+
+/// Levels generated with `SWOQ_SERVER_GENERATE`, one per tier of the generator
+const GENERATED_LEVELS: i32 = 25;
+
+fn init_logging() {
+    let filter =
+        EnvFilter::try_from_default_env().unwrap_or_else(|_| EnvFilter::new("robbot=info,info"));
+
+    let subscriber = FmtSubscriber::builder()
+        .with_env_filter(filter)
+        .with_target(false)
+        .with_ansi(true)
+        .finish();
+
+    tracing::subscriber::set_global_default(subscriber).expect("setting default subscriber failed");
+}
+
+#[tokio::main]
+async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    dotenv().ok();
+    init_logging();
+
+    let addr = env::var("SWOQ_SERVER_ADDR")
+        .unwrap_or_else(|_| "127.0.0.1:5001".to_string())
+        .parse()?;
+    let levels_path = env::var("SWOQ_SERVER_LEVELS").unwrap_or_else(|_| "./levels/".to_string());
+    let allowed_users = env::var("SWOQ_SERVER_USERS").ok().map(|users| {
+        users
+            .split(',')
+            .map(|user| user.trim().to_string())
+            .filter(|user| !user.is_empty())
+            .collect()
+    });
+
+    let levels = match env::var("SWOQ_SERVER_GENERATE") {
+        Ok(seed) => {
+            let seed: u64 = seed.trim().parse()?;
+            let levels = (0..GENERATED_LEVELS)
+                .map(|number| generate_level(number, seed.wrapping_add(number as u64)))
+                .collect::<Result<Vec<Level>, _>>()?;
+            tracing::info!("Generated levels 0 to {} from seed {}", GENERATED_LEVELS - 1, seed);
+            levels
+        }
+        Err(_) => {
+            let levels = load_levels(Path::new(&levels_path))?;
+            if levels.is_empty() {
+                return Err(format!("No levels found in {}", levels_path).into());
+            }
+            tracing::info!(
+                "Loaded levels {:?} from {}",
+                levels.iter().map(|level| level.number).collect::<Vec<_>>(),
+                levels_path
+            );
+            levels
+        }
+    };
+
+    let config = ServerConfig {
+        allowed_users,
+        ..Default::default()
+    };
+    let server = SimServer::new(levels, config);
+
+    tracing::info!("Listening on {}", addr);
+    Server::builder()
+        .add_service(GameServiceServer::new(server))
+        .serve(addr)
+        .await?;
+
+    Ok(())
+}
@@ -0,0 +1,460 @@
+//! Offline training of the RL planner on the simulator.
+//!
+//! Subcommands:
+//! - `collect`: play the expert planner on the training levels, optionally saving the demos
+//! - `convert`: turn `.swoq` replays of expert games into a demo file
+//! - `bc`: clone the expert's demos into a model (behavioral cloning)
+//! - `ppo`: PPO training, optionally warm started with BC, from a model or resumed from a checkpoint
+//!
+//! Settings come from `--config` or `./robbot-train.toml`, see `robbot-train.example.toml`.
+//! Needs the `rl` feature: `cargo run --release --features rl --bin robbot-train -- ppo`.
+
+use clap::{Parser, Subcommand};
+use std::error::Error;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use tracing_subscriber::{EnvFilter, FmtSubscriber};
+
+use burn::tensor::backend::AutodiffBackend;
+
+use robbot::config::{ConfigError, RlConfig};
+use robbot::eval::{PlannerKind, parse_level_list};
+use robbot::infra::ReplayGameConnection;
+use robbot::planners::rl::{
+    BCConfig, BCTrainer, BackendKind, BackendTask, BatchEnv, Curriculum, DEFAULT_TRAIN_CONFIG_FILE,
+    DemoCollector, DemoDataset, PPOTrainer, StateEncoder, TrainSettings,
+};
+use robbot::sim::{Level, SimConfig, load_levels};
+
+#[derive(Debug, Parser)]
+#[command(
+    name = "robbot-train",
+    about = "Train the RL planner on the offline simulator"
+)]
+struct Cli {
+    /// Settings file, defaults to ./robbot-train.toml when present
+    #[arg(long, value_name = "FILE")]
+    config: Option<PathBuf>,
+    /// Burn backend, cpu or metal
+    #[arg(long)]
+    backend: Option<String>,
+    /// Levels to play, like 3, 1-5 or 1,3,7-9
+    #[arg(long, value_name = "LEVELS")]
+    levels: Option<String>,
+    /// Expert planner for demos, heuristic or goap
+    #[arg(long)]
+    expert: Option<String>,
+
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Debug, Subcommand)]
+enum Command {
+    /// Play the expert on the levels and report the demos it yields
+    Collect {
+        /// Save the demos to a demo file
+        #[arg(long, value_name = "FILE")]
+        output: Option<PathBuf>,
+    },
+    /// Convert replays of expert games into a demo file
+    Convert {
+        #[arg(long, value_name = "FILE")]
+        output: PathBuf,
+        /// Replay files, or folders of .swoq files
+        #[arg(required = true, value_name = "REPLAY")]
+        replays: Vec<PathBuf>,
+    },
+    /// Train a model on the expert's demos
+    Bc {
+        /// Model path without extension, defaults to bc.save_path
+        #[arg(long, value_name = "PATH")]
+        output: Option<String>,
+        /// Train on saved demo files instead of playing the expert
+        #[arg(long, value_name = "FILE", num_args = 1..)]
+        demos: Vec<PathBuf>,
+    },
+    /// Train with PPO on the levels, in parallel environments
+    Ppo {
+        /// Demo files for the BC warmup instead of playing the expert
+        #[arg(long, value_name = "FILE", num_args = 1..)]
+        demos: Vec<PathBuf>,
+        /// Continue from a checkpoint and its iteration, like checkpoints/checkpoint_200
+        #[arg(long, value_name = "CHECKPOINT", conflicts_with = "init")]
+        resume: Option<String>,
+        /// Start from the weights of a model, such as one trained by `bc`
+        #[arg(long, value_name = "MODEL")]
+        init: Option<String>,
+        #[arg(long, value_name = "N")]
+        iterations: Option<usize>,
+    },
+}
+
+impl Cli {
+    /// Settings file overridden by the flags
+    fn settings(&self) -> Result<TrainSettings, ConfigError> {
+        let default = Path::new(DEFAULT_TRAIN_CONFIG_FILE);
+        let mut settings = match &self.config {
+            Some(path) => TrainSettings::load(path)?,
+            None if default.exists() => TrainSettings::load(default)?,
+            None => TrainSettings::default(),
+        };
+
+        if let Some(levels) = &self.levels {
+            settings.levels = parse_level_list(levels).map_err(|_| ConfigError::InvalidValue {
+                setting: "--levels".to_string(),
+                value: levels.clone(),
+            })?;
+        }
+        if let Some(expert) = &self.expert {
+            settings.expert =
+                PlannerKind::parse(expert).ok_or_else(|| ConfigError::InvalidValue {
+                    setting: "--expert".to_string(),
+                    value: expert.clone(),
+                })?;
+        }
+        if let Command::Ppo {
+            iterations: Some(iterations),
+            ..
+        } = self.command
+        {
+            settings.ppo.num_iterations = iterations;
+        }
+        settings.validate()?;
+        Ok(settings)
+    }
+
+    fn backend(&self) -> Result<BackendKind, ConfigError> {
+        match &self.backend {
+            Some(name) => BackendKind::parse(name).map_err(|reason| ConfigError::Invalid {
+                setting: "--backend",
+                reason,
+            }),
+            None => Ok(BackendKind::default()),
+        }
+    }
+}
+
+/// Settings problems end the program before any training starts, like in `robbot`
+fn exit_on_error<T>(result: Result<T, ConfigError>) -> T {
+    result.unwrap_or_else(|e| {
+        eprintln!("robbot-train: {}", e);
+        std::process::exit(2);
+    })
+}
+
+fn init_logging() {
+    let filter =
+        EnvFilter::try_from_default_env().unwrap_or_else(|_| EnvFilter::new("robbot=info,info"));
+
+    let subscriber = FmtSubscriber::builder()
+        .with_env_filter(filter)
+        .with_target(false)
+        .with_ansi(true)
+        .finish();
+
+    tracing::subscriber::set_global_default(subscriber).expect("setting default subscriber failed");
+}
+
+/// The configured levels in their configured order, or every level found
+fn training_levels(settings: &TrainSettings) -> Result<Vec<Level>, Box<dyn Error>> {
+    let available = load_levels(Path::new(&settings.levels_path))?;
+    if settings.levels.is_empty() {
+        if available.is_empty() {
+            return Err(format!("No levels found in {}", settings.levels_path).into());
+        }
+        return Ok(available);
+    }
+
+    settings
+        .levels
+        .iter()
+        .map(|&number| {
+            available
+                .iter()
+                .find(|level| level.number == number)
+                .cloned()
+                .ok_or_else(|| {
+                    format!("Level {} not found in {}", number, settings.levels_path).into()
+                })
+        })
+        .collect()
+}
+
+/// Play every level once with the expert and keep the ticks that map onto the action space
+fn collect_demos(
+    settings: &TrainSettings,
+    levels: &[Level],
+) -> Result<DemoDataset, Box<dyn Error>> {
+    let encoder = StateEncoder::new(settings.encoder.clone());
+    let mut collector = DemoCollector::new();
+
+    for level in levels {
+        let mut planner = settings
+            .expert
+            .create(&settings.goap, &RlConfig::default())?;
+        let game = collector.collect_game(planner.as_mut(), level, SimConfig::default(), &encoder);
+        tracing::info!(
+            "{} level {}: {:?} after {} ticks, {} demos, {} unmatched ticks",
+            settings.expert.name(),
+            level.number,
+            game.status,
+            game.ticks,
+            game.demos,
+            game.unmatched
+        );
+    }
+
+    tracing::info!("Collected {} demos", collector.len());
+    Ok(collector.into_dataset())
+}
+
+/// `.swoq` files in the given files and folders, sorted per folder
+fn replay_files(paths: &[PathBuf]) -> Result<Vec<PathBuf>, Box<dyn Error>> {
+    let mut files = Vec::new();
+    for path in paths {
+        if !path.is_dir() {
+            files.push(path.clone());
+            continue;
+        }
+        let mut found = Vec::new();
+        for entry in std::fs::read_dir(path)? {
+            let file = entry?.path();
+            if file.extension().is_some_and(|ext| ext == "swoq") {
+                found.push(file);
+            }
+        }
+        found.sort();
+        files.extend(found);
+    }
+    Ok(files)
+}
+
+fn convert_replays(
+    settings: &TrainSettings,
+    replays: &[PathBuf],
+) -> Result<DemoDataset, Box<dyn Error>> {
+    let encoder = StateEncoder::new(settings.encoder.clone());
+    let mut collector = DemoCollector::new();
+
+    for path in replay_files(replays)? {
+        let replay = ReplayGameConnection::open(&path)
+            .map_err(|e| format!("Cannot read replay {}: {}", path.display(), e))?;
+        let source = path.display().to_string();
+        let game = collector.collect_replay(&replay, &source, &encoder);
+        tracing::info!(
+            "{}: level {}, {:?} after {} ticks, {} demos, {} unmatched ticks",
+            source,
+            game.level,
+            game.status,
+            game.ticks,
+            game.demos,
+            game.unmatched
+        );
+    }
+
+    tracing::info!("Converted {} demos", collector.len());
+    Ok(collector.into_dataset())
+}
+
+/// Demos from the demo files, or from the expert playing the levels when there are none
+fn load_demos(
+    settings: &TrainSettings,
+    levels: &[Level],
+    files: &[PathBuf],
+) -> Result<DemoDataset, Box<dyn Error>> {
+    if files.is_empty() {
+        return collect_demos(settings, levels);
+    }
+
+    let encoder = StateEncoder::new(settings.encoder.clone());
+    let mut dataset = DemoDataset::new();
+    for file in files {
+        dataset.append(DemoDataset::load(file, &encoder)?);
+    }
+    tracing::info!("Loaded {} demos of {} games", dataset.len(), dataset.games().len());
+    Ok(dataset)
+}
+
+fn save_demos(
+    settings: &TrainSettings,
+    dataset: &DemoDataset,
+    path: &Path,
+) -> Result<(), Box<dyn Error>> {
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent).ok();
+    }
+    dataset.save(path, &StateEncoder::new(settings.encoder.clone()))?;
+    tracing::info!("Saved {} demos to {}", dataset.len(), path.display());
+    Ok(())
+}
+
+/// Iteration a `checkpoint_<n>` file was written after
+fn checkpoint_iteration(path: &str) -> Option<usize> {
+    Path::new(path)
+        .file_stem()?
+        .to_str()?
+        .strip_prefix("checkpoint_")?
+        .parse()
+        .ok()
+}
+
+fn create_parent_dir(path: &str) -> std::io::Result<()> {
+    match Path::new(path).parent() {
+        Some(parent) => std::fs::create_dir_all(parent),
+        None => Ok(()),
+    }
+}
+
+struct BcTask<'a> {
+    settings: &'a TrainSettings,
+    dataset: DemoDataset,
+    output: String,
+}
+
+impl BackendTask for BcTask<'_> {
+    type Output = ();
+
+    fn run<B: AutodiffBackend>(self, device: B::Device) {
+        let config = BCConfig {
+            save_path: self.output,
+            ..self.settings.bc.clone()
+        };
+        let mut trainer =
+            BCTrainer::<B>::new(device, &self.settings.encoder, &self.settings.mappo, config);
+        trainer.train(&self.dataset);
+    }
+}
+
+struct PpoTask<'a> {
+    settings: &'a TrainSettings,
+    levels: Vec<Level>,
+    resume: Option<String>,
+    init: Option<String>,
+    /// Demos for the BC warmup, when it runs
+    warmup: Option<DemoDataset>,
+}
+
+impl BackendTask for PpoTask<'_> {
+    type Output = Result<(), Box<dyn Error>>;
+
+    fn run<B: AutodiffBackend>(self, device: B::Device) -> Self::Output {
+        let config = self.settings.train_config();
+        let mut trainer = PPOTrainer::<B>::new(device, config.clone());
+        let curriculum = self.settings.curriculum.enabled.then(|| {
+            let curriculum = Curriculum::new(self.levels.clone(), self.settings.curriculum.clone());
+            Arc::new(curriculum)
+        });
+
+        if let Some(path) = &self.resume {
+            trainer.load_checkpoint(path)?;
+            trainer.set_iteration(checkpoint_iteration(path).map_or(0, |iteration| iteration + 1));
+            if let Some(curriculum) = &curriculum {
+                curriculum.restore(path).map_err(|e| {
+                    format!("Cannot restore the curriculum stage of {}: {}", path, e)
+                })?;
+            }
+        } else if let Some(path) = &self.init {
+            trainer.load_checkpoint(path)?;
+        } else if let Some(dataset) = &self.warmup {
+            trainer.warm_start(dataset, self.settings.bc.clone());
+        }
+
+        let seed = config.seed.unwrap_or_else(rand::random);
+        tracing::info!("Training with {} envs, seed {}", config.num_envs, seed);
+        let envs = match curriculum {
+            Some(curriculum) => {
+                tracing::info!("Curriculum starts on level {}", curriculum.current_level().number);
+                trainer.set_curriculum(curriculum.clone());
+                BatchEnv::with_sampler(
+                    curriculum,
+                    config.num_envs,
+                    SimConfig::default(),
+                    config.env_config.clone(),
+                    seed,
+                )
+            }
+            None => BatchEnv::from_levels(
+                &self.levels,
+                config.num_envs,
+                SimConfig::default(),
+                config.env_config.clone(),
+                seed,
+            ),
+        };
+        trainer.train(envs);
+        Ok(())
+    }
+}
+
+fn main() {
+    init_logging();
+
+    if let Err(e) = run(Cli::parse()) {
+        eprintln!("robbot-train: {}", e);
+        std::process::exit(1);
+    }
+}
+
+fn run(cli: Cli) -> Result<(), Box<dyn Error>> {
+    let settings = exit_on_error(cli.settings());
+    let backend = exit_on_error(cli.backend());
+    // Replays bring their own levels
+    let levels = match cli.command {
+        Command::Convert { .. } => Vec::new(),
+        _ => training_levels(&settings)?,
+    };
+    tracing::info!(
+        "Levels {:?}, backend {}",
+        levels.iter().map(|level| level.number).collect::<Vec<_>>(),
+        backend.name()
+    );
+
+    match cli.command {
+        Command::Collect { output } => {
+            let dataset = collect_demos(&settings, &levels)?;
+            if let Some(path) = output {
+                save_demos(&settings, &dataset, &path)?;
+            }
+        }
+        Command::Convert { output, replays } => {
+            let dataset = convert_replays(&settings, &replays)?;
+            save_demos(&settings, &dataset, &output)?;
+        }
+        Command::Bc { output, demos } => {
+            let dataset = load_demos(&settings, &levels, &demos)?;
+            let output = output.unwrap_or_else(|| settings.bc.save_path.clone());
+            create_parent_dir(&output)
+                .map_err(|e| format!("Cannot create the directory for {}: {}", output, e))?;
+            backend.run(BcTask {
+                settings: &settings,
+                dataset,
+                output,
+            });
+        }
+        Command::Ppo {
+            demos,
+            resume,
+            init,
+            ..
+        } => {
+            let warm_start = settings.ppo.use_bc_warmup && resume.is_none() && init.is_none();
+            if !demos.is_empty() && !warm_start {
+                tracing::warn!("Ignoring --demos, the BC warmup does not run");
+            }
+            let warmup = match warm_start {
+                true => Some(load_demos(&settings, &levels, &demos)?),
+                false => None,
+            };
+            backend.run(PpoTask {
+                settings: &settings,
+                levels,
+                resume,
+                init,
+                warmup,
+            })?;
+        }
+    }
+
+    Ok(())
+}
@@ -0,0 +1,229 @@
+use std::env;
+use std::path::{Path, PathBuf};
+
+use clap::Parser;
+
+use super::settings::{
+    Config, ConfigError, DEFAULT_CONFIG_FILE, ObserverKind, parse_list, parse_planner,
+    parse_planners,
+};
+
+/// Command line flags, each overrides the config file and the environment
+#[derive(Debug, Default, Parser)]
+#[command(name = "robbot", about = "SWOQ bot")]
+pub struct Cli {
+    /// Config file, defaults to $SWOQ_CONFIG or ./robbot.toml when present
+    #[arg(long, value_name = "FILE")]
+    pub config: Option<PathBuf>,
+
+    #[arg(long)]
+    pub user_id: Option<String>,
+    #[arg(long)]
+    pub user_name: Option<String>,
+    #[arg(long)]
+    pub host: Option<String>,
+    #[arg(long, value_name = "DIR")]
+    pub replays_folder: Option<String>,
+    /// Play back a recorded game instead of connecting
+    #[arg(long, value_name = "FILE")]
+    pub replay_file: Option<String>,
+
+    /// heuristic, goap or rl
+    #[arg(long)]
+    pub planner: Option<String>,
+    /// Levels to play, like 3, 1-5 or 1,3,7-9
+    #[arg(long, value_name = "LEVELS")]
+    pub level: Option<String>,
+    /// Seeds to play every level with, like 42 or 1-10
+    #[arg(long, value_name = "SEEDS")]
+    pub seed: Option<String>,
+    /// Passes over all levels and seeds, 0 repeats forever
+    #[arg(long, value_name = "N")]
+    pub loop_count: Option<u32>,
+    /// Show the game in the visualizer window
+    #[arg(long)]
+    pub visualizer: bool,
+    /// Also write every game event as JSON lines to this file
+    #[arg(long, value_name = "FILE")]
+    pub event_log: Option<String>,
+
+    #[arg(long, value_name = "N")]
+    pub goap_max_depth: Option<usize>,
+    #[arg(long, value_name = "MS")]
+    pub goap_timeout_ms: Option<u64>,
+    /// Spend at most MS on each GOAP tick, improving the plans over later ticks
+    #[arg(long, value_name = "MS")]
+    pub goap_tick_budget_ms: Option<u64>,
+    /// Write a report of every GOAP search to this directory, as JSON and Graphviz DOT
+    #[arg(long, value_name = "DIR")]
+    pub goap_report_dir: Option<String>,
+    /// Checkpoint of the rl planner, without the .mpk extension
+    #[arg(long, value_name = "PATH")]
+    pub rl_model: Option<String>,
+    /// Burn backend of the rl planner, cpu or metal
+    #[arg(long, value_name = "BACKEND")]
+    pub rl_backend: Option<String>,
+    #[arg(long, value_name = "N")]
+    pub cbs_max_ct_nodes: Option<usize>,
+    #[arg(long, value_name = "N")]
+    pub cbs_max_expansions: Option<usize>,
+
+    /// Run a headless evaluation of these levels instead of playing
+    #[arg(long, value_name = "LEVELS")]
+    pub eval_levels: Option<String>,
+    #[arg(long, value_name = "N")]
+    pub eval_games: Option<i32>,
+    #[arg(long, value_name = "SEED")]
+    pub eval_seed: Option<i32>,
+    /// Comma separated planners to evaluate
+    #[arg(long, value_name = "PLANNERS")]
+    pub eval_planners: Option<String>,
+    /// Report path without extension
+    #[arg(long, value_name = "PATH")]
+    pub eval_report: Option<String>,
+}
+
+impl Cli {
+    /// Combine defaults, config file, environment and these flags into a checked `Config`
+    pub fn resolve(&self) -> Result<Config, ConfigError> {
+        let mut config = match self.config_path() {
+            Some(path) => Config::load(&path)?,
+            None => Config::default(),
+        };
+        config.apply_env(|key| env::var(key).ok())?;
+        self.apply(&mut config)?;
+        config.validate()?;
+        Ok(config)
+    }
+
+    fn config_path(&self) -> Option<PathBuf> {
+        self.config
+            .clone()
+            .or_else(|| env::var_os("SWOQ_CONFIG").map(PathBuf::from))
+            .or_else(|| {
+                let default = Path::new(DEFAULT_CONFIG_FILE);
+                default.exists().then(|| default.to_path_buf())
+            })
+    }
+
+    pub fn apply(&self, config: &mut Config) -> Result<(), ConfigError> {
+        let connection = &mut config.connection;
+        for (flag, field) in [
+            (&self.user_id, &mut connection.user_id),
+            (&self.user_name, &mut connection.user_name),
+            (&self.host, &mut connection.host),
+            (&self.replays_folder, &mut connection.replays_folder),
+            (&self.replay_file, &mut connection.replay_file),
+        ] {
+            if let Some(value) = flag {
+                *field = Some(value.clone());
+            }
+        }
+
+        if let Some(planner) = &self.planner {
+            config.game.planner = parse_planner("--planner", planner)?;
+        }
+        if let Some(levels) = &self.level {
+            config.game.levels = parse_list("--level", levels)?;
+        }
+        if let Some(seeds) = &self.seed {
+            config.game.seeds = parse_list("--seed", seeds)?;
+        }
+        if let Some(loop_count) = self.loop_count {
+            config.game.loop_count = loop_count;
+        }
+        if self.visualizer {
+            config.game.observer = ObserverKind::Visualizer;
+        }
+        if let Some(event_log) = &self.event_log {
+            config.game.event_log = Some(event_log.clone());
+        }
+
+        if let Some(max_depth) = self.goap_max_depth {
+            config.goap.max_depth = max_depth;
+        }
+        if let Some(timeout_ms) = self.goap_timeout_ms {
+            config.goap.timeout_ms = timeout_ms;
+        }
+        if let Some(tick_budget_ms) = self.goap_tick_budget_ms {
+            config.goap.tick_budget_ms = Some(tick_budget_ms);
+        }
+        if let Some(report_dir) = &self.goap_report_dir {
+            config.goap.report_dir = Some(report_dir.clone());
+        }
+        if let Some(model_path) = &self.rl_model {
+            config.rl.model_path = model_path.clone();
+        }
+        if let Some(backend) = &self.rl_backend {
+            config.rl.backend = Some(backend.clone());
+        }
+        if let Some(max_ct_nodes) = self.cbs_max_ct_nodes {
+            config.cbs.max_ct_nodes = max_ct_nodes;
+        }
+        if let Some(max_expansions) = self.cbs_max_expansions {
+            config.cbs.max_expansions = max_expansions;
+        }
+
+        if let Some(levels) = &self.eval_levels {
+            config.eval.levels = parse_list("--eval-levels", levels)?;
+        }
+        if let Some(games) = self.eval_games {
+            config.eval.games = games;
+        }
+        if let Some(seed) = self.eval_seed {
+            config.eval.first_seed = seed;
+        }
+        if let Some(planners) = &self.eval_planners {
+            config.eval.planners = parse_planners("--eval-planners", planners)?;
+        }
+        if let Some(report) = &self.eval_report {
+            config.eval.report = report.clone();
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::eval::PlannerKind;
+
+    #[test]
+    fn test_flags_override_config() {
+        let cli = Cli::try_parse_from([
+            "robbot",
+            "--planner",
+            "goap",
+            "--level",
+            "1,3",
+            "--loop-count",
+            "0",
+            "--goap-timeout-ms",
+            "100",
+            "--goap-tick-budget-ms",
+            "30",
+            "--cbs-max-expansions",
+            "50",
+        ])
+        .unwrap();
+
+        let mut config = Config::default();
+        config.goap.max_depth = 20;
+        cli.apply(&mut config).unwrap();
+
+        assert_eq!(config.game.planner, PlannerKind::Goap);
+        assert_eq!(config.game.levels, vec![1, 3]);
+        assert_eq!(config.game.loop_count, 0);
+        assert_eq!(config.goap.max_depth, 20);
+        assert_eq!(config.goap.timeout_ms, 100);
+        assert_eq!(config.goap.tick_budget_ms, Some(30));
+        assert_eq!(config.cbs.max_expansions, 50);
+    }
+
+    #[test]
+    fn test_invalid_flag_value_is_an_error() {
+        let cli = Cli::try_parse_from(["robbot", "--eval-planners", "goap,astar"]).unwrap();
+        let error = cli.apply(&mut Config::default()).unwrap_err();
+        assert_eq!(error.to_string(), "Invalid value \"astar\" for --eval-planners");
+    }
+}
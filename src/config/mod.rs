@@ -0,0 +1,14 @@
+//! Runtime configuration of the bot
+//!
+//! Settings come from a TOML config file (see `robbot.example.toml`), the
+//! `SWOQ_*` environment variables and command line flags, in increasing
+//! priority. Problems are reported as a `ConfigError` before any game starts.
+
+mod cli;
+mod settings;
+
+pub use cli::Cli;
+pub use settings::{
+    Config, ConfigError, ConnectionConfig, DEFAULT_CONFIG_FILE, EvalSettings, GameConfig,
+    GoapConfig, ObserverKind, RlConfig,
+};
@@ -0,0 +1,650 @@
+use std::error::Error;
+use std::fmt;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+
+use serde::Deserialize;
+
+use crate::eval::{self, PlannerKind};
+use crate::infra::CbsLimits;
+
+/// Config file read when neither `--config` nor `SWOQ_CONFIG` names one
+pub const DEFAULT_CONFIG_FILE: &str = "robbot.toml";
+
+#[derive(Debug)]
+pub enum ConfigError {
+    Read {
+        path: PathBuf,
+        message: String,
+    },
+    Parse {
+        path: PathBuf,
+        message: String,
+    },
+    InvalidValue {
+        setting: String,
+        value: String,
+    },
+    Missing {
+        setting: &'static str,
+        env: &'static str,
+        flag: &'static str,
+    },
+    Invalid {
+        setting: &'static str,
+        reason: String,
+    },
+}
+
+impl fmt::Display for ConfigError {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ConfigError::Read { path, message } => {
+                write!(formatter, "Cannot read config file {}: {}", path.display(), message)
+            }
+            ConfigError::Parse { path, message } => {
+                write!(formatter, "Invalid config file {}: {}", path.display(), message)
+            }
+            ConfigError::InvalidValue { setting, value } => {
+                write!(formatter, "Invalid value {:?} for {}", value, setting)
+            }
+            ConfigError::Missing { setting, env, flag } => write!(
+                formatter,
+                "Missing {}, set it in the config file, with {} or with {}",
+                setting, env, flag
+            ),
+            ConfigError::Invalid { setting, reason } => {
+                write!(formatter, "Invalid {}: {}", setting, reason)
+            }
+        }
+    }
+}
+
+impl Error for ConfigError {}
+
+/// Runtime settings of the bot.
+/// Sources in increasing priority: defaults, config file, `SWOQ_*` environment
+/// variables and command line flags.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Config {
+    pub connection: ConnectionConfig,
+    pub game: GameConfig,
+    pub goap: GoapConfig,
+    pub rl: RlConfig,
+    pub cbs: CbsLimits,
+    pub eval: EvalSettings,
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ConnectionConfig {
+    pub user_id: Option<String>,
+    pub user_name: Option<String>,
+    pub host: Option<String>,
+    /// Folder where the server connection writes a replay of every game
+    pub replays_folder: Option<String>,
+    /// Play back this recorded game instead of connecting to a server
+    pub replay_file: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ObserverKind {
+    /// Log progress to the console
+    #[default]
+    Default,
+    /// Console logging plus the visualizer window
+    Visualizer,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct GameConfig {
+    pub planner: PlannerKind,
+    /// Levels to play in turn, empty lets the server choose
+    pub levels: Vec<i32>,
+    /// Seeds to play every level with, empty lets the server choose
+    pub seeds: Vec<i32>,
+    /// Number of passes over all levels and seeds, 0 repeats forever
+    pub loop_count: u32,
+    pub observer: ObserverKind,
+    /// Also write every game event as JSON lines to this file
+    pub event_log: Option<String>,
+}
+
+impl Default for GameConfig {
+    fn default() -> Self {
+        Self {
+            planner: PlannerKind::Heuristic,
+            levels: Vec::new(),
+            seeds: Vec::new(),
+            loop_count: 1,
+            observer: ObserverKind::Default,
+            event_log: None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct GoapConfig {
+    pub max_depth: usize,
+    /// Time limit of a single replan
+    pub timeout_ms: u64,
+    /// Time per tick, executing the plans included; the search gets what is left and
+    /// continues over the following ticks instead of blocking the replan. Leave out to block.
+    pub tick_budget_ms: Option<u64>,
+    /// Write a report of every search to this directory, as JSON and Graphviz DOT
+    pub report_dir: Option<String>,
+}
+
+impl Default for GoapConfig {
+    fn default() -> Self {
+        Self {
+            max_depth: 10,
+            timeout_ms: 5000,
+            tick_budget_ms: None,
+            report_dir: None,
+        }
+    }
+}
+
+/// Trained policy played by the `rl` planner
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct RlConfig {
+    /// MAPPO checkpoint written by the trainer, without the `.mpk` extension
+    pub model_path: String,
+    /// `cpu`, or `metal` in builds with the `rl-metal` feature. Unset picks the build's default
+    pub backend: Option<String>,
+}
+
+impl Default for RlConfig {
+    fn default() -> Self {
+        Self {
+            model_path: "checkpoints/final".to_string(),
+            backend: None,
+        }
+    }
+}
+
+/// Headless evaluation, runs instead of a normal game when `levels` is not empty
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct EvalSettings {
+    pub levels: Vec<i32>,
+    /// Games per level, with seeds `first_seed..first_seed + games`
+    pub games: i32,
+    pub first_seed: i32,
+    pub planners: Vec<PlannerKind>,
+    /// Report path without extension, `.csv` and `.json` files are written
+    pub report: String,
+}
+
+impl Default for EvalSettings {
+    fn default() -> Self {
+        Self {
+            levels: Vec::new(),
+            games: 10,
+            first_seed: 0,
+            planners: vec![PlannerKind::Heuristic, PlannerKind::Goap],
+            report: "eval-report".to_string(),
+        }
+    }
+}
+
+impl Config {
+    pub fn from_toml(text: &str, path: &Path) -> Result<Self, ConfigError> {
+        toml::from_str(text).map_err(|e| ConfigError::Parse {
+            path: path.to_path_buf(),
+            message: e.message().to_string(),
+        })
+    }
+
+    pub fn load(path: &Path) -> Result<Self, ConfigError> {
+        let text = fs::read_to_string(path).map_err(|e| ConfigError::Read {
+            path: path.to_path_buf(),
+            message: e.to_string(),
+        })?;
+        Self::from_toml(&text, path)
+    }
+
+    /// Override settings with the `SWOQ_*` variables that `lookup` knows
+    pub fn apply_env(
+        &mut self,
+        lookup: impl Fn(&str) -> Option<String>,
+    ) -> Result<(), ConfigError> {
+        let var = |key: &str| lookup(key).filter(|value| !value.trim().is_empty());
+
+        let connection = &mut self.connection;
+        for (key, field) in [
+            ("SWOQ_USER_ID", &mut connection.user_id),
+            ("SWOQ_USER_NAME", &mut connection.user_name),
+            ("SWOQ_HOST", &mut connection.host),
+            ("SWOQ_REPLAYS_FOLDER", &mut connection.replays_folder),
+            ("SWOQ_REPLAY_FILE", &mut connection.replay_file),
+        ] {
+            if let Some(value) = var(key) {
+                *field = Some(value);
+            }
+        }
+
+        if let Some(value) = var("SWOQ_GOAP_ENABLED") {
+            self.game.planner = match parse_value::<bool>("SWOQ_GOAP_ENABLED", &value)? {
+                true => PlannerKind::Goap,
+                false => PlannerKind::Heuristic,
+            };
+        }
+        if let Some(value) = var("SWOQ_PLANNER") {
+            self.game.planner = parse_planner("SWOQ_PLANNER", &value)?;
+        }
+        if let Some(value) = var("SWOQ_LEVEL") {
+            self.game.levels = parse_list("SWOQ_LEVEL", &value)?;
+        }
+        if let Some(value) = var("SWOQ_SEED") {
+            self.game.seeds = parse_list("SWOQ_SEED", &value)?;
+        }
+        if let Some(value) = var("SWOQ_LOOP") {
+            // Older setting: loop forever or play once
+            self.game.loop_count = match parse_value::<bool>("SWOQ_LOOP", &value)? {
+                true => 0,
+                false => 1,
+            };
+        }
+        if let Some(value) = var("SWOQ_LOOP_COUNT") {
+            self.game.loop_count = parse_value("SWOQ_LOOP_COUNT", &value)?;
+        }
+        if let Some(value) = var("SWOQ_VISUALIZER") {
+            self.game.observer = match parse_value::<bool>("SWOQ_VISUALIZER", &value)? {
+                true => ObserverKind::Visualizer,
+                false => ObserverKind::Default,
+            };
+        }
+        if let Some(value) = var("SWOQ_EVENT_LOG") {
+            self.game.event_log = Some(value);
+        }
+
+        if let Some(value) = var("SWOQ_GOAP_MAX_DEPTH") {
+            self.goap.max_depth = parse_value("SWOQ_GOAP_MAX_DEPTH", &value)?;
+        }
+        if let Some(value) = var("SWOQ_GOAP_TIMEOUT_MS") {
+            self.goap.timeout_ms = parse_value("SWOQ_GOAP_TIMEOUT_MS", &value)?;
+        }
+        if let Some(value) = var("SWOQ_GOAP_TICK_BUDGET_MS") {
+            self.goap.tick_budget_ms = Some(parse_value("SWOQ_GOAP_TICK_BUDGET_MS", &value)?);
+        }
+        if let Some(value) = var("SWOQ_GOAP_REPORT_DIR") {
+            self.goap.report_dir = Some(value);
+        }
+        if let Some(value) = var("SWOQ_RL_MODEL") {
+            self.rl.model_path = value;
+        }
+        if let Some(value) = var("SWOQ_RL_BACKEND") {
+            self.rl.backend = Some(value);
+        }
+        if let Some(value) = var("SWOQ_CBS_MAX_CT_NODES") {
+            self.cbs.max_ct_nodes = parse_value("SWOQ_CBS_MAX_CT_NODES", &value)?;
+        }
+        if let Some(value) = var("SWOQ_CBS_MAX_EXPANSIONS") {
+            self.cbs.max_expansions = parse_value("SWOQ_CBS_MAX_EXPANSIONS", &value)?;
+        }
+
+        if let Some(value) = var("SWOQ_EVAL_LEVELS") {
+            self.eval.levels = parse_list("SWOQ_EVAL_LEVELS", &value)?;
+        }
+        if let Some(value) = var("SWOQ_EVAL_GAMES") {
+            self.eval.games = parse_value("SWOQ_EVAL_GAMES", &value)?;
+        }
+        if let Some(value) = var("SWOQ_EVAL_SEED") {
+            self.eval.first_seed = parse_value("SWOQ_EVAL_SEED", &value)?;
+        }
+        if let Some(value) = var("SWOQ_EVAL_PLANNERS") {
+            self.eval.planners = parse_planners("SWOQ_EVAL_PLANNERS", &value)?;
+        }
+        if let Some(value) = var("SWOQ_EVAL_REPORT") {
+            self.eval.report = value;
+        }
+        Ok(())
+    }
+
+    /// Check the combined settings before anything connects
+    pub fn validate(&self) -> Result<(), ConfigError> {
+        let replaying = self.connection.replay_file.is_some() && !self.is_evaluation();
+        if !replaying {
+            let connection = &self.connection;
+            for (field, setting, env, flag) in [
+                (&connection.user_id, "connection.user_id", "SWOQ_USER_ID", "--user-id"),
+                (&connection.user_name, "connection.user_name", "SWOQ_USER_NAME", "--user-name"),
+                (&connection.host, "connection.host", "SWOQ_HOST", "--host"),
+            ] {
+                if field.as_deref().is_none_or(|value| value.trim().is_empty()) {
+                    return Err(ConfigError::Missing { setting, env, flag });
+                }
+            }
+        }
+
+        let positive = [
+            ("goap.max_depth", self.goap.max_depth as u64),
+            ("goap.timeout_ms", self.goap.timeout_ms),
+            // Only checked when set
+            ("goap.tick_budget_ms", self.goap.tick_budget_ms.unwrap_or(1)),
+            ("cbs.max_ct_nodes", self.cbs.max_ct_nodes as u64),
+            ("cbs.max_expansions", self.cbs.max_expansions as u64),
+        ];
+        for (setting, value) in positive {
+            if value == 0 {
+                return Err(ConfigError::Invalid {
+                    setting,
+                    reason: "must be greater than 0".to_string(),
+                });
+            }
+        }
+
+        if self.is_evaluation() {
+            if self.eval.games <= 0 {
+                return Err(ConfigError::Invalid {
+                    setting: "eval.games",
+                    reason: format!("must be greater than 0, got {}", self.eval.games),
+                });
+            }
+            if self.eval.planners.is_empty() {
+                return Err(ConfigError::Invalid {
+                    setting: "eval.planners",
+                    reason: "no planners to evaluate".to_string(),
+                });
+            }
+        }
+        self.validate_rl()
+    }
+
+    /// The `rl` planner needs the feature and a checkpoint to load, but only when it plays
+    fn validate_rl(&self) -> Result<(), ConfigError> {
+        let (setting, uses_rl) = match self.is_evaluation() {
+            true => ("eval.planners", self.eval.planners.contains(&PlannerKind::Rl)),
+            false => ("game.planner", self.game.planner == PlannerKind::Rl),
+        };
+        if !uses_rl {
+            return Ok(());
+        }
+        if cfg!(not(feature = "rl")) {
+            return Err(ConfigError::Invalid {
+                setting,
+                reason: "the rl planner needs a build with the rl feature".to_string(),
+            });
+        }
+        #[cfg(feature = "rl")]
+        if let Some(backend) = &self.rl.backend {
+            crate::planners::rl::BackendKind::parse(backend).map_err(|reason| {
+                ConfigError::Invalid {
+                    setting: "rl.backend",
+                    reason,
+                }
+            })?;
+        }
+
+        let checkpoint = format!("{}.mpk", self.rl.model_path);
+        if !Path::new(&checkpoint).is_file() {
+            return Err(ConfigError::Invalid {
+                setting: "rl.model_path",
+                reason: format!("no checkpoint at {}", checkpoint),
+            });
+        }
+        Ok(())
+    }
+
+    pub fn is_evaluation(&self) -> bool {
+        !self.eval.levels.is_empty()
+    }
+
+    /// Settings for a headless evaluation run
+    pub fn eval_config(&self) -> eval::EvalConfig {
+        eval::EvalConfig {
+            planners: self.eval.planners.clone(),
+            levels: self.eval.levels.clone(),
+            games_per_level: self.eval.games,
+            first_seed: self.eval.first_seed,
+            goap: self.goap.clone(),
+            rl: self.rl.clone(),
+            cbs: self.cbs,
+        }
+    }
+}
+
+pub(super) fn parse_value<T: FromStr>(setting: &str, value: &str) -> Result<T, ConfigError> {
+    value.trim().parse().map_err(|_| ConfigError::InvalidValue {
+        setting: setting.to_string(),
+        value: value.to_string(),
+    })
+}
+
+/// Numbers, ranges or both, like `3`, `1-5` or `1,3,7-9`
+pub(super) fn parse_list(setting: &str, value: &str) -> Result<Vec<i32>, ConfigError> {
+    eval::parse_level_list(value).map_err(|_| ConfigError::InvalidValue {
+        setting: setting.to_string(),
+        value: value.to_string(),
+    })
+}
+
+pub(super) fn parse_planner(setting: &str, value: &str) -> Result<PlannerKind, ConfigError> {
+    PlannerKind::parse(value).ok_or_else(|| ConfigError::InvalidValue {
+        setting: setting.to_string(),
+        value: value.to_string(),
+    })
+}
+
+/// Comma separated planner names
+pub(super) fn parse_planners(setting: &str, value: &str) -> Result<Vec<PlannerKind>, ConfigError> {
+    value
+        .split(',')
+        .map(|name| parse_planner(setting, name))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+
+    fn env(vars: &[(&str, &str)]) -> impl Fn(&str) -> Option<String> {
+        let vars: HashMap<String, String> = vars
+            .iter()
+            .map(|(key, value)| (key.to_string(), value.to_string()))
+            .collect();
+        move |key| vars.get(key).cloned()
+    }
+
+    fn parse(text: &str) -> Result<Config, ConfigError> {
+        Config::from_toml(text, Path::new("test.toml"))
+    }
+
+    #[test]
+    fn test_defaults_match_previous_behaviour() {
+        let config = Config::default();
+        assert_eq!(config.game.planner, PlannerKind::Heuristic);
+        assert_eq!(config.game.loop_count, 1);
+        assert_eq!(config.game.observer, ObserverKind::Default);
+        assert_eq!(
+            config.goap,
+            GoapConfig {
+                max_depth: 10,
+                timeout_ms: 5000,
+                tick_budget_ms: None,
+                report_dir: None
+            }
+        );
+        assert_eq!(config.cbs, CbsLimits::default());
+        assert!(!config.is_evaluation());
+    }
+
+    #[test]
+    fn test_parse_file() {
+        let config = parse(
+            r#"
+            [connection]
+            user_id = "id"
+            user_name = "rob"
+            host = "localhost:5001"
+
+            [game]
+            planner = "goap"
+            levels = [3, 4]
+            seeds = [42]
+            loop_count = 0
+            observer = "visualizer"
+            event_log = "run.jsonl"
+
+            [goap]
+            max_depth = 50
+            tick_budget_ms = 20
+
+            [cbs]
+            max_ct_nodes = 200
+            "#,
+        )
+        .unwrap();
+
+        assert_eq!(config.connection.host.as_deref(), Some("localhost:5001"));
+        assert_eq!(config.game.planner, PlannerKind::Goap);
+        assert_eq!(config.game.levels, vec![3, 4]);
+        assert_eq!(config.game.seeds, vec![42]);
+        assert_eq!(config.game.loop_count, 0);
+        assert_eq!(config.game.observer, ObserverKind::Visualizer);
+        assert_eq!(config.game.event_log.as_deref(), Some("run.jsonl"));
+        assert_eq!(
+            config.goap,
+            GoapConfig {
+                max_depth: 50,
+                timeout_ms: 5000,
+                tick_budget_ms: Some(20),
+                report_dir: None
+            }
+        );
+        assert_eq!(config.cbs.max_ct_nodes, 200);
+        assert_eq!(config.cbs.max_expansions, 5000);
+        assert!(config.validate().is_ok());
+    }
+
+    #[test]
+    fn test_example_config_parses() {
+        let text = include_str!("../../robbot.example.toml");
+        let config = parse(text).unwrap();
+        assert_eq!(config.game.planner, PlannerKind::Goap);
+        assert_eq!(config.cbs, CbsLimits::default());
+        assert!(!config.is_evaluation());
+    }
+
+    #[test]
+    fn test_parse_rejects_unknown_settings() {
+        let error = parse("[goap]\nmax_dept = 5\n").unwrap_err();
+        assert!(matches!(error, ConfigError::Parse { .. }));
+        assert!(error.to_string().contains("max_dept"), "{}", error);
+
+        let error = parse("[game]\nplanner = \"random\"\n").unwrap_err();
+        assert!(matches!(error, ConfigError::Parse { .. }));
+    }
+
+    #[test]
+    fn test_env_overrides_file() {
+        let mut config = parse("[game]\nplanner = \"goap\"\nlevels = [1]\n").unwrap();
+        config
+            .apply_env(env(&[
+                ("SWOQ_PLANNER", "heuristic"),
+                ("SWOQ_LEVEL", "5-7"),
+                ("SWOQ_LOOP", "true"),
+                ("SWOQ_GOAP_TIMEOUT_MS", "250"),
+                ("SWOQ_GOAP_TICK_BUDGET_MS", "15"),
+                ("SWOQ_GOAP_REPORT_DIR", "plans"),
+                ("SWOQ_EVAL_PLANNERS", "goap"),
+            ]))
+            .unwrap();
+
+        assert_eq!(config.game.planner, PlannerKind::Heuristic);
+        assert_eq!(config.game.levels, vec![5, 6, 7]);
+        assert_eq!(config.game.loop_count, 0);
+        assert_eq!(config.goap.timeout_ms, 250);
+        assert_eq!(config.goap.tick_budget_ms, Some(15));
+        assert_eq!(config.goap.report_dir.as_deref(), Some("plans"));
+        assert_eq!(config.eval.planners, vec![PlannerKind::Goap]);
+    }
+
+    #[test]
+    fn test_invalid_env_value_is_an_error() {
+        let mut config = Config::default();
+        let error = config
+            .apply_env(env(&[("SWOQ_GOAP_MAX_DEPTH", "deep")]))
+            .unwrap_err();
+        assert_eq!(error.to_string(), "Invalid value \"deep\" for SWOQ_GOAP_MAX_DEPTH");
+    }
+
+    #[test]
+    fn test_validate_reports_missing_connection_settings() {
+        let mut config = Config::default();
+        config.connection.user_id = Some("id".to_string());
+        let error = config.validate().unwrap_err();
+        assert!(matches!(
+            error,
+            ConfigError::Missing {
+                setting: "connection.user_name",
+                ..
+            }
+        ));
+
+        // Replays do not need a server
+        config.connection.replay_file = Some("game.swoq".to_string());
+        assert!(config.validate().is_ok());
+    }
+
+    #[test]
+    fn test_validate_rejects_zero_limits() {
+        let mut config = Config::default();
+        config.connection.replay_file = Some("game.swoq".to_string());
+        config.cbs.max_expansions = 0;
+        let error = config.validate().unwrap_err();
+        assert!(matches!(
+            error,
+            ConfigError::Invalid {
+                setting: "cbs.max_expansions",
+                ..
+            }
+        ));
+
+        config.cbs.max_expansions = 1;
+        config.goap.tick_budget_ms = Some(0);
+        let error = config.validate().unwrap_err();
+        assert!(matches!(
+            error,
+            ConfigError::Invalid {
+                setting: "goap.tick_budget_ms",
+                ..
+            }
+        ));
+    }
+
+    #[test]
+    fn test_validate_rl_planner() {
+        let mut config = parse("[game]\nplanner = \"rl\"\n").unwrap();
+        config.connection.replay_file = Some("game.swoq".to_string());
+        config
+            .apply_env(env(&[("SWOQ_RL_MODEL", "missing/model")]))
+            .unwrap();
+        assert_eq!(config.rl.model_path, "missing/model");
+
+        let error = config.validate().unwrap_err();
+        #[cfg(not(feature = "rl"))]
+        assert!(matches!(
+            error,
+            ConfigError::Invalid {
+                setting: "game.planner",
+                ..
+            }
+        ));
+        #[cfg(feature = "rl")]
+        assert_eq!(error.to_string(), "Invalid rl.model_path: no checkpoint at missing/model.mpk");
+
+        // Only checked when the rl planner plays
+        config.game.planner = PlannerKind::Goap;
+        assert!(config.validate().is_ok());
+    }
+}
@@ -0,0 +1,11 @@
+//! Headless batch evaluation of the planners
+//!
+//! Plays a number of seeded games per level with each planner, collects a
+//! `GameSummary` per game and reports success rate, deaths, tick percentiles
+//! and planning time per level as CSV and JSON.
+
+mod report;
+mod runner;
+
+pub use report::{EvalReport, GameRecord, LevelStats};
+pub use runner::{EvalConfig, PlannerKind, parse_level_list, run_evaluation};
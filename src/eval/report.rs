@@ -0,0 +1,227 @@
+use serde::Serialize;
+use std::fs::File;
+use std::io::{self, Write};
+use std::path::Path;
+
+use crate::eval::PlannerKind;
+use crate::infra::GameSummary;
+
+/// One evaluated game
+#[derive(Debug, Clone, Serialize)]
+pub struct GameRecord {
+    pub planner: String,
+    pub level: i32,
+    pub seed: Option<i32>,
+    pub status: String,
+    pub success: bool,
+    pub ticks: i32,
+    pub deaths: i32,
+    pub planning_ms: f64,
+    /// Why the game could not be played to the end
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+/// Aggregated results of one planner on one level.
+/// Tick percentiles are taken over the successful games only.
+#[derive(Debug, Clone, Serialize)]
+pub struct LevelStats {
+    pub planner: String,
+    pub level: i32,
+    pub games: usize,
+    pub successes: usize,
+    pub success_rate: f64,
+    pub deaths: i32,
+    pub ticks_p50: Option<i32>,
+    pub ticks_p90: Option<i32>,
+    pub ticks_max: Option<i32>,
+    pub mean_planning_ms: f64,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct EvalReport {
+    pub games: Vec<GameRecord>,
+}
+
+#[derive(Serialize)]
+struct JsonReport<'a> {
+    levels: Vec<LevelStats>,
+    games: &'a [GameRecord],
+}
+
+impl EvalReport {
+    pub fn record(&mut self, planner: PlannerKind, level: i32, summary: &GameSummary) {
+        self.games.push(GameRecord {
+            planner: planner.name().to_string(),
+            level,
+            seed: summary.seed,
+            status: format!("{:?}", summary.status),
+            success: summary.is_success(),
+            ticks: summary.ticks,
+            deaths: summary.deaths,
+            planning_ms: summary.planning_time.as_secs_f64() * 1000.0,
+            error: None,
+        });
+    }
+
+    /// Record a game that failed with an error as an unsuccessful game
+    pub fn record_error(&mut self, planner: PlannerKind, level: i32, seed: i32, error: String) {
+        self.games.push(GameRecord {
+            planner: planner.name().to_string(),
+            level,
+            seed: Some(seed),
+            status: "Error".to_string(),
+            success: false,
+            ticks: 0,
+            deaths: 0,
+            planning_ms: 0.0,
+            error: Some(error),
+        });
+    }
+
+    /// Statistics per planner and level, in the order they were first played
+    pub fn level_stats(&self) -> Vec<LevelStats> {
+        let mut keys: Vec<(&str, i32)> = Vec::new();
+        for game in &self.games {
+            let key = (game.planner.as_str(), game.level);
+            if !keys.contains(&key) {
+                keys.push(key);
+            }
+        }
+
+        keys.into_iter()
+            .map(|(planner, level)| {
+                let games: Vec<&GameRecord> = self
+                    .games
+                    .iter()
+                    .filter(|g| g.planner == planner && g.level == level)
+                    .collect();
+                let mut success_ticks: Vec<i32> = games
+                    .iter()
+                    .filter(|g| g.success)
+                    .map(|g| g.ticks)
+                    .collect();
+                success_ticks.sort_unstable();
+
+                LevelStats {
+                    planner: planner.to_string(),
+                    level,
+                    games: games.len(),
+                    successes: success_ticks.len(),
+                    success_rate: success_ticks.len() as f64 / games.len() as f64,
+                    deaths: games.iter().map(|g| g.deaths).sum(),
+                    ticks_p50: percentile(&success_ticks, 50.0),
+                    ticks_p90: percentile(&success_ticks, 90.0),
+                    ticks_max: success_ticks.last().copied(),
+                    mean_planning_ms: games.iter().map(|g| g.planning_ms).sum::<f64>()
+                        / games.len() as f64,
+                }
+            })
+            .collect()
+    }
+
+    /// Write the per-level statistics as CSV
+    pub fn write_csv(&self, path: &Path) -> io::Result<()> {
+        let mut file = File::create(path)?;
+        writeln!(
+            file,
+            "planner,level,games,successes,success_rate,deaths,ticks_p50,ticks_p90,ticks_max,mean_planning_ms"
+        )?;
+        let optional = |value: Option<i32>| value.map(|v| v.to_string()).unwrap_or_default();
+        for stats in self.level_stats() {
+            writeln!(
+                file,
+                "{},{},{},{},{:.4},{},{},{},{},{:.2}",
+                stats.planner,
+                stats.level,
+                stats.games,
+                stats.successes,
+                stats.success_rate,
+                stats.deaths,
+                optional(stats.ticks_p50),
+                optional(stats.ticks_p90),
+                optional(stats.ticks_max),
+                stats.mean_planning_ms
+            )?;
+        }
+        Ok(())
+    }
+
+    /// Write the per-level statistics and every game as JSON
+    pub fn write_json(&self, path: &Path) -> io::Result<()> {
+        let report = JsonReport {
+            levels: self.level_stats(),
+            games: &self.games,
+        };
+        let file = File::create(path)?;
+        serde_json::to_writer_pretty(file, &report)?;
+        Ok(())
+    }
+}
+
+/// Nearest-rank percentile of sorted values
+fn percentile(sorted: &[i32], percent: f64) -> Option<i32> {
+    if sorted.is_empty() {
+        return None;
+    }
+    let rank = (percent / 100.0 * sorted.len() as f64).ceil() as usize;
+    Some(sorted[rank.clamp(1, sorted.len()) - 1])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::swoq_interface::GameStatus;
+    use std::time::Duration;
+
+    fn summary(status: GameStatus, ticks: i32) -> GameSummary {
+        GameSummary {
+            game_id: "game".to_string(),
+            seed: Some(1),
+            level: 4,
+            status,
+            ticks,
+            deaths: i32::from(status == GameStatus::FinishedPlayerDied),
+            planning_time: Duration::from_millis(10),
+        }
+    }
+
+    #[test]
+    fn test_percentile() {
+        assert_eq!(percentile(&[], 50.0), None);
+        assert_eq!(percentile(&[7], 90.0), Some(7));
+        let values: Vec<i32> = (1..=10).collect();
+        assert_eq!(percentile(&values, 50.0), Some(5));
+        assert_eq!(percentile(&values, 90.0), Some(9));
+        assert_eq!(percentile(&values, 100.0), Some(10));
+    }
+
+    #[test]
+    fn test_level_stats_and_csv() {
+        let mut report = EvalReport::default();
+        report.record(PlannerKind::Goap, 4, &summary(GameStatus::FinishedSuccess, 120));
+        report.record(PlannerKind::Goap, 4, &summary(GameStatus::FinishedPlayerDied, 30));
+        report.record(PlannerKind::Goap, 4, &summary(GameStatus::FinishedSuccess, 100));
+        report.record(PlannerKind::Goap, 4, &summary(GameStatus::FinishedTimeout, 2000));
+        report.record(PlannerKind::Heuristic, 4, &summary(GameStatus::FinishedTimeout, 2000));
+
+        let stats = report.level_stats();
+        assert_eq!(stats.len(), 2);
+        assert_eq!(stats[0].planner, "goap");
+        assert_eq!(stats[0].successes, 2);
+        assert_eq!(stats[0].success_rate, 0.5);
+        assert_eq!(stats[0].deaths, 1);
+        assert_eq!(stats[0].ticks_p50, Some(100));
+        assert_eq!(stats[0].ticks_max, Some(120));
+        assert_eq!(stats[1].ticks_p50, None);
+
+        let path = std::env::temp_dir().join(format!("robbot-eval-{}.csv", std::process::id()));
+        report.write_csv(&path).unwrap();
+        let csv = std::fs::read_to_string(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        let lines: Vec<&str> = csv.lines().collect();
+        assert_eq!(lines.len(), 3);
+        assert_eq!(lines[1], "goap,4,4,2,0.5000,1,100,120,120,10.00");
+        assert_eq!(lines[2], "heuristic,4,1,0,0.0000,0,,,,10.00");
+    }
+}
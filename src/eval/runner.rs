@@ -0,0 +1,315 @@
+use std::error::Error;
+use std::future::Future;
+
+use serde::Deserialize;
+
+use crate::config::{GoapConfig, RlConfig};
+use crate::eval::EvalReport;
+use crate::infra::{CbsLimits, GameConnection, GameObserver, GameSummary};
+use crate::planners::goap::GoapPlanner;
+use crate::planners::heuristic::HeuristicPlanner;
+use crate::planners::{GameRunner, Planner};
+use crate::state::WorldState;
+use crate::swoq_interface::{ActResult, DirectedAction, GameStatus, State};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum PlannerKind {
+    Heuristic,
+    Goap,
+    /// Trained MAPPO policy, needs a build with the `rl` feature
+    Rl,
+}
+
+impl PlannerKind {
+    pub fn name(&self) -> &'static str {
+        match self {
+            PlannerKind::Heuristic => "heuristic",
+            PlannerKind::Goap => "goap",
+            PlannerKind::Rl => "rl",
+        }
+    }
+
+    pub fn create(
+        &self,
+        goap: &GoapConfig,
+        rl: &RlConfig,
+    ) -> Result<Box<dyn Planner>, Box<dyn Error>> {
+        match self {
+            PlannerKind::Heuristic => Ok(Box::new(HeuristicPlanner::new())),
+            PlannerKind::Goap => Ok(Box::new(
+                GoapPlanner::new(goap.max_depth)
+                    .with_timeout(goap.timeout_ms)
+                    .with_tick_budget(goap.tick_budget_ms)
+                    .with_report_dir(goap.report_dir.clone()),
+            )),
+            PlannerKind::Rl => create_rl_planner(rl),
+        }
+    }
+
+    pub fn parse(name: &str) -> Option<Self> {
+        match name.trim().to_ascii_lowercase().as_str() {
+            "heuristic" => Some(PlannerKind::Heuristic),
+            "goap" => Some(PlannerKind::Goap),
+            "rl" => Some(PlannerKind::Rl),
+            _ => None,
+        }
+    }
+}
+
+#[cfg(feature = "rl")]
+fn create_rl_planner(config: &RlConfig) -> Result<Box<dyn Planner>, Box<dyn Error>> {
+    use crate::planners::rl::{BackendKind, InferenceConfig, load_planner};
+
+    let backend = match &config.backend {
+        Some(name) => BackendKind::parse(name)?,
+        None => BackendKind::default(),
+    };
+    let inference = InferenceConfig {
+        model_path: config.model_path.clone(),
+        ..Default::default()
+    };
+    Ok(load_planner(backend, inference)?)
+}
+
+#[cfg(not(feature = "rl"))]
+fn create_rl_planner(_config: &RlConfig) -> Result<Box<dyn Planner>, Box<dyn Error>> {
+    Err("The rl planner needs a build with the rl feature".into())
+}
+
+#[derive(Debug, Clone)]
+pub struct EvalConfig {
+    pub planners: Vec<PlannerKind>,
+    pub levels: Vec<i32>,
+    /// Games per level, played with seeds `first_seed..first_seed + games_per_level`
+    pub games_per_level: i32,
+    pub first_seed: i32,
+    pub goap: GoapConfig,
+    pub rl: RlConfig,
+    pub cbs: CbsLimits,
+}
+
+/// Parse a level list such as `3`, `1-5` or `1,3,7-9`
+pub fn parse_level_list(text: &str) -> Result<Vec<i32>, String> {
+    let mut levels = Vec::new();
+    for part in text
+        .split(',')
+        .map(str::trim)
+        .filter(|part| !part.is_empty())
+    {
+        let parse = |value: &str| {
+            value
+                .trim()
+                .parse::<i32>()
+                .map_err(|_| format!("Invalid level {:?} in {:?}", value, text))
+        };
+        match part.split_once('-') {
+            Some((first, last)) => {
+                let (first, last) = (parse(first)?, parse(last)?);
+                if first > last {
+                    return Err(format!("Empty level range {:?}", part));
+                }
+                levels.extend(first..=last);
+            }
+            None => levels.push(parse(part)?),
+        }
+    }
+    if levels.is_empty() {
+        return Err("No levels to evaluate".to_string());
+    }
+    Ok(levels)
+}
+
+/// Play every configured game and collect the results.
+/// `connect` opens a fresh connection for each planner. A game that fails with an
+/// error is recorded as a failed game and the evaluation continues.
+pub async fn run_evaluation<F, Fut>(
+    config: &EvalConfig,
+    mut connect: F,
+) -> Result<EvalReport, Box<dyn Error>>
+where
+    F: FnMut() -> Fut,
+    Fut: Future<Output = Result<GameConnection, Box<dyn Error>>>,
+{
+    let mut report = EvalReport::default();
+
+    for &planner in &config.planners {
+        let connection = connect().await?;
+        let planner_impl = planner.create(&config.goap, &config.rl)?;
+        let mut runner =
+            GameRunner::new(connection, EvalObserver, planner_impl).with_cbs_limits(config.cbs);
+        for (level, seed) in games(config) {
+            match runner.run(Some(level), Some(seed)).await {
+                Ok(summary) => record(&mut report, planner, level, &summary),
+                Err(e) => {
+                    tracing::error!(
+                        "{} level {} seed {} failed: {}",
+                        planner.name(),
+                        level,
+                        seed,
+                        e
+                    );
+                    report.record_error(planner, level, seed, e.to_string());
+                }
+            }
+        }
+    }
+
+    Ok(report)
+}
+
+fn games(config: &EvalConfig) -> impl Iterator<Item = (i32, i32)> + '_ {
+    config.levels.iter().flat_map(move |&level| {
+        (config.first_seed..config.first_seed + config.games_per_level)
+            .map(move |seed| (level, seed))
+    })
+}
+
+fn record(report: &mut EvalReport, planner: PlannerKind, level: i32, summary: &GameSummary) {
+    tracing::info!(
+        "{} level {} seed {:?}: {:?} after {} ticks ({:.1}ms planning)",
+        planner.name(),
+        level,
+        summary.seed,
+        summary.status,
+        summary.ticks,
+        summary.planning_time.as_secs_f64() * 1000.0
+    );
+    report.record(planner, level, summary);
+}
+
+/// Observer that stays quiet, evaluation only needs the game summaries
+struct EvalObserver;
+
+impl GameObserver for EvalObserver {
+    fn on_game_start(
+        &mut self,
+        _game_id: &str,
+        _seed: Option<i32>,
+        _map_width: i32,
+        _map_height: i32,
+        _visibility_range: i32,
+    ) {
+    }
+
+    fn on_new_level(&mut self, _level: i32) {}
+
+    fn on_state_update(
+        &mut self,
+        _state: &State,
+        _world: &WorldState,
+        _game_count: i32,
+        _successful_runs: i32,
+        _failed_runs: i32,
+    ) {
+    }
+
+    fn on_goal_selected(&mut self, _player_index: usize, _goal_name: &str, _world: &WorldState) {}
+
+    fn on_action_selected(&mut self, _action: DirectedAction, _world: &WorldState) {}
+
+    fn on_action_result(
+        &mut self,
+        _action: DirectedAction,
+        _action2: Option<DirectedAction>,
+        _result: ActResult,
+        _world: &WorldState,
+    ) {
+    }
+
+    fn on_game_finished(
+        &mut self,
+        _status: GameStatus,
+        _final_tick: i32,
+        _game_count: i32,
+        _successful_runs: i32,
+        _failed_runs: i32,
+    ) {
+    }
+
+    fn on_oscillation_detected(&mut self, _message: &str) {}
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sim::{Level, ServerConfig, SimServer};
+    use crate::swoq_interface::game_service_server::GameServiceServer;
+    use tonic::transport::Server;
+    use tonic::transport::server::TcpIncoming;
+
+    #[test]
+    fn test_parse_level_list() {
+        assert_eq!(parse_level_list("3").unwrap(), vec![3]);
+        assert_eq!(parse_level_list("1-3, 7").unwrap(), vec![1, 2, 3, 7]);
+        assert!(parse_level_list("").is_err());
+        assert!(parse_level_list("5-2").is_err());
+        assert!(parse_level_list("one").is_err());
+    }
+
+    fn spawn_server(levels: Vec<Level>) -> String {
+        let incoming = TcpIncoming::bind("127.0.0.1:0".parse().unwrap()).unwrap();
+        let host = incoming.local_addr().unwrap().to_string();
+        let server = SimServer::new(levels, ServerConfig::default());
+        tokio::spawn(
+            Server::builder()
+                .add_service(GameServiceServer::new(server))
+                .serve_with_incoming(incoming),
+        );
+        host
+    }
+
+    fn eval_config(planners: Vec<PlannerKind>, levels: Vec<i32>) -> EvalConfig {
+        EvalConfig {
+            planners,
+            levels,
+            games_per_level: 2,
+            first_seed: 10,
+            goap: GoapConfig::default(),
+            rl: RlConfig::default(),
+            cbs: CbsLimits::default(),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_evaluation_against_local_server() {
+        let host = spawn_server(vec![
+            Level::parse(0, "#######\n#1   E#\n#######").unwrap(),
+            Level::parse(1, "#####\n#  E#\n#1###\n#####").unwrap(),
+        ]);
+
+        let config = eval_config(vec![PlannerKind::Heuristic, PlannerKind::Goap], vec![0, 1]);
+        let report = run_evaluation(&config, || {
+            GameConnection::new("eval".to_string(), "eval".to_string(), host.clone(), None)
+        })
+        .await
+        .unwrap();
+
+        assert_eq!(report.games.len(), 8);
+        assert_eq!(report.games[1].seed, Some(11));
+        let stats = report.level_stats();
+        assert_eq!(stats.len(), 4);
+        assert!(stats.iter().all(|s| s.games == 2 && s.success_rate == 1.0));
+    }
+
+    #[tokio::test]
+    async fn test_failed_game_does_not_stop_the_evaluation() {
+        let host = spawn_server(vec![Level::parse(0, "#######\n#1   E#\n#######").unwrap()]);
+
+        // The server does not know level 3, starting its games fails
+        let config = eval_config(vec![PlannerKind::Heuristic], vec![3, 0]);
+        let report = run_evaluation(&config, || {
+            GameConnection::new("eval".to_string(), "eval".to_string(), host.clone(), None)
+        })
+        .await
+        .unwrap();
+
+        assert_eq!(report.games.len(), 4);
+        let (failed, played) = report.games.split_at(2);
+        assert!(failed.iter().all(|g| !g.success && g.error.is_some()));
+        assert!(played.iter().all(|g| g.success && g.error.is_none()));
+        let stats = report.level_stats();
+        assert_eq!((stats[0].level, stats[0].success_rate), (3, 0.0));
+        assert_eq!((stats[1].level, stats[1].success_rate), (0, 1.0));
+    }
+}
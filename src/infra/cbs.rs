@@ -1,7 +1,10 @@
 use std::cmp::Ordering;
 use std::collections::{BinaryHeap, HashMap, HashSet};
 
+use serde::Deserialize;
+
 use crate::infra::Position;
+use crate::infra::enemy_tracker::PREDICTION_HORIZON;
 use crate::state::Map;
 
 // ============================================================================
@@ -16,6 +19,25 @@ pub struct Agent {
     pub goal: Position,
 }
 
+/// Search limits for CBS, the search gives up and reports no solution when one is exceeded
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct CbsLimits {
+    /// Constraint tree nodes expanded before the high-level search gives up
+    pub max_ct_nodes: usize,
+    /// A* expansions per low-level search
+    pub max_expansions: usize,
+}
+
+impl Default for CbsLimits {
+    fn default() -> Self {
+        Self {
+            max_ct_nodes: 1000,
+            max_expansions: 5000,
+        }
+    }
+}
+
 /// Represents a constraint on an agent's movement
 #[derive(Clone, Debug, Eq, PartialEq, Hash)]
 enum Constraint {
@@ -125,26 +147,30 @@ fn heuristic(a: Position, b: Position) -> i32 {
 }
 
 fn reconstruct_path(
-    came_from: &HashMap<Position, Position>,
-    mut current: Position,
+    came_from: &HashMap<(Position, i32), (Position, i32)>,
+    mut current: (Position, i32),
 ) -> Vec<Position> {
-    let mut path = vec![current];
+    let mut path = vec![current.0];
     while let Some(&prev) = came_from.get(&current) {
-        path.push(prev);
+        path.push(prev.0);
         current = prev;
     }
     path.reverse();
     path
 }
 
-/// A* pathfinding with CBS constraints
+/// Space-time A* pathfinding with CBS constraints
+///
+/// The agent may wait in place, so the search runs over (position, tick). Constraints and the
+/// predicted enemy costs only change up to a horizon, later ticks are folded into one to keep
+/// the search finite. The goal only counts as reached once no constraint puts the agent off it again.
 fn find_path_with_constraints(
     map: &Map,
     start: Position,
     goal: Position,
     agent_id: usize,
     constraints: &[Constraint],
-    is_walkable: &dyn Fn(&Position, usize, Position) -> bool,
+    env: &PathfindingEnv,
 ) -> Option<Vec<Position>> {
     tracing::trace!(
         "CBS A*: Finding path for agent {} from {:?} to {:?} with {} constraints",
@@ -153,25 +179,42 @@ fn find_path_with_constraints(
         goal,
         constraints.len()
     );
-    
-    if start == goal {
-        tracing::trace!("CBS A*: Agent {} already at goal", agent_id);
-        return Some(vec![goal]);
+
+    if violates_constraints(&start, &start, 0, constraints) {
+        return None;
     }
 
+    let horizon = constraints
+        .iter()
+        .map(|c| match c {
+            Constraint::Vertex { time, .. } | Constraint::Edge { time, .. } => *time,
+        })
+        .max()
+        .unwrap_or(0);
+    let cost_horizon = horizon.max(PREDICTION_HORIZON as i32);
+    let goal_blocked_until = constraints
+        .iter()
+        .filter_map(|c| match c {
+            Constraint::Vertex { pos, time, .. } if *pos == goal => Some(*time),
+            _ => None,
+        })
+        .max()
+        .unwrap_or(-1);
+    let fold = |tick: i32| tick.min(cost_horizon + 1);
+
     let mut open_set = BinaryHeap::new();
-    let mut came_from: HashMap<Position, Position> = HashMap::new();
-    let mut g_score: HashMap<Position, i32> = HashMap::new();
-    let mut closed_set: HashSet<Position> = HashSet::new();
+    let mut came_from: HashMap<(Position, i32), (Position, i32)> = HashMap::new();
+    let mut g_score: HashMap<(Position, i32), i32> = HashMap::new();
+    let mut closed_set: HashSet<(Position, i32)> = HashSet::new();
 
-    g_score.insert(start, 0);
+    g_score.insert((start, 0), 0);
     open_set.push(AStarNode {
         pos: start,
         f_score: heuristic(start, goal),
         tick: 0,
     });
 
-    const MAX_EXPANSIONS: usize = 5000;
+    let max_expansions = env.limits.max_expansions;
     let mut expansions = 0;
 
     while let Some(AStarNode {
@@ -180,36 +223,40 @@ fn find_path_with_constraints(
         ..
     }) = open_set.pop()
     {
-        if current == goal {
+        let current_key = (current, fold(current_tick));
+        if current == goal && current_tick > goal_blocked_until {
             tracing::trace!(
                 "CBS A*: Agent {} reached goal after {} expansions",
                 agent_id,
                 expansions
             );
-            return Some(reconstruct_path(&came_from, current));
+            return Some(reconstruct_path(&came_from, current_key));
         }
 
-        if closed_set.contains(&current) {
+        if !closed_set.insert(current_key) {
             continue;
         }
-        closed_set.insert(current);
 
         expansions += 1;
-        if expansions > MAX_EXPANSIONS {
+        if expansions > max_expansions {
             tracing::warn!(
-                "CBS A*: Agent {} exceeded MAX_EXPANSIONS ({}) - from {:?} to {:?}",
+                "CBS A*: Agent {} exceeded max expansions ({}) - from {:?} to {:?}",
                 agent_id,
-                MAX_EXPANSIONS,
+                max_expansions,
                 start,
                 goal
             );
             return None;
         }
 
-        let current_g_score = *g_score.get(&current).unwrap_or(&0);
+        let current_g_score = g_score[&current_key];
+        let next_tick = current_tick + 1;
 
-        for neighbor in current.neighbors() {
-            if closed_set.contains(&neighbor) {
+        // Waiting only helps while constraints or enemy predictions are ahead
+        let wait = (next_tick <= cost_horizon + 1).then_some(current);
+        for neighbor in current.neighbors().into_iter().chain(wait) {
+            let neighbor_key = (neighbor, fold(next_tick));
+            if closed_set.contains(&neighbor_key) {
                 continue;
             }
 
@@ -222,22 +269,20 @@ fn find_path_with_constraints(
             }
 
             // Check walkability for this agent
-            if !is_walkable(&neighbor, agent_id, goal) {
+            if neighbor != current && !(env.is_walkable)(&neighbor, agent_id, goal) {
                 continue;
             }
 
-            let next_tick = current_tick + 1;
-
             // Check CBS constraints
             if violates_constraints(&neighbor, &current, next_tick, constraints) {
                 continue;
             }
 
-            let tentative_g = current_g_score + 1; // Unit cost
+            let tentative_g = current_g_score + (env.step_cost)(&neighbor, agent_id, next_tick);
 
-            if tentative_g < *g_score.get(&neighbor).unwrap_or(&i32::MAX) {
-                came_from.insert(neighbor, current);
-                g_score.insert(neighbor, tentative_g);
+            if tentative_g < *g_score.get(&neighbor_key).unwrap_or(&i32::MAX) {
+                came_from.insert(neighbor_key, current_key);
+                g_score.insert(neighbor_key, tentative_g);
                 open_set.push(AStarNode {
                     pos: neighbor,
                     f_score: tentative_g + heuristic(neighbor, goal),
@@ -268,10 +313,12 @@ fn violates_constraints(
     })
 }
 
-/// Pathfinding environment (map and walkability function)
+/// Pathfinding environment (map, walkability and step cost functions)
 struct PathfindingEnv<'a> {
     map: &'a Map,
     is_walkable: &'a dyn Fn(&Position, usize, Position) -> bool,
+    step_cost: &'a dyn Fn(&Position, usize, i32) -> i32,
+    limits: CbsLimits,
 }
 
 // ============================================================================
@@ -294,6 +341,32 @@ impl CBS {
     pub fn find_paths<F>(map: &Map, agents: &[Agent], is_walkable: F) -> Option<Vec<Vec<Position>>>
     where
         F: Fn(&Position, usize, Position) -> bool,
+    {
+        Self::find_paths_with_cost(
+            map,
+            agents,
+            is_walkable,
+            |_pos, _agent_id, _tick| 1,
+            CbsLimits::default(),
+        )
+    }
+
+    /// Find collision-free paths for multiple agents with a custom cost for each step
+    ///
+    /// The low-level search minimizes the summed step cost, so agents can prefer paths that
+    /// avoid expensive tiles (e.g. near predicted enemy positions).
+    /// `step_cost` receives (position, agent_id, tick_from_start) and returns the cost to enter
+    /// that position. Costs must be at least 1. `limits` bounds the search effort.
+    pub fn find_paths_with_cost<F, C>(
+        map: &Map,
+        agents: &[Agent],
+        is_walkable: F,
+        step_cost: C,
+        limits: CbsLimits,
+    ) -> Option<Vec<Vec<Position>>>
+    where
+        F: Fn(&Position, usize, Position) -> bool,
+        C: Fn(&Position, usize, i32) -> i32,
     {
         tracing::debug!("CBS: Starting with {} agents", agents.len());
         for agent in agents.iter() {
@@ -329,6 +402,8 @@ impl CBS {
         let env = PathfindingEnv {
             map,
             is_walkable: &is_walkable,
+            step_cost: &step_cost,
+            limits,
          };
         
         tracing::debug!("CBS: Finding initial paths for all agents");
@@ -346,7 +421,7 @@ impl CBS {
         tracing::debug!("CBS: All initial paths found, starting conflict resolution");
         open.push(root);
 
-        const MAX_CT_NODES: usize = 1000;
+        let max_ct_nodes = limits.max_ct_nodes;
         let mut nodes_expanded = 0;
 
         while let Some(node) = open.pop() {
@@ -354,11 +429,11 @@ impl CBS {
             tracing::debug!(
                 "CBS: Expanding CT node {}/{} with cost {}",
                 nodes_expanded,
-                MAX_CT_NODES,
+                max_ct_nodes,
                 node.cost
             );
             
-            if nodes_expanded > MAX_CT_NODES {
+            if nodes_expanded > max_ct_nodes {
                 tracing::warn!("CBS: Timeout - expanded {} nodes", nodes_expanded);
                 return None; // Timeout
             }
@@ -605,7 +680,7 @@ impl CBS {
             agent.goal,
             agent.id,
             &agent_constraints,
-            &env.is_walkable,
+            env,
         )?;
 
         tracing::trace!(
@@ -624,6 +699,12 @@ impl CBS {
 
 #[cfg(test)]
 mod tests {
+    use std::cmp::Reverse;
+
+    use rand::rngs::StdRng;
+    use rand::seq::IndexedRandom;
+    use rand::{Rng, SeedableRng};
+
     use super::*;
     use crate::swoq_interface::Tile;
 
@@ -1259,4 +1340,231 @@ mod tests {
         assert!(result.is_some(), "Should handle empty agent list");
         assert_eq!(result.unwrap().len(), 0);
     }
+
+    // ------------------------------------------------------------------------
+    // Properties on random maps and agents, checked against a brute force search
+    // ------------------------------------------------------------------------
+
+    /// Random map with walls around it and about `wall_percent` walls inside
+    fn random_map(rng: &mut StdRng, width: i32, height: i32, wall_percent: u32) -> Map {
+        let mut map = Map::new(width, height);
+        for y in 0..height {
+            for x in 0..width {
+                let border = x == 0 || y == 0 || x == width - 1 || y == height - 1;
+                let tile = if border || rng.random_range(0..100) < wall_percent {
+                    Tile::Wall
+                } else {
+                    Tile::Empty
+                };
+                map.insert(Position::new(x, y), tile);
+            }
+        }
+        map
+    }
+
+    /// Agents on distinct random floor tiles, two agents may share a goal
+    fn random_agents(rng: &mut StdRng, map: &Map, count: usize) -> Vec<Agent> {
+        let mut floor: Vec<Position> = map
+            .iter()
+            .filter(|(_, tile)| **tile == Tile::Empty)
+            .map(|(pos, _)| *pos)
+            .collect();
+        floor.sort_by_key(|pos| (pos.y, pos.x));
+        let starts: Vec<Position> = floor.choose_multiple(rng, count).copied().collect();
+        starts
+            .into_iter()
+            .enumerate()
+            .map(|(id, start)| Agent {
+                id,
+                start,
+                goal: *floor.choose(rng).unwrap(),
+            })
+            .collect()
+    }
+
+    /// The conflict between agents `i < j` stepping from `before` to `after`, by the
+    /// rules of `find_first_conflict`
+    fn conflict(
+        agents: &[Agent],
+        i: usize,
+        j: usize,
+        before: &[Position],
+        after: &[Position],
+    ) -> Option<&'static str> {
+        if after[i] == after[j] {
+            let shared_goal = agents[i].goal == agents[j].goal && after[i] == agents[i].goal;
+            return (!shared_goal).then_some("vertex");
+        }
+        if after[i] == before[j] && after[j] == before[i] {
+            return Some("edge");
+        }
+        (after[i] == before[j]).then_some("sequential")
+    }
+
+    fn find_conflict(
+        agents: &[Agent],
+        before: &[Position],
+        after: &[Position],
+    ) -> Option<(usize, usize, &'static str)> {
+        (0..agents.len()).find_map(|j| {
+            (0..j).find_map(|i| conflict(agents, i, j, before, after).map(|kind| (i, j, kind)))
+        })
+    }
+
+    /// Lowest sum of costs of a conflict-free plan, by Dijkstra over the joint positions of
+    /// the agents. Agents may wait, and stop paying once they settle on their goal for good.
+    fn brute_force_cost(map: &Map, agents: &[Agent]) -> Option<i32> {
+        let starts: Vec<Position> = agents.iter().map(|agent| agent.start).collect();
+        if find_conflict(agents, &starts, &starts).is_some() {
+            return None;
+        }
+        let all_settled = (1u32 << agents.len()) - 1;
+        let mut states = vec![(starts, 0u32)];
+        let mut best = HashMap::from([(states[0].clone(), 0)]);
+        let mut open = BinaryHeap::from([Reverse((0, 0usize))]);
+
+        while let Some(Reverse((cost, index))) = open.pop() {
+            let (positions, settled) = states[index].clone();
+            if best[&(positions.clone(), settled)] < cost {
+                continue;
+            }
+            if settled == all_settled {
+                return Some(cost);
+            }
+            let mut push = |state: (Vec<Position>, u32), cost: i32| {
+                if best.get(&state).is_none_or(|&known| cost < known) {
+                    best.insert(state.clone(), cost);
+                    states.push(state);
+                    open.push(Reverse((cost, states.len() - 1)));
+                }
+            };
+
+            for (i, agent) in agents.iter().enumerate() {
+                if settled & (1 << i) == 0 && positions[i] == agent.goal {
+                    push((positions.clone(), settled | (1 << i)), cost);
+                }
+            }
+
+            // Every combination of a wait or a step for the agents that did not settle
+            let options: Vec<Vec<Position>> = (0..agents.len())
+                .map(|i| {
+                    let mut options = vec![positions[i]];
+                    if settled & (1 << i) == 0 {
+                        options.extend(
+                            positions[i]
+                                .neighbors()
+                                .into_iter()
+                                .filter(|pos| map.get(pos) == Some(&Tile::Empty)),
+                        );
+                    }
+                    options
+                })
+                .collect();
+            let moving = (0..agents.len())
+                .filter(|i| settled & (1 << i) == 0)
+                .count() as i32;
+            let mut choice = vec![0; agents.len()];
+            loop {
+                let after: Vec<Position> =
+                    (0..agents.len()).map(|i| options[i][choice[i]]).collect();
+                if find_conflict(agents, &positions, &after).is_none() {
+                    push((after, settled), cost + moving);
+                }
+                let Some(i) = (0..agents.len()).find(|&i| choice[i] + 1 < options[i].len()) else {
+                    break;
+                };
+                choice[i] += 1;
+                choice[..i].fill(0);
+            }
+        }
+        None
+    }
+
+    fn assert_valid_solution(map: &Map, agents: &[Agent], paths: &[Vec<Position>], seed: u64) {
+        assert_eq!(paths.len(), agents.len(), "seed {}", seed);
+        for (agent, path) in agents.iter().zip(paths) {
+            assert_eq!(path.first(), Some(&agent.start), "seed {}: agent {} start", seed, agent.id);
+            assert_eq!(path.last(), Some(&agent.goal), "seed {}: agent {} goal", seed, agent.id);
+            for step in path.windows(2) {
+                assert!(
+                    step[0] == step[1] || step[0].is_adjacent(&step[1]),
+                    "seed {}: agent {} jumps from {:?} to {:?}",
+                    seed,
+                    agent.id,
+                    step[0],
+                    step[1]
+                );
+                assert_eq!(
+                    map.get(&step[1]),
+                    Some(&Tile::Empty),
+                    "seed {}: agent {} steps on {:?}",
+                    seed,
+                    agent.id,
+                    step[1]
+                );
+            }
+        }
+
+        let at = |t: usize| -> Vec<Position> {
+            paths
+                .iter()
+                .map(|path| path[t.min(path.len() - 1)])
+                .collect()
+        };
+        let horizon = paths.iter().map(Vec::len).max().unwrap_or(0);
+        for t in 0..horizon {
+            let before = at(t.saturating_sub(1));
+            if let Some((i, j, kind)) = find_conflict(agents, &before, &at(t)) {
+                panic!(
+                    "seed {}: {} conflict between agents {} and {} at t={}",
+                    seed, kind, i, j, t
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_cbs_matches_brute_force_on_random_instances() {
+        // CBS only gives up at its limits. These solve every solvable instance below, so
+        // None is exact: CBS returns it for the instances without a conflict-free plan.
+        const LIMITS: CbsLimits = CbsLimits {
+            max_ct_nodes: 10_000,
+            max_expansions: 100_000,
+        };
+        for seed in 0..200 {
+            let mut rng = StdRng::seed_from_u64(seed);
+            let map = random_map(&mut rng, 7, 6, 20);
+            // The brute force is slow for three agents, keep those to a quarter
+            let count = if seed % 4 == 0 { 3 } else { 2 };
+            let agents = random_agents(&mut rng, &map, count);
+            if agents.len() < count {
+                continue;
+            }
+            let is_walkable = |pos: &Position, _agent_id: usize, _goal: Position| {
+                map.get(pos) == Some(&Tile::Empty)
+            };
+
+            let expected = brute_force_cost(&map, &agents);
+            let paths = CBS::find_paths_with_cost(
+                &map,
+                &agents,
+                is_walkable,
+                |_pos, _agent_id, _tick| 1,
+                LIMITS,
+            );
+
+            match (paths, expected) {
+                (Some(paths), Some(cost)) => {
+                    assert_valid_solution(&map, &agents, &paths, seed);
+                    let cbs_cost: usize = paths.iter().map(|path| path.len() - 1).sum();
+                    assert_eq!(cbs_cost as i32, cost, "seed {}: CBS solution is not optimal", seed);
+                }
+                (None, None) => {}
+                (paths, cost) => panic!(
+                    "seed {}: CBS found {:?} where the brute force cost is {:?}\n{:?}",
+                    seed, paths, cost, agents
+                ),
+            }
+        }
+    }
 }
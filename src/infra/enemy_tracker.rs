@@ -0,0 +1,596 @@
+use std::collections::{HashMap, HashSet, VecDeque};
+
+use tracing::debug;
+
+use crate::infra::{Bounds, Position, ReservationTable};
+use crate::state::Map;
+use crate::swoq_interface::Tile;
+
+/// Number of observations kept per enemy to estimate its movement
+const HISTORY_LEN: usize = 8;
+
+/// Enemies that have not been seen for this many ticks are forgotten
+const FORGET_AFTER_TICKS: i32 = 12;
+
+/// Number of ticks ahead that threat predictions are computed for
+pub const PREDICTION_HORIZON: usize = 8;
+
+/// Predictions of enemies with uncertain whereabouts are capped to this many steps,
+/// beyond that the whole neighbourhood would be flagged and the prediction becomes useless
+const MAX_UNCERTAIN_RADIUS: i32 = 3;
+
+/// How an enemy has been observed to move
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MovementPattern {
+    /// Not enough observations yet
+    Unknown,
+    /// Has not moved while being watched
+    Stationary,
+    /// Moves towards the closest player
+    Chasing,
+    /// Moves, but not consistently towards a player
+    Wandering,
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Observation {
+    tick: i32,
+    position: Position,
+    /// Distance to the closest player at the time of the observation
+    player_distance: i32,
+}
+
+/// An enemy with an identity that is kept across ticks
+#[derive(Debug, Clone)]
+pub struct TrackedEnemy {
+    pub id: usize,
+    pub is_boss: bool,
+    /// Last known position
+    pub position: Position,
+    pub last_seen_tick: i32,
+    pub pattern: MovementPattern,
+    /// Observed tiles moved per tick (0.0 - 1.0)
+    pub speed: f32,
+    history: VecDeque<Observation>,
+}
+
+impl TrackedEnemy {
+    fn new(id: usize, is_boss: bool, observation: Observation) -> Self {
+        Self {
+            id,
+            is_boss,
+            position: observation.position,
+            last_seen_tick: observation.tick,
+            pattern: MovementPattern::Unknown,
+            speed: 1.0,
+            history: VecDeque::from([observation]),
+        }
+    }
+
+    fn observe(&mut self, observation: Observation) {
+        self.position = observation.position;
+        self.last_seen_tick = observation.tick;
+        self.history.push_back(observation);
+        if self.history.len() > HISTORY_LEN {
+            self.history.pop_front();
+        }
+        self.estimate_movement();
+    }
+
+    /// Estimate the movement pattern and speed from the observation history
+    fn estimate_movement(&mut self) {
+        let (Some(first), Some(last)) = (self.history.front(), self.history.back()) else {
+            return;
+        };
+        let observed_ticks = last.tick - first.tick;
+        if observed_ticks < 2 {
+            return;
+        }
+
+        let mut moves = 0;
+        let mut approaching = 0;
+        for (prev, next) in self.history.iter().zip(self.history.iter().skip(1)) {
+            if prev.position != next.position {
+                moves += 1;
+                if next.player_distance < prev.player_distance {
+                    approaching += 1;
+                }
+            }
+        }
+
+        self.speed = (moves as f32 / observed_ticks as f32).min(1.0);
+        self.pattern = if moves == 0 {
+            MovementPattern::Stationary
+        } else if approaching * 2 > moves {
+            MovementPattern::Chasing
+        } else {
+            MovementPattern::Wandering
+        };
+    }
+
+    /// Number of tiles the enemy may have moved `ticks` after it was last seen
+    fn reach(&self, ticks: i32) -> i32 {
+        if self.pattern == MovementPattern::Stationary {
+            return 0;
+        }
+        // Unobserved enemies are assumed to move every tick
+        let speed = if self.pattern == MovementPattern::Unknown {
+            1.0
+        } else {
+            self.speed
+        };
+        ((ticks as f32 * speed).ceil() as i32).min(MAX_UNCERTAIN_RADIUS)
+    }
+}
+
+/// Tracks enemies (and the boss) across ticks, giving each a stable identity.
+/// Enemies are matched to their previous position by distance, since they move
+/// at most one tile per tick.
+#[derive(Debug, Clone, Default)]
+pub struct EnemyTracker {
+    enemies: Vec<TrackedEnemy>,
+    next_id: usize,
+}
+
+impl EnemyTracker {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Update the tracker with the enemies currently in view
+    #[tracing::instrument(level = "trace", skip_all, fields(seen_count = seen_enemies.len()))]
+    pub fn update(
+        &mut self,
+        tick: i32,
+        seen_enemies: &[Position],
+        seen_boss: Option<Position>,
+        player_positions: &[Position],
+        all_visibility_bounds: &[Bounds],
+    ) {
+        let player_distance = |pos: Position| {
+            player_positions
+                .iter()
+                .map(|p| p.distance(&pos))
+                .min()
+                .unwrap_or(i32::MAX)
+        };
+
+        let mut sightings: Vec<(Position, bool)> =
+            seen_enemies.iter().map(|&pos| (pos, false)).collect();
+        sightings.extend(seen_boss.map(|pos| (pos, true)));
+
+        // Greedily match sightings to known enemies, closest pairs first
+        let mut pairs: Vec<(i32, usize, usize)> = Vec::new();
+        for (enemy_idx, enemy) in self.enemies.iter().enumerate() {
+            let max_distance = (tick - enemy.last_seen_tick).max(1);
+            for (sighting_idx, &(pos, is_boss)) in sightings.iter().enumerate() {
+                let distance = enemy.position.distance(&pos);
+                if is_boss == enemy.is_boss && distance <= max_distance {
+                    pairs.push((distance, enemy_idx, sighting_idx));
+                }
+            }
+        }
+        pairs.sort();
+
+        let mut matched_enemies = HashSet::new();
+        let mut matched_sightings = HashSet::new();
+        for (_, enemy_idx, sighting_idx) in pairs {
+            if matched_enemies.contains(&enemy_idx) || matched_sightings.contains(&sighting_idx) {
+                continue;
+            }
+            matched_enemies.insert(enemy_idx);
+            matched_sightings.insert(sighting_idx);
+            let position = sightings[sighting_idx].0;
+            self.enemies[enemy_idx].observe(Observation {
+                tick,
+                position,
+                player_distance: player_distance(position),
+            });
+        }
+
+        // Enemies that were not seen are kept until they could be anywhere,
+        // or until every tile they could have reached is in view (and they were not there)
+        let is_visible = |pos: &Position| all_visibility_bounds.iter().any(|b| b.contains(pos));
+        let mut index = 0;
+        self.enemies.retain(|enemy| {
+            let seen = matched_enemies.contains(&index);
+            index += 1;
+            if seen {
+                return true;
+            }
+            let unseen_ticks = tick - enemy.last_seen_tick;
+            let radius = enemy.reach(unseen_ticks);
+            let all_in_view = (-radius..=radius).all(|dx| {
+                let dy_range = radius - dx.abs();
+                (-dy_range..=dy_range).all(|dy| {
+                    is_visible(&Position::new(enemy.position.x + dx, enemy.position.y + dy))
+                })
+            });
+            let keep = unseen_ticks <= FORGET_AFTER_TICKS && !all_in_view;
+            if !keep {
+                debug!("Forgetting enemy {} last seen at {:?}", enemy.id, enemy.position);
+            }
+            keep
+        });
+
+        // New enemies
+        for (sighting_idx, &(position, is_boss)) in sightings.iter().enumerate() {
+            if matched_sightings.contains(&sighting_idx) {
+                continue;
+            }
+            debug!("New enemy {} at {:?} (boss={})", self.next_id, position, is_boss);
+            self.enemies.push(TrackedEnemy::new(
+                self.next_id,
+                is_boss,
+                Observation {
+                    tick,
+                    position,
+                    player_distance: player_distance(position),
+                },
+            ));
+            self.next_id += 1;
+        }
+    }
+
+    pub fn enemies(&self) -> &[TrackedEnemy] {
+        &self.enemies
+    }
+
+    pub fn get(&self, id: usize) -> Option<&TrackedEnemy> {
+        self.enemies.iter().find(|enemy| enemy.id == id)
+    }
+
+    /// Predict where the tracked enemies can be over the next `PREDICTION_HORIZON` ticks
+    #[tracing::instrument(level = "trace", skip_all, fields(enemy_count = self.enemies.len()))]
+    pub fn predict(&self, map: &Map, tick: i32, player_positions: &[Position]) -> ThreatMap {
+        let mut occupied = vec![HashSet::new(); PREDICTION_HORIZON + 1];
+        for enemy in &self.enemies {
+            let predicted = Self::predict_enemy(enemy, map, tick, player_positions);
+            for (positions, enemy_positions) in occupied.iter_mut().zip(predicted) {
+                positions.extend(enemy_positions);
+            }
+        }
+        ThreatMap { occupied }
+    }
+
+    /// Reservations for the enemy positions that are predicted with certainty
+    /// (a single possible tile), for space-time pathfinding
+    pub fn reservations(
+        &self,
+        map: &Map,
+        tick: i32,
+        player_positions: &[Position],
+    ) -> ReservationTable {
+        let mut reservations = ReservationTable::new();
+        for enemy in &self.enemies {
+            let predicted = Self::predict_enemy(enemy, map, tick, player_positions);
+            let mut previous = None;
+            for (offset, positions) in predicted.iter().enumerate() {
+                let [pos] = positions.iter().copied().collect::<Vec<_>>()[..] else {
+                    break;
+                };
+                match previous {
+                    Some(from) => reservations.reserve_move(from, pos, offset as i32),
+                    None => reservations.reserve(pos, offset as i32),
+                }
+                previous = Some(pos);
+            }
+        }
+        reservations
+    }
+
+    /// Possible positions of one enemy per tick offset from now
+    fn predict_enemy(
+        enemy: &TrackedEnemy,
+        map: &Map,
+        tick: i32,
+        player_positions: &[Position],
+    ) -> Vec<HashSet<Position>> {
+        let unseen_ticks = tick - enemy.last_seen_tick;
+        let chase_target = player_positions
+            .iter()
+            .min_by_key(|p| p.distance(&enemy.position))
+            .copied();
+
+        match (enemy.pattern, chase_target) {
+            (MovementPattern::Chasing, Some(target)) if unseen_ticks == 0 => {
+                // Follow the shortest path towards the closest player
+                let path = chase_path(map, enemy.position, target);
+                (0..=PREDICTION_HORIZON)
+                    .map(|offset| {
+                        let steps = (offset as f32 * enemy.speed).ceil() as usize;
+                        HashSet::from([path[steps.min(path.len() - 1)]])
+                    })
+                    .collect()
+            }
+            _ => (0..=PREDICTION_HORIZON)
+                .map(|offset| {
+                    let radius = enemy.reach(unseen_ticks + offset as i32);
+                    reachable_within(map, enemy.position, radius)
+                })
+                .collect(),
+        }
+    }
+}
+
+/// Predicted enemy positions per tick offset from now
+#[derive(Debug, Clone, Default)]
+pub struct ThreatMap {
+    occupied: Vec<HashSet<Position>>,
+}
+
+impl ThreatMap {
+    /// Positions an enemy may occupy `ticks_ahead` ticks from now.
+    /// Offsets beyond the prediction horizon use the last prediction.
+    pub fn positions_at(&self, ticks_ahead: i32) -> Option<&HashSet<Position>> {
+        let index = (ticks_ahead.max(0) as usize).min(self.occupied.len().saturating_sub(1));
+        self.occupied.get(index)
+    }
+
+    pub fn is_occupied(&self, pos: &Position, ticks_ahead: i32) -> bool {
+        self.positions_at(ticks_ahead)
+            .is_some_and(|positions| positions.contains(pos))
+    }
+
+    /// True if an enemy may be on or next to `pos` at the given tick offset
+    pub fn is_threatened(&self, pos: &Position, ticks_ahead: i32) -> bool {
+        self.positions_at(ticks_ahead).is_some_and(|positions| {
+            positions.contains(pos) || pos.neighbors().iter().any(|n| positions.contains(n))
+        })
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.occupied.iter().all(|positions| positions.is_empty())
+    }
+}
+
+/// Enemies can walk on floor-like tiles, and may be anywhere we have not seen
+fn is_enemy_walkable(map: &Map, pos: &Position) -> bool {
+    if pos.x < 0 || pos.x >= map.width || pos.y < 0 || pos.y >= map.height {
+        return false;
+    }
+    matches!(
+        map.get(pos),
+        Some(
+            Tile::Empty
+                | Tile::Player
+                | Tile::Enemy
+                | Tile::Boss
+                | Tile::Unknown
+                | Tile::PressurePlateRed
+                | Tile::PressurePlateGreen
+                | Tile::PressurePlateBlue
+        ) | None
+    )
+}
+
+/// All tiles an enemy can reach from `start` in at most `radius` steps
+fn reachable_within(map: &Map, start: Position, radius: i32) -> HashSet<Position> {
+    let mut reached = HashSet::from([start]);
+    let mut queue = VecDeque::from([(start, 0)]);
+    while let Some((current, distance)) = queue.pop_front() {
+        if distance == radius {
+            continue;
+        }
+        for neighbor in current.neighbors() {
+            if !reached.contains(&neighbor) && is_enemy_walkable(map, &neighbor) {
+                reached.insert(neighbor);
+                queue.push_back((neighbor, distance + 1));
+            }
+        }
+    }
+    reached
+}
+
+/// Shortest enemy path from `start` up to the tile next to `target`, starting with `start`.
+/// Returns just `start` when the target cannot be reached.
+fn chase_path(map: &Map, start: Position, target: Position) -> Vec<Position> {
+    let mut came_from: HashMap<Position, Position> = HashMap::new();
+    let mut queue = VecDeque::from([start]);
+    came_from.insert(start, start);
+
+    while let Some(current) = queue.pop_front() {
+        if current.is_adjacent(&target) {
+            let mut path = vec![current];
+            let mut step = current;
+            while came_from[&step] != step {
+                step = came_from[&step];
+                path.push(step);
+            }
+            path.reverse();
+            return path;
+        }
+        for neighbor in current.neighbors() {
+            if !came_from.contains_key(&neighbor) && is_enemy_walkable(map, &neighbor) {
+                came_from.insert(neighbor, current);
+                queue.push_back(neighbor);
+            }
+        }
+    }
+    vec![start]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sim::{Level, SimConfig, Simulator};
+    use crate::state::WorldState;
+    use crate::swoq_interface::{ActResult, DirectedAction};
+
+    fn open_map(width: i32, height: i32) -> Map {
+        let mut map = Map::new(width, height);
+        for x in 0..width {
+            for y in 0..height {
+                map.insert(Position::new(x, y), Tile::Empty);
+            }
+        }
+        map
+    }
+
+    fn everything_visible() -> Vec<Bounds> {
+        vec![Bounds::new(0, 20, 0, 20)]
+    }
+
+    #[test]
+    fn test_identity_kept_across_moves() {
+        let mut tracker = EnemyTracker::new();
+        let bounds = everything_visible();
+        let player = [Position::new(0, 0)];
+        tracker.update(0, &[Position::new(5, 5), Position::new(9, 9)], None, &player, &bounds);
+        let ids: Vec<usize> = tracker.enemies().iter().map(|e| e.id).collect();
+
+        tracker.update(1, &[Position::new(9, 8), Position::new(5, 4)], None, &player, &bounds);
+        assert_eq!(tracker.enemies().len(), 2);
+        assert_eq!(tracker.get(ids[0]).unwrap().position, Position::new(5, 4));
+        assert_eq!(tracker.get(ids[1]).unwrap().position, Position::new(9, 8));
+    }
+
+    #[test]
+    fn test_movement_pattern_estimation() {
+        let mut tracker = EnemyTracker::new();
+        let bounds = everything_visible();
+        let player = [Position::new(0, 5)];
+        for tick in 0..4 {
+            tracker.update(
+                tick,
+                &[Position::new(8 - tick, 5), Position::new(3, 0)],
+                None,
+                &player,
+                &bounds,
+            );
+        }
+        let chaser = tracker
+            .enemies()
+            .iter()
+            .find(|e| e.position.y == 5)
+            .unwrap();
+        assert_eq!(chaser.pattern, MovementPattern::Chasing);
+        assert_eq!(chaser.speed, 1.0);
+        let guard = tracker
+            .enemies()
+            .iter()
+            .find(|e| e.position.y == 0)
+            .unwrap();
+        assert_eq!(guard.pattern, MovementPattern::Stationary);
+    }
+
+    #[test]
+    fn test_chaser_prediction_follows_path_to_player() {
+        let map = open_map(10, 3);
+        let mut tracker = EnemyTracker::new();
+        let bounds = everything_visible();
+        let player = [Position::new(0, 1)];
+        for tick in 0..3 {
+            tracker.update(tick, &[Position::new(8 - tick, 1)], None, &player, &bounds);
+        }
+
+        let threats = tracker.predict(&map, 2, &player);
+        assert!(threats.is_occupied(&Position::new(6, 1), 0));
+        assert!(threats.is_occupied(&Position::new(4, 1), 2));
+        // Stops next to the player
+        assert!(threats.is_occupied(&Position::new(1, 1), 7));
+        assert!(!threats.is_occupied(&Position::new(0, 1), 7));
+        assert!(threats.is_threatened(&Position::new(3, 1), 2));
+        assert!(!threats.is_threatened(&Position::new(2, 1), 2));
+    }
+
+    #[test]
+    fn test_unseen_enemy_spreads_and_is_forgotten() {
+        let map = open_map(20, 20);
+        let mut tracker = EnemyTracker::new();
+        let player = [Position::new(0, 0)];
+        let far_view = [Bounds::new(0, 2, 0, 2)];
+        tracker.update(0, &[Position::new(10, 10)], None, &player, &far_view);
+        tracker.update(2, &[], None, &player, &far_view);
+        assert_eq!(tracker.enemies().len(), 1);
+
+        let threats = tracker.predict(&map, 2, &player);
+        assert!(threats.is_occupied(&Position::new(12, 10), 0));
+        assert!(!threats.is_occupied(&Position::new(14, 10), 0));
+        assert!(threats.is_occupied(&Position::new(13, 10), 1));
+
+        // Everything around its last position is in view and it is not there
+        tracker.update(3, &[], None, &player, &everything_visible());
+        assert!(tracker.enemies().is_empty());
+    }
+
+    #[test]
+    fn test_boss_tracked_separately() {
+        let mut tracker = EnemyTracker::new();
+        let bounds = everything_visible();
+        let player = [Position::new(0, 0)];
+        tracker.update(0, &[Position::new(4, 4)], Some(Position::new(4, 5)), &player, &bounds);
+        tracker.update(1, &[Position::new(4, 5)], Some(Position::new(4, 6)), &player, &bounds);
+        let boss = tracker.enemies().iter().find(|e| e.is_boss).unwrap();
+        assert_eq!(boss.position, Position::new(4, 6));
+        let enemy = tracker.enemies().iter().find(|e| !e.is_boss).unwrap();
+        assert_eq!(enemy.position, Position::new(4, 5));
+    }
+
+    #[test]
+    fn test_reservations_only_for_certain_predictions() {
+        let map = open_map(12, 12);
+        let mut tracker = EnemyTracker::new();
+        let bounds = everything_visible();
+        let player = [Position::new(0, 1)];
+        for tick in 0..3 {
+            tracker.update(
+                tick,
+                &[Position::new(8 - tick, 1), Position::new(5, 8)],
+                None,
+                &player,
+                &bounds,
+            );
+        }
+        // The second enemy starts wandering away from the player
+        tracker.update(3, &[Position::new(5, 1), Position::new(5, 9)], None, &player, &bounds);
+
+        let reservations = tracker.reservations(&map, 3, &player);
+        assert!(reservations.is_reserved(&Position::new(5, 1), 0));
+        assert!(reservations.is_reserved(&Position::new(4, 1), 1));
+        assert!(reservations.is_reserved(&Position::new(5, 9), 0));
+        assert!(!reservations.is_reserved(&Position::new(5, 10), 1));
+        // Swapping places with the chaser is not possible
+        assert!(!reservations.is_move_allowed(Position::new(4, 1), Position::new(5, 1), 1));
+    }
+
+    #[test]
+    fn test_world_state_predicts_chasing_enemy() {
+        let level = Level::parse(
+            10,
+            "\
+###########
+#1      e #
+###########",
+        )
+        .unwrap();
+        let config = SimConfig {
+            visibility_range: 8,
+            ..SimConfig::default()
+        };
+        let mut sim = Simulator::new(&level, config);
+        let mut world = WorldState::new(sim.map_width(), sim.map_height(), sim.visibility_range());
+        world.update(&sim.state());
+
+        // The enemy keeps approaching
+        for _ in 0..3 {
+            assert_eq!(sim.act(DirectedAction::None, None), ActResult::Ok);
+            world.update(&sim.state());
+        }
+        let [enemy] = world.enemy_tracker.enemies() else {
+            panic!("expected a single tracked enemy");
+        };
+        assert_eq!(enemy.pattern, MovementPattern::Chasing);
+        let position = Position::new(5, 1);
+        assert_eq!(enemy.position, position);
+
+        // Predicted to keep coming, so the tile in front of the player gets expensive
+        let next = Position::new(position.x - 1, position.y);
+        assert!(world.threats.is_occupied(&next, 1));
+        let player = world.players[0].position;
+        let in_front = Position::new(player.x + 1, player.y);
+        let goal = Position::new(player.x + 2, player.y);
+        assert!(world.path_cost(&in_front, goal, 3) > 1);
+        assert_eq!(world.path_cost(&in_front, goal, 0), 1);
+    }
+}
@@ -0,0 +1,42 @@
+use std::time::Duration;
+
+use crate::infra::swoq::Game;
+use crate::swoq_interface::GameStatus;
+
+/// Outcome of a single game, returned by the planners' `Game::run`
+#[derive(Debug, Clone)]
+pub struct GameSummary {
+    pub game_id: String,
+    pub seed: Option<i32>,
+    /// Level the game ended on (quests can advance several levels)
+    pub level: i32,
+    pub status: GameStatus,
+    pub ticks: i32,
+    pub deaths: i32,
+    /// Time spent selecting actions, excluding the server round trips
+    pub planning_time: Duration,
+}
+
+impl GameSummary {
+    pub fn new(game: &Game, planning_time: Duration) -> Self {
+        let status =
+            GameStatus::try_from(game.state.status).unwrap_or(GameStatus::FinishedCanceled);
+        let deaths = match status {
+            GameStatus::FinishedPlayerDied | GameStatus::FinishedPlayer2Died => 1,
+            _ => 0,
+        };
+        Self {
+            game_id: game.game_id.clone(),
+            seed: game.seed,
+            level: game.state.level,
+            status,
+            ticks: game.state.tick,
+            deaths,
+            planning_time,
+        }
+    }
+
+    pub fn is_success(&self) -> bool {
+        self.status == GameStatus::FinishedSuccess
+    }
+}
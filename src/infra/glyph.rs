@@ -0,0 +1,61 @@
+use crate::swoq_interface::Tile;
+
+/// Glyph for a tile, matching the characters used by `WorldState::draw_ascii_map`
+/// (without the ANSI colors).
+/// `None` is a tile that was never seen.
+pub fn tile_to_glyph(tile: Option<Tile>) -> char {
+    match tile {
+        Some(Tile::Unknown) => '·',
+        Some(Tile::Wall) => '█',
+        Some(Tile::Empty) | Some(Tile::Player) => ' ',
+        Some(Tile::Exit) => 'E',
+        Some(Tile::KeyRed) => 'r',
+        Some(Tile::KeyGreen) => 'g',
+        Some(Tile::KeyBlue) => 'b',
+        Some(Tile::DoorRed) => 'R',
+        Some(Tile::DoorGreen) => 'G',
+        Some(Tile::DoorBlue) => 'B',
+        Some(Tile::Enemy) => 'e',
+        Some(Tile::Boulder) => 'o',
+        Some(Tile::Sword) => 's',
+        Some(Tile::Health) => '+',
+        Some(Tile::PressurePlateRed) => '▫',
+        Some(Tile::PressurePlateGreen) => '▪',
+        Some(Tile::PressurePlateBlue) => '◦',
+        Some(Tile::Boss) => 'X',
+        Some(Tile::Treasure) => '$',
+        None => '?',
+    }
+}
+
+/// Parse a glyph back into a tile.
+/// Accepts the glyphs produced by `tile_to_glyph` plus a few ASCII aliases
+/// (`#` wall, `.` empty, `O` unmoved boulder). Players (`1`, `2`) map to `Tile::Player`.
+/// Returns `Some(None)` for the never-seen glyph `?` and `None` for unrecognized characters.
+pub fn tile_from_glyph(glyph: char) -> Option<Option<Tile>> {
+    let tile = match glyph {
+        '?' => return Some(None),
+        '·' => Tile::Unknown,
+        '█' | '#' => Tile::Wall,
+        ' ' | '.' => Tile::Empty,
+        '1' | '2' => Tile::Player,
+        'E' => Tile::Exit,
+        'r' => Tile::KeyRed,
+        'g' => Tile::KeyGreen,
+        'b' => Tile::KeyBlue,
+        'R' => Tile::DoorRed,
+        'G' => Tile::DoorGreen,
+        'B' => Tile::DoorBlue,
+        'e' => Tile::Enemy,
+        'o' | 'O' => Tile::Boulder,
+        's' => Tile::Sword,
+        '+' => Tile::Health,
+        '▫' => Tile::PressurePlateRed,
+        '▪' => Tile::PressurePlateGreen,
+        '◦' => Tile::PressurePlateBlue,
+        'X' => Tile::Boss,
+        '$' => Tile::Treasure,
+        _ => return None,
+    };
+    Some(Some(tile))
+}
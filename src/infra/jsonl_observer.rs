@@ -0,0 +1,309 @@
+use serde::Serialize;
+use std::fs::File;
+use std::io::{self, BufWriter, Write};
+use std::path::Path;
+
+use crate::infra::{GameObserver, Position};
+use crate::state::WorldState;
+use crate::swoq_interface::{ActResult, DirectedAction, GameStatus, State};
+
+/// Player as seen at the start of a tick
+#[derive(Debug, Serialize)]
+struct PlayerRecord {
+    position: Position,
+    health: i32,
+    inventory: String,
+    has_sword: bool,
+    is_active: bool,
+}
+
+/// One line of the event log, tagged with its `event` name
+#[derive(Debug, Serialize)]
+#[serde(tag = "event", rename_all = "snake_case")]
+enum Event<'a> {
+    GameStart {
+        game_id: &'a str,
+        seed: Option<i32>,
+        map_width: i32,
+        map_height: i32,
+        visibility_range: i32,
+    },
+    Level {
+        level: i32,
+    },
+    Tick {
+        tick: i32,
+        level: i32,
+        game_count: i32,
+        players: Vec<PlayerRecord>,
+        enemies: Vec<Position>,
+        boss: Option<Position>,
+        exit: Option<Position>,
+    },
+    Goal {
+        tick: i32,
+        player: usize,
+        goal: &'a str,
+    },
+    Path {
+        tick: i32,
+        player: usize,
+        path: Option<Vec<Position>>,
+    },
+    Action {
+        tick: i32,
+        action: String,
+    },
+    ActionResult {
+        tick: i32,
+        action: String,
+        action2: Option<String>,
+        result: String,
+    },
+    Oscillation {
+        tick: i32,
+        message: &'a str,
+    },
+    Finish {
+        status: String,
+        tick: i32,
+        game_count: i32,
+        successful_runs: i32,
+        failed_runs: i32,
+    },
+}
+
+/// Writes every game event as one JSON object per line, for grepping, diffing runs
+/// and loading them into notebooks
+pub struct JsonlObserver {
+    writer: Box<dyn Write + Send>,
+    tick: i32,
+    failed: bool,
+}
+
+impl JsonlObserver {
+    pub fn new(writer: impl Write + Send + 'static) -> Self {
+        Self {
+            writer: Box::new(writer),
+            tick: 0,
+            failed: false,
+        }
+    }
+
+    /// Log to a new file, replacing an existing one
+    pub fn create(path: &Path) -> io::Result<Self> {
+        Ok(Self::new(BufWriter::new(File::create(path)?)))
+    }
+
+    fn write(&mut self, event: &Event) {
+        if self.failed {
+            return;
+        }
+        let result = serde_json::to_writer(&mut self.writer, event)
+            .map_err(io::Error::from)
+            .and_then(|_| writeln!(self.writer));
+        if let Err(e) = result {
+            // Keep playing, the log is only a by-product
+            tracing::warn!("Event log disabled after write error: {}", e);
+            self.failed = true;
+        }
+    }
+
+    fn flush(&mut self) {
+        if !self.failed
+            && let Err(e) = self.writer.flush()
+        {
+            tracing::warn!("Event log disabled after flush error: {}", e);
+            self.failed = true;
+        }
+    }
+}
+
+impl GameObserver for JsonlObserver {
+    fn on_game_start(
+        &mut self,
+        game_id: &str,
+        seed: Option<i32>,
+        map_width: i32,
+        map_height: i32,
+        visibility_range: i32,
+    ) {
+        self.tick = 0;
+        self.write(&Event::GameStart {
+            game_id,
+            seed,
+            map_width,
+            map_height,
+            visibility_range,
+        });
+    }
+
+    fn on_new_level(&mut self, level: i32) {
+        self.write(&Event::Level { level });
+    }
+
+    fn on_state_update(
+        &mut self,
+        state: &State,
+        world: &WorldState,
+        game_count: i32,
+        _successful_runs: i32,
+        _failed_runs: i32,
+    ) {
+        self.tick = state.tick;
+        let players = world
+            .players
+            .iter()
+            .map(|player| PlayerRecord {
+                position: player.position,
+                health: player.health,
+                inventory: format!("{:?}", player.inventory),
+                has_sword: player.has_sword,
+                is_active: player.is_active,
+            })
+            .collect();
+        self.write(&Event::Tick {
+            tick: state.tick,
+            level: world.level,
+            game_count,
+            players,
+            enemies: world.enemies.get_positions().to_vec(),
+            boss: world.boss_position,
+            exit: world.exit_position,
+        });
+    }
+
+    fn on_goal_selected(&mut self, player_index: usize, goal_name: &str, _world: &WorldState) {
+        self.write(&Event::Goal {
+            tick: self.tick,
+            player: player_index,
+            goal: goal_name,
+        });
+    }
+
+    fn on_paths_updated(&mut self, paths: Vec<Option<Vec<Position>>>) {
+        for (player, path) in paths.into_iter().enumerate() {
+            self.write(&Event::Path {
+                tick: self.tick,
+                player,
+                path,
+            });
+        }
+    }
+
+    fn on_action_selected(&mut self, action: DirectedAction, _world: &WorldState) {
+        self.write(&Event::Action {
+            tick: self.tick,
+            action: format!("{:?}", action),
+        });
+    }
+
+    fn on_action_result(
+        &mut self,
+        action: DirectedAction,
+        action2: Option<DirectedAction>,
+        result: ActResult,
+        _world: &WorldState,
+    ) {
+        self.write(&Event::ActionResult {
+            tick: self.tick,
+            action: format!("{:?}", action),
+            action2: action2.map(|action| format!("{:?}", action)),
+            result: format!("{:?}", result),
+        });
+    }
+
+    fn on_game_finished(
+        &mut self,
+        status: GameStatus,
+        final_tick: i32,
+        game_count: i32,
+        successful_runs: i32,
+        failed_runs: i32,
+    ) {
+        self.write(&Event::Finish {
+            status: format!("{:?}", status),
+            tick: final_tick,
+            game_count,
+            successful_runs,
+            failed_runs,
+        });
+        self.flush();
+    }
+
+    fn on_oscillation_detected(&mut self, message: &str) {
+        self.write(&Event::Oscillation {
+            tick: self.tick,
+            message,
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::{Arc, Mutex};
+
+    /// Writer whose contents stay readable after the observer took it
+    #[derive(Clone, Default)]
+    struct SharedBuffer(Arc<Mutex<Vec<u8>>>);
+
+    impl Write for SharedBuffer {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            self.0.lock().unwrap().extend_from_slice(buf);
+            Ok(buf.len())
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_writes_one_json_object_per_event() {
+        let buffer = SharedBuffer::default();
+        let mut observer = JsonlObserver::new(buffer.clone());
+        let world = WorldState::new(4, 4, 3);
+        let state = State {
+            tick: 7,
+            ..Default::default()
+        };
+
+        observer.on_game_start("game-1", Some(42), 4, 4, 3);
+        observer.on_state_update(&state, &world, 1, 0, 0);
+        observer.on_goal_selected(0, "Explore", &world);
+        observer.on_paths_updated(vec![Some(vec![Position::new(0, 0), Position::new(1, 0)])]);
+        observer.on_action_result(DirectedAction::MoveEast, None, ActResult::Ok, &world);
+        observer.on_game_finished(GameStatus::FinishedSuccess, 8, 1, 1, 0);
+
+        let text = String::from_utf8(buffer.0.lock().unwrap().clone()).unwrap();
+        let lines: Vec<serde_json::Value> = text
+            .lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect();
+        let events: Vec<&str> = lines
+            .iter()
+            .map(|line| line["event"].as_str().unwrap())
+            .collect();
+        assert_eq!(
+            events,
+            vec![
+                "game_start",
+                "tick",
+                "goal",
+                "path",
+                "action_result",
+                "finish"
+            ]
+        );
+
+        assert_eq!(lines[0]["seed"], 42);
+        assert_eq!(lines[1]["players"][0]["health"], 5);
+        assert_eq!(lines[2]["tick"], 7);
+        assert_eq!(lines[2]["goal"], "Explore");
+        assert_eq!(lines[3]["path"][1]["x"], 1);
+        assert_eq!(lines[4]["action"], "MoveEast");
+        assert_eq!(lines[4]["action2"], serde_json::Value::Null);
+        assert_eq!(lines[5]["status"], "FinishedSuccess");
+    }
+}
@@ -2,20 +2,30 @@ mod boulder_tracker;
 mod cbs;
 mod composite_observer;
 mod default_observer;
+mod enemy_tracker;
 mod game_observer;
+mod game_summary;
+mod glyph;
 mod item_tracker;
+mod jsonl_observer;
 mod pathfinding;
+mod replay;
 pub mod swoq;
 mod types;
 mod visualizing_observer;
 
 pub use boulder_tracker::BoulderTracker;
-pub use cbs::{Agent, CBS};
+pub use cbs::{Agent, CBS, CbsLimits};
 pub use composite_observer::CompositeObserver;
 pub use default_observer::DefaultObserver;
+pub use enemy_tracker::{EnemyTracker, MovementPattern, ThreatMap, TrackedEnemy};
 pub use game_observer::GameObserver;
+pub use game_summary::GameSummary;
+pub use glyph::{tile_from_glyph, tile_to_glyph};
 pub use item_tracker::{ColoredItemTracker, ItemTracker};
-pub use pathfinding::AStar;
+pub use jsonl_observer::JsonlObserver;
+pub use pathfinding::{AStar, ReservationTable};
+pub use replay::{ReplayGameConnection, ReplayMessage, ReplayReader};
 pub use swoq::GameConnection;
 pub use types::{Bounds, Color, Position};
 pub use visualizing_observer::VisualizingObserver;
@@ -26,6 +36,7 @@ use crate::swoq_interface::DirectedAction;
 // Helper functions
 // ============================================================================
 
+/// Action to take the first step of `path` (which starts at `current`)
 pub fn path_to_action(current: Position, path: &[Position]) -> Option<DirectedAction> {
     if path.len() < 2 {
         return None;
@@ -45,6 +56,21 @@ pub fn path_to_action(current: Position, path: &[Position]) -> Option<DirectedAc
     }
 }
 
+/// Action to take the first step of a timed path, which repeats `current` to wait
+/// in place (`DirectedAction::None`)
+pub fn timed_path_to_action(current: Position, path: &[Position]) -> Option<DirectedAction> {
+    if path.get(1) == Some(&current) {
+        return Some(DirectedAction::None);
+    }
+    path_to_action(current, path)
+}
+
+/// Number of positions a path visits, not counting the waits of a timed path.
+/// This is the path length as a distance, a timed path can take longer.
+pub fn path_length(path: &[Position]) -> usize {
+    path.len() - path.windows(2).filter(|step| step[0] == step[1]).count()
+}
+
 pub fn use_direction(from: Position, to: Position) -> DirectedAction {
     if to.y < from.y {
         DirectedAction::UseNorth
@@ -114,6 +114,209 @@ impl AStar {
 
         None
     }
+
+    /// Space-time A*: find a path that avoids the time-varying obstacles in `reservations`.
+    ///
+    /// The returned path holds the position at every tick from the start (`path[0] == start`),
+    /// so a repeated position means waiting in place for a tick. Waiting costs 1, entering a
+    /// position costs `cost_fn(position, goal, tick_from_start)`.
+    /// The goal only counts as reached when it stays free for the rest of the reservations.
+    pub fn find_timed_path<F, C>(
+        map: &Map,
+        start: Position,
+        goal: Position,
+        is_walkable_at_tick: F,
+        cost_fn: C,
+        reservations: &ReservationTable,
+    ) -> Option<Vec<Position>>
+    where
+        F: Fn(&Position, Position, i32) -> bool,
+        C: Fn(&Position, Position, i32) -> i32,
+    {
+        // Beyond the last reservation time no longer matters, so states collapse into one tick
+        let horizon = reservations.horizon() + 1;
+        let key = |pos: Position, tick: i32| (pos, tick.min(horizon));
+
+        let mut open_set = BinaryHeap::new();
+        let mut came_from: HashMap<(Position, i32), (Position, i32)> = HashMap::new();
+        let mut g_score: HashMap<(Position, i32), i32> = HashMap::new();
+        let mut closed_set: HashSet<(Position, i32)> = HashSet::new();
+
+        g_score.insert(key(start, 0), 0);
+        open_set.push(Node {
+            pos: start,
+            f_score: heuristic(start, goal),
+            tick: 0,
+        });
+
+        const MAX_EXPANSIONS: usize = 10000;
+        const WAIT_COST: i32 = 1;
+        let mut expansions = 0;
+
+        while let Some(Node {
+            pos: current,
+            tick: current_tick,
+            ..
+        }) = open_set.pop()
+        {
+            let current_key = key(current, current_tick);
+            if current == goal && !reservations.is_reserved_after(&goal, current_tick) {
+                return Some(reconstruct_timed_path(&came_from, current_key));
+            }
+
+            if !closed_set.insert(current_key) {
+                continue;
+            }
+
+            expansions += 1;
+            if expansions > MAX_EXPANSIONS {
+                return None;
+            }
+
+            let current_g_score = *g_score.get(&current_key).unwrap_or(&0);
+            let next_tick = current_tick + 1;
+
+            // Waiting only makes sense while there are reservations to wait for
+            let wait = (current_tick < horizon).then_some(current);
+            for neighbor in current.neighbors().into_iter().chain(wait) {
+                if neighbor.x < 0
+                    || neighbor.x >= map.width
+                    || neighbor.y < 0
+                    || neighbor.y >= map.height
+                {
+                    continue;
+                }
+
+                let neighbor_key = key(neighbor, next_tick);
+                if closed_set.contains(&neighbor_key) {
+                    continue;
+                }
+
+                let is_wait = neighbor == current;
+                if !is_wait && !is_walkable_at_tick(&neighbor, goal, next_tick) {
+                    continue;
+                }
+                if !reservations.is_move_allowed(current, neighbor, next_tick) {
+                    continue;
+                }
+
+                let step_cost = if is_wait {
+                    WAIT_COST
+                } else {
+                    cost_fn(&neighbor, goal, next_tick)
+                };
+                let tentative_g = current_g_score + step_cost;
+
+                if tentative_g < *g_score.get(&neighbor_key).unwrap_or(&i32::MAX) {
+                    came_from.insert(neighbor_key, current_key);
+                    g_score.insert(neighbor_key, tentative_g);
+                    open_set.push(Node {
+                        pos: neighbor,
+                        f_score: tentative_g + heuristic(neighbor, goal),
+                        tick: next_tick,
+                    });
+                }
+            }
+        }
+
+        None
+    }
+}
+
+/// Positions that are taken at specific ticks, for space-time pathfinding.
+/// Ticks are relative to the start of the search (0 = now).
+///
+/// Examples: an enemy predicted at a tile at t+3, another player's planned path,
+/// or a door that closes from t+5 on when its plate is released.
+#[derive(Debug, Clone, Default)]
+pub struct ReservationTable {
+    vertices: HashSet<(Position, i32)>,
+    /// (from, to, tick): an obstacle moves from `from` to `to`, arriving at `tick`
+    edges: HashSet<(Position, Position, i32)>,
+    /// Positions that are taken from the given tick onwards
+    blocked_from: HashMap<Position, i32>,
+    horizon: i32,
+}
+
+impl ReservationTable {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Reserve a position at a single tick
+    pub fn reserve(&mut self, pos: Position, tick: i32) {
+        self.vertices.insert((pos, tick));
+        self.horizon = self.horizon.max(tick);
+    }
+
+    /// Reserve a move from `from` to `to` arriving at `tick`, which also rules out swapping places
+    pub fn reserve_move(&mut self, from: Position, to: Position, tick: i32) {
+        self.reserve(to, tick);
+        if from != to {
+            self.edges.insert((from, to, tick));
+        }
+    }
+
+    /// Reserve a position from `tick` on, forever
+    pub fn block_from(&mut self, pos: Position, tick: i32) {
+        let entry = self.blocked_from.entry(pos).or_insert(tick);
+        *entry = (*entry).min(tick);
+        self.horizon = self.horizon.max(tick);
+    }
+
+    /// Reserve a planned path, where `path[i]` is taken at `start_tick + i`.
+    /// The last position stays taken after the path ends.
+    pub fn reserve_path(&mut self, path: &[Position], start_tick: i32) {
+        for (i, &pos) in path.iter().enumerate() {
+            let tick = start_tick + i as i32;
+            match i.checked_sub(1) {
+                Some(prev) => self.reserve_move(path[prev], pos, tick),
+                None => self.reserve(pos, tick),
+            }
+        }
+        if let Some(&last) = path.last() {
+            self.block_from(last, start_tick + path.len() as i32 - 1);
+        }
+    }
+
+    /// Add all reservations of another table
+    pub fn merge(&mut self, other: &ReservationTable) {
+        self.vertices.extend(other.vertices.iter().copied());
+        self.edges.extend(other.edges.iter().copied());
+        for (&pos, &tick) in &other.blocked_from {
+            self.block_from(pos, tick);
+        }
+        self.horizon = self.horizon.max(other.horizon);
+    }
+
+    pub fn is_reserved(&self, pos: &Position, tick: i32) -> bool {
+        self.vertices.contains(&(*pos, tick))
+            || self.blocked_from.get(pos).is_some_and(|&from| tick >= from)
+    }
+
+    /// True if the position is taken at any tick after `tick`
+    pub fn is_reserved_after(&self, pos: &Position, tick: i32) -> bool {
+        self.blocked_from.contains_key(pos)
+            || self
+                .vertices
+                .iter()
+                .any(|(reserved, t)| reserved == pos && *t > tick)
+    }
+
+    /// Check if moving (or waiting) from `from` to `to`, arriving at `tick`, is free.
+    /// Moves that swap places with a reserved move are not allowed.
+    pub fn is_move_allowed(&self, from: Position, to: Position, tick: i32) -> bool {
+        !self.is_reserved(&to, tick) && !self.edges.contains(&(to, from, tick))
+    }
+
+    /// Last tick with a reservation that changes over time
+    pub fn horizon(&self) -> i32 {
+        self.horizon
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.vertices.is_empty() && self.blocked_from.is_empty()
+    }
 }
 
 fn heuristic(a: Position, b: Position) -> i32 {
@@ -132,3 +335,362 @@ fn reconstruct_path(
     path.reverse();
     path
 }
+
+fn reconstruct_timed_path(
+    came_from: &HashMap<(Position, i32), (Position, i32)>,
+    mut current: (Position, i32),
+) -> Vec<Position> {
+    let mut path = vec![current.0];
+    while let Some(&prev) = came_from.get(&current) {
+        path.push(prev.0);
+        current = prev;
+    }
+    path.reverse();
+    path
+}
+
+#[cfg(test)]
+mod tests {
+    use std::cmp::Reverse;
+
+    use rand::rngs::StdRng;
+    use rand::{Rng, SeedableRng};
+
+    use super::*;
+    use crate::swoq_interface::Tile;
+
+    /// Corridor of the given length along the top row, the row below is unexplored
+    fn corridor(length: i32) -> Map {
+        let mut map = Map::new(length, 2);
+        for x in 0..length {
+            map.insert(Position::new(x, 0), Tile::Empty);
+        }
+        map
+    }
+
+    fn find(
+        map: &Map,
+        start: Position,
+        goal: Position,
+        reservations: &ReservationTable,
+    ) -> Option<Vec<Position>> {
+        AStar::find_timed_path(
+            map,
+            start,
+            goal,
+            |pos, _goal, _tick| map.get(pos) == Some(&Tile::Empty),
+            |_pos, _goal, _tick| 1,
+            reservations,
+        )
+    }
+
+    #[test]
+    fn test_timed_path_without_reservations() {
+        let map = corridor(5);
+        let path = find(&map, Position::new(0, 0), Position::new(4, 0), &ReservationTable::new());
+        assert_eq!(path.unwrap().len(), 5);
+    }
+
+    #[test]
+    fn test_timed_path_waits_for_obstacle_to_pass() {
+        // A side pocket at (2, 1) lets an obstacle coming down the corridor pass by
+        let mut map = corridor(6);
+        map.insert(Position::new(2, 1), Tile::Empty);
+        let mut reservations = ReservationTable::new();
+        // Obstacle walks from (5, 0) to (0, 0), one tile per tick, then leaves the map
+        let obstacle: Vec<Position> = (0..6).rev().map(|x| Position::new(x, 0)).collect();
+        for (tick, pair) in obstacle.windows(2).enumerate() {
+            reservations.reserve_move(pair[0], pair[1], tick as i32 + 1);
+        }
+        reservations.reserve(obstacle[0], 0);
+
+        let path = find(&map, Position::new(0, 0), Position::new(5, 0), &reservations).unwrap();
+        for (tick, pos) in path.iter().enumerate() {
+            assert!(!reservations.is_reserved(pos, tick as i32), "collision at tick {}", tick);
+        }
+        assert!(path.contains(&Position::new(2, 1)));
+        assert_eq!(*path.last().unwrap(), Position::new(5, 0));
+    }
+
+    #[test]
+    fn test_timed_path_waits_in_place() {
+        let map = corridor(4);
+        let mut reservations = ReservationTable::new();
+        reservations.reserve(Position::new(1, 0), 1);
+        reservations.reserve(Position::new(1, 0), 2);
+
+        let path = find(&map, Position::new(0, 0), Position::new(3, 0), &reservations).unwrap();
+        assert_eq!(
+            path,
+            vec![
+                Position::new(0, 0),
+                Position::new(0, 0),
+                Position::new(0, 0),
+                Position::new(1, 0),
+                Position::new(2, 0),
+                Position::new(3, 0),
+            ]
+        );
+        assert_eq!(
+            crate::infra::timed_path_to_action(path[0], &path),
+            Some(crate::swoq_interface::DirectedAction::None)
+        );
+        assert_eq!(crate::infra::path_length(&path), 4);
+    }
+
+    #[test]
+    fn test_timed_path_through_closing_door() {
+        let map = corridor(6);
+        // The door at (3, 0) closes at tick 5, reaching the goal behind it takes 5 ticks
+        let mut reservations = ReservationTable::new();
+        reservations.block_from(Position::new(3, 0), 5);
+        assert!(find(&map, Position::new(0, 0), Position::new(5, 0), &reservations).is_some());
+
+        // Closing at tick 3 is too early to pass, and no later arrival helps
+        let mut reservations = ReservationTable::new();
+        reservations.block_from(Position::new(3, 0), 3);
+        assert!(find(&map, Position::new(0, 0), Position::new(5, 0), &reservations).is_none());
+    }
+
+    #[test]
+    fn test_timed_path_avoids_goal_taken_later() {
+        let map = corridor(3);
+        let mut reservations = ReservationTable::new();
+        reservations.reserve_path(&[Position::new(0, 0), Position::new(1, 0)], 5);
+        // Reaching (1, 0) early is no good, the path ends there at tick 6 and stays
+        assert!(find(&map, Position::new(2, 0), Position::new(1, 0), &reservations).is_none());
+        assert!(find(&map, Position::new(2, 0), Position::new(2, 0), &reservations).is_some());
+    }
+
+    /// Cheapest cost from `start` to `goal` by Dijkstra, entering a tile costs `costs[tile]`
+    fn dijkstra(
+        map: &Map,
+        costs: &HashMap<Position, i32>,
+        start: Position,
+        goal: Position,
+    ) -> Option<i32> {
+        let mut best = HashMap::from([(start, 0)]);
+        let mut open = BinaryHeap::from([Reverse((0, start.x, start.y))]);
+        while let Some(Reverse((cost, x, y))) = open.pop() {
+            let pos = Position::new(x, y);
+            if pos == goal {
+                return Some(cost);
+            }
+            if best[&pos] < cost {
+                continue;
+            }
+            for next in pos.neighbors() {
+                let Some(&step) = costs.get(&next) else {
+                    continue;
+                };
+                if map.get(&next) == Some(&Tile::Empty)
+                    && best.get(&next).is_none_or(|&known| cost + step < known)
+                {
+                    best.insert(next, cost + step);
+                    open.push(Reverse((cost + step, next.x, next.y)));
+                }
+            }
+        }
+        None
+    }
+
+    /// Random map with about a quarter walls, the cost of entering every floor tile and
+    /// the floor tiles
+    fn random_costs_map(rng: &mut StdRng) -> (Map, HashMap<Position, i32>, Vec<Position>) {
+        let (width, height) = (rng.random_range(3..12), rng.random_range(3..10));
+        let mut map = Map::new(width, height);
+        let mut costs = HashMap::new();
+        let mut floor = Vec::new();
+        for y in 0..height {
+            for x in 0..width {
+                let pos = Position::new(x, y);
+                if rng.random_range(0..100) < 25 {
+                    map.insert(pos, Tile::Wall);
+                } else {
+                    map.insert(pos, Tile::Empty);
+                    costs.insert(pos, rng.random_range(1..=4));
+                    floor.push(pos);
+                }
+            }
+        }
+        (map, costs, floor)
+    }
+
+    #[test]
+    fn test_path_with_cost_is_optimal_on_random_maps() {
+        for seed in 0..300 {
+            let mut rng = StdRng::seed_from_u64(seed);
+            let (map, costs, floor) = random_costs_map(&mut rng);
+            if floor.is_empty() {
+                continue;
+            }
+            let start = floor[rng.random_range(0..floor.len())];
+            let goal = floor[rng.random_range(0..floor.len())];
+
+            let path = AStar::find_path_with_cost(
+                &map,
+                start,
+                goal,
+                |pos, _goal, _tick| map.get(pos) == Some(&Tile::Empty),
+                |pos, _goal, _tick| costs[pos],
+            );
+            let expected = dijkstra(&map, &costs, start, goal);
+            let Some(path) = path else {
+                assert_eq!(expected, None, "seed {}: no path from {:?} to {:?}", seed, start, goal);
+                continue;
+            };
+
+            assert_eq!(path.first(), Some(&start), "seed {}", seed);
+            assert_eq!(path.last(), Some(&goal), "seed {}", seed);
+            for step in path.windows(2) {
+                assert!(
+                    step[0].is_adjacent(&step[1]),
+                    "seed {}: {:?} to {:?}",
+                    seed,
+                    step[0],
+                    step[1]
+                );
+                assert_eq!(map.get(&step[1]), Some(&Tile::Empty), "seed {}: {:?}", seed, step[1]);
+            }
+            let cost: i32 = path[1..].iter().map(|pos| costs[pos]).sum();
+            assert_eq!(
+                Some(cost),
+                expected,
+                "seed {}: path from {:?} to {:?} is not optimal",
+                seed,
+                start,
+                goal
+            );
+        }
+    }
+
+    /// Cheapest cost from `start` to `goal` by Dijkstra over positions and ticks, with the
+    /// rules of `find_timed_path`. Ticks past the reservations are all alike.
+    fn timed_dijkstra(
+        map: &Map,
+        costs: &HashMap<Position, i32>,
+        reservations: &ReservationTable,
+        start: Position,
+        goal: Position,
+    ) -> Option<i32> {
+        let last_tick = reservations.horizon() + 1;
+        let mut best = HashMap::from([((start, 0), 0)]);
+        let mut open = BinaryHeap::from([Reverse((0, 0, start.x, start.y))]);
+        while let Some(Reverse((cost, tick, x, y))) = open.pop() {
+            let pos = Position::new(x, y);
+            if pos == goal && !reservations.is_reserved_after(&goal, tick) {
+                return Some(cost);
+            }
+            if best[&(pos, tick)] < cost {
+                continue;
+            }
+            let next_tick = (tick + 1).min(last_tick);
+            for next in pos.neighbors().into_iter().chain([pos]) {
+                let step = if next == pos {
+                    1
+                } else if map.get(&next) == Some(&Tile::Empty) {
+                    costs[&next]
+                } else {
+                    continue;
+                };
+                if reservations.is_move_allowed(pos, next, tick + 1)
+                    && best
+                        .get(&(next, next_tick))
+                        .is_none_or(|&known| cost + step < known)
+                {
+                    best.insert((next, next_tick), cost + step);
+                    open.push(Reverse((cost + step, next_tick, next.x, next.y)));
+                }
+            }
+        }
+        None
+    }
+
+    /// Obstacles walking the floor from a random tick on, some of them stay where they stop
+    fn random_reservations(rng: &mut StdRng, map: &Map, floor: &[Position]) -> ReservationTable {
+        let mut reservations = ReservationTable::new();
+        for _ in 0..rng.random_range(1..=3) {
+            let mut tick = rng.random_range(0..3);
+            let mut pos = floor[rng.random_range(0..floor.len())];
+            reservations.reserve(pos, tick);
+            for _ in 0..rng.random_range(1..=6) {
+                let options: Vec<Position> = pos
+                    .neighbors()
+                    .into_iter()
+                    .chain([pos])
+                    .filter(|next| map.get(next) == Some(&Tile::Empty))
+                    .collect();
+                let next = options[rng.random_range(0..options.len())];
+                tick += 1;
+                reservations.reserve_move(pos, next, tick);
+                pos = next;
+            }
+            if rng.random_range(0..100) < 30 {
+                reservations.block_from(pos, tick);
+            }
+        }
+        reservations
+    }
+
+    #[test]
+    fn test_timed_path_is_optimal_on_random_maps_and_reservations() {
+        for seed in 0..300 {
+            let mut rng = StdRng::seed_from_u64(seed);
+            let (map, costs, floor) = random_costs_map(&mut rng);
+            if floor.is_empty() {
+                continue;
+            }
+            let reservations = random_reservations(&mut rng, &map, &floor);
+            let start = floor[rng.random_range(0..floor.len())];
+            let goal = floor[rng.random_range(0..floor.len())];
+
+            let path = AStar::find_timed_path(
+                &map,
+                start,
+                goal,
+                |pos, _goal, _tick| map.get(pos) == Some(&Tile::Empty),
+                |pos, _goal, _tick| costs[pos],
+                &reservations,
+            );
+            let expected = timed_dijkstra(&map, &costs, &reservations, start, goal);
+            let Some(path) = path else {
+                assert_eq!(expected, None, "seed {}: no path from {:?} to {:?}", seed, start, goal);
+                continue;
+            };
+
+            assert_eq!(path.first(), Some(&start), "seed {}", seed);
+            assert_eq!(path.last(), Some(&goal), "seed {}", seed);
+            let arrival = path.len() as i32 - 1;
+            assert!(!reservations.is_reserved_after(&goal, arrival), "seed {}", seed);
+            let mut cost = 0;
+            for (tick, step) in path.windows(2).enumerate() {
+                let (from, to) = (step[0], step[1]);
+                assert!(
+                    from == to || from.is_adjacent(&to),
+                    "seed {}: {:?} to {:?}",
+                    seed,
+                    from,
+                    to
+                );
+                assert_eq!(map.get(&to), Some(&Tile::Empty), "seed {}: {:?}", seed, to);
+                assert!(
+                    reservations.is_move_allowed(from, to, tick as i32 + 1),
+                    "seed {}: {:?} to {:?} at tick {} is reserved",
+                    seed,
+                    from,
+                    to,
+                    tick + 1
+                );
+                cost += if from == to { 1 } else { costs[&to] };
+            }
+            assert_eq!(
+                Some(cost),
+                expected,
+                "seed {}: path from {:?} to {:?} is not optimal",
+                seed,
+                start,
+                goal
+            );
+        }
+    }
+}
@@ -0,0 +1,239 @@
+use prost::Message;
+use std::fs;
+use std::io;
+use std::path::Path;
+
+use crate::swoq_interface::{ActRequest, ActResponse, StartRequest, StartResponse};
+
+/// A single message from a `.swoq` replay file
+#[derive(Debug, Clone, PartialEq)]
+pub enum ReplayMessage {
+    StartRequest(StartRequest),
+    StartResponse(StartResponse),
+    ActRequest(ActRequest),
+    ActResponse(ActResponse),
+}
+
+/// Reads the length-delimited messages written by `ReplayFile`.
+///
+/// A replay holds the `StartRequest` and `StartResponse`, followed by an
+/// `ActRequest`/`ActResponse` pair for every tick that was played.
+pub struct ReplayReader {
+    data: Vec<u8>,
+    offset: usize,
+    index: usize,
+}
+
+impl ReplayReader {
+    pub fn open(path: &Path) -> io::Result<Self> {
+        Ok(Self::new(fs::read(path)?))
+    }
+
+    pub fn new(data: Vec<u8>) -> Self {
+        Self {
+            data,
+            offset: 0,
+            index: 0,
+        }
+    }
+
+    fn read_message(&mut self) -> io::Result<ReplayMessage> {
+        let mut buf = &self.data[self.offset..];
+        let len = prost::decode_length_delimiter(&mut buf)?;
+        let header_len = self.data.len() - self.offset - buf.len();
+        if buf.len() < len {
+            return Err(io::Error::new(
+                io::ErrorKind::UnexpectedEof,
+                format!("Replay message {} is truncated", self.index),
+            ));
+        }
+        let payload = &buf[..len];
+
+        let message = match self.index {
+            0 => ReplayMessage::StartRequest(StartRequest::decode(payload)?),
+            1 => ReplayMessage::StartResponse(StartResponse::decode(payload)?),
+            n if n % 2 == 0 => ReplayMessage::ActRequest(ActRequest::decode(payload)?),
+            _ => ReplayMessage::ActResponse(ActResponse::decode(payload)?),
+        };
+
+        self.offset += header_len + len;
+        self.index += 1;
+        Ok(message)
+    }
+}
+
+impl Iterator for ReplayReader {
+    type Item = io::Result<ReplayMessage>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.offset >= self.data.len() {
+            return None;
+        }
+        let message = self.read_message();
+        if message.is_err() {
+            // Stop iterating after a corrupt message
+            self.offset = self.data.len();
+        }
+        Some(message)
+    }
+}
+
+/// A recorded game, served by `GameConnection::from_replay` instead of a game server.
+///
+/// Planners run unchanged against the recorded `State`s. Every action they choose is
+/// compared with the recorded one and the first mismatch ends the game with
+/// `SwoqError::ReplayDiverged`, reporting the tick where the planner went another way.
+#[derive(Debug, Clone)]
+pub struct ReplayGameConnection {
+    pub start_request: StartRequest,
+    pub start_response: StartResponse,
+    pub steps: Vec<(ActRequest, ActResponse)>,
+}
+
+impl ReplayGameConnection {
+    pub fn open(path: &Path) -> io::Result<Self> {
+        Self::from_reader(ReplayReader::open(path)?)
+    }
+
+    pub fn from_reader(reader: ReplayReader) -> io::Result<Self> {
+        let invalid = |what: &str| io::Error::new(io::ErrorKind::InvalidData, what.to_string());
+
+        let mut messages = reader;
+        let Some(ReplayMessage::StartRequest(start_request)) = messages.next().transpose()? else {
+            return Err(invalid("Replay does not begin with a StartRequest"));
+        };
+        let Some(ReplayMessage::StartResponse(start_response)) = messages.next().transpose()?
+        else {
+            return Err(invalid("Replay is missing the StartResponse"));
+        };
+        if start_response.state.is_none() || start_response.game_id.is_none() {
+            return Err(invalid("Replay StartResponse has no game"));
+        }
+
+        let mut steps = Vec::new();
+        while let Some(message) = messages.next() {
+            let ReplayMessage::ActRequest(request) = message? else {
+                return Err(invalid("Expected an ActRequest"));
+            };
+            match messages.next().transpose()? {
+                Some(ReplayMessage::ActResponse(response)) => steps.push((request, response)),
+                // The game was interrupted before the last response was recorded
+                None => break,
+                Some(_) => return Err(invalid("Expected an ActResponse")),
+            }
+        }
+
+        Ok(Self {
+            start_request,
+            start_response,
+            steps,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::infra::GameConnection;
+    use crate::infra::swoq::SwoqError;
+    use crate::swoq_interface::{ActResult, DirectedAction, GameStatus, StartResult, State};
+
+    fn state(tick: i32, status: GameStatus) -> State {
+        State {
+            tick,
+            level: 3,
+            status: status as i32,
+            player_state: None,
+            player2_state: None,
+        }
+    }
+
+    fn act(action: DirectedAction, tick: i32, status: GameStatus) -> (ActRequest, ActResponse) {
+        let request = ActRequest {
+            game_id: "game".to_string(),
+            action: Some(action as i32),
+            action2: None,
+        };
+        let response = ActResponse {
+            result: ActResult::Ok as i32,
+            state: Some(state(tick, status)),
+        };
+        (request, response)
+    }
+
+    fn recording() -> Vec<u8> {
+        let start_request = StartRequest {
+            user_id: "id".to_string(),
+            user_name: "bot".to_string(),
+            level: Some(3),
+            seed: None,
+        };
+        let start_response = StartResponse {
+            result: StartResult::Ok as i32,
+            game_id: Some("game".to_string()),
+            map_width: Some(8),
+            map_height: Some(6),
+            visibility_range: Some(4),
+            state: Some(state(0, GameStatus::Active)),
+            seed: Some(7),
+        };
+        let steps = [
+            act(DirectedAction::MoveEast, 1, GameStatus::Active),
+            act(DirectedAction::MoveSouth, 2, GameStatus::Active),
+            act(DirectedAction::MoveSouth, 3, GameStatus::FinishedSuccess),
+        ];
+
+        let mut data = Vec::new();
+        start_request.encode_length_delimited(&mut data).unwrap();
+        start_response.encode_length_delimited(&mut data).unwrap();
+        for (request, response) in steps {
+            request.encode_length_delimited(&mut data).unwrap();
+            response.encode_length_delimited(&mut data).unwrap();
+        }
+        data
+    }
+
+    #[test]
+    fn test_reader_yields_messages_in_order() {
+        let messages: Vec<ReplayMessage> = ReplayReader::new(recording())
+            .collect::<io::Result<_>>()
+            .unwrap();
+        assert_eq!(messages.len(), 8);
+        assert!(matches!(messages[0], ReplayMessage::StartRequest(_)));
+        assert!(matches!(messages[1], ReplayMessage::StartResponse(_)));
+        assert!(matches!(messages[2], ReplayMessage::ActRequest(_)));
+        assert!(matches!(messages[7], ReplayMessage::ActResponse(_)));
+
+        let mut truncated = recording();
+        truncated.truncate(truncated.len() - 3);
+        let replay = ReplayGameConnection::from_reader(ReplayReader::new(truncated));
+        assert!(replay.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_replay_reports_first_divergent_tick() {
+        let replay = ReplayGameConnection::from_reader(ReplayReader::new(recording())).unwrap();
+        let mut connection = GameConnection::from_replay(replay);
+        let mut game = connection.start(Some(3), None).await.unwrap();
+        assert_eq!(game.map_width, 8);
+        assert_eq!(game.seed, Some(7));
+
+        let result = game.act(DirectedAction::MoveEast, None).await.unwrap();
+        assert_eq!(result, ActResult::Ok);
+        assert_eq!(game.state.tick, 1);
+
+        let error = game.act(DirectedAction::MoveWest, None).await.unwrap_err();
+        match error.downcast_ref::<SwoqError>() {
+            Some(SwoqError::ReplayDiverged {
+                tick,
+                recorded,
+                chosen,
+            }) => {
+                assert_eq!(*tick, 1);
+                assert_eq!(recorded.0, Some(DirectedAction::MoveSouth));
+                assert_eq!(chosen.0, Some(DirectedAction::MoveWest));
+            }
+            _ => panic!("Expected a divergence, got {}", error),
+        }
+    }
+}
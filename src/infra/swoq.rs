@@ -8,14 +8,27 @@ use std::path::Path;
 use time::{OffsetDateTime, format_description};
 use tonic::transport::Channel;
 
+use crate::infra::ReplayGameConnection;
 use crate::swoq_interface::game_service_client::GameServiceClient;
 use crate::swoq_interface::{
-    self, ActRequest, ActResponse, StartRequest, StartResponse, StartResult,
+    self, ActRequest, ActResponse, DirectedAction, StartRequest, StartResponse, StartResult,
 };
 
 #[derive(Debug)]
 pub enum SwoqError {
-    StartFailed { result: swoq_interface::StartResult },
+    StartFailed {
+        result: swoq_interface::StartResult,
+    },
+    /// The planner chose other actions than the ones recorded in the replay
+    ReplayDiverged {
+        tick: i32,
+        recorded: (Option<DirectedAction>, Option<DirectedAction>),
+        chosen: (Option<DirectedAction>, Option<DirectedAction>),
+    },
+    /// The planner kept playing after the last recorded action
+    ReplayExhausted {
+        tick: i32,
+    },
 }
 
 impl fmt::Display for SwoqError {
@@ -24,17 +37,37 @@ impl fmt::Display for SwoqError {
             SwoqError::StartFailed { result } => {
                 write!(formatter, "Start failed (result {})", result.as_str_name())
             }
+            SwoqError::ReplayDiverged {
+                tick,
+                recorded,
+                chosen,
+            } => {
+                write!(
+                    formatter,
+                    "Replay diverged at tick {}: recorded {:?}/{:?}, planner chose {:?}/{:?}",
+                    tick, recorded.0, recorded.1, chosen.0, chosen.1
+                )
+            }
+            SwoqError::ReplayExhausted { tick } => {
+                write!(formatter, "Replay has no recorded action for tick {}", tick)
+            }
         }
     }
 }
 
 impl Error for SwoqError {}
 
+/// Where games are played: a game server, or a recorded replay
+enum Backend {
+    Remote(GameServiceClient<Channel>),
+    Replay(Option<ReplayGameConnection>),
+}
+
 pub struct GameConnection {
     user_id: String,
     user_name: String,
     replays_folder: Option<String>,
-    client: GameServiceClient<Channel>,
+    backend: Backend,
 }
 
 impl GameConnection {
@@ -49,15 +82,50 @@ impl GameConnection {
             user_id,
             user_name,
             replays_folder,
-            client,
+            backend: Backend::Remote(client),
         })
     }
 
+    /// Play back a recorded game. The replay can be started once.
+    pub fn from_replay(replay: ReplayGameConnection) -> Self {
+        GameConnection {
+            user_id: replay.start_request.user_id.clone(),
+            user_name: replay.start_request.user_name.clone(),
+            replays_folder: None,
+            backend: Backend::Replay(Some(replay)),
+        }
+    }
+
     pub async fn start(
         &mut self,
         level: Option<i32>,
         seed: Option<i32>,
     ) -> Result<Game, Box<dyn std::error::Error>> {
+        let client = match &mut self.backend {
+            Backend::Remote(client) => client,
+            Backend::Replay(replay) => {
+                let replay = replay.take().ok_or_else(|| {
+                    Box::new(SwoqError::StartFailed {
+                        result: StartResult::NotAllowed,
+                    })
+                })?;
+                if level != replay.start_request.level || seed != replay.start_request.seed {
+                    tracing::warn!(
+                        "Replaying level {:?} seed {:?} instead of level {:?} seed {:?}",
+                        replay.start_request.level,
+                        replay.start_request.seed,
+                        level,
+                        seed
+                    );
+                }
+                return Ok(Game::new(
+                    GameBackend::Replay(replay.steps.into_iter()),
+                    replay.start_response,
+                    None,
+                ));
+            }
+        };
+
         loop {
             let request = StartRequest {
                 user_id: self.user_id.clone(),
@@ -65,7 +133,7 @@ impl GameConnection {
                 level,
                 seed,
             };
-            let response = self.client.start(request.clone()).await?.into_inner();
+            let response = client.start(request.clone()).await?.into_inner();
 
             let result = StartResult::try_from(response.result).unwrap();
 
@@ -75,7 +143,11 @@ impl GameConnection {
                         .replays_folder
                         .as_ref()
                         .and_then(|folder| ReplayFile::new(folder, &request, &response).ok());
-                    return Ok(Game::new(self.client.clone(), response, replay_file));
+                    return Ok(Game::new(
+                        GameBackend::Remote(client.clone()),
+                        response,
+                        replay_file,
+                    ));
                 }
                 StartResult::QuestQueued => {
                     println!("Quest queued, retrying ...");
@@ -88,8 +160,13 @@ impl GameConnection {
     }
 }
 
+enum GameBackend {
+    Remote(GameServiceClient<Channel>),
+    Replay(std::vec::IntoIter<(ActRequest, ActResponse)>),
+}
+
 pub struct Game {
-    client: GameServiceClient<Channel>,
+    backend: GameBackend,
     replay_file: Option<ReplayFile>,
     pub game_id: String,
     pub map_height: i32,
@@ -100,13 +177,9 @@ pub struct Game {
 }
 
 impl Game {
-    fn new(
-        client: GameServiceClient<Channel>,
-        response: StartResponse,
-        replay_file: Option<ReplayFile>,
-    ) -> Self {
+    fn new(backend: GameBackend, response: StartResponse, replay_file: Option<ReplayFile>) -> Self {
         Game {
-            client,
+            backend,
             replay_file,
             game_id: response.game_id.clone().unwrap(),
             map_height: response.map_height.unwrap(),
@@ -127,7 +200,26 @@ impl Game {
             action: Some(action as i32),
             action2: action2.map(|a| a as i32), // For level 12+ two-player control
         };
-        let response = self.client.act(request.clone()).await?.into_inner();
+        let response = match &mut self.backend {
+            GameBackend::Remote(client) => client.act(request.clone()).await?.into_inner(),
+            GameBackend::Replay(steps) => {
+                let Some((recorded, response)) = steps.next() else {
+                    return Err(Box::new(SwoqError::ReplayExhausted {
+                        tick: self.state.tick,
+                    }));
+                };
+                if recorded.action != request.action || recorded.action2 != request.action2 {
+                    let decode =
+                        |action: Option<i32>| action.and_then(|a| DirectedAction::try_from(a).ok());
+                    return Err(Box::new(SwoqError::ReplayDiverged {
+                        tick: self.state.tick,
+                        recorded: (decode(recorded.action), decode(recorded.action2)),
+                        chosen: (Some(action), action2),
+                    }));
+                }
+                response
+            }
+        };
         let result = swoq_interface::ActResult::try_from(response.result).unwrap();
 
         if let Some(ref mut replay_file) = self.replay_file {
@@ -1,5 +1,8 @@
+pub mod config;
+pub mod eval;
 pub mod infra;
 pub mod planners;
+pub mod sim;
 pub mod state;
 pub mod ui;
 
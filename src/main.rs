@@ -1,15 +1,17 @@
+use clap::Parser;
 use dotenv::dotenv;
-use std::env;
+use std::path::Path;
 use std::sync::{Arc, Mutex, mpsc};
 use tracing_subscriber::{EnvFilter, FmtSubscriber};
 
-use robbot::infra::{CompositeObserver, DefaultObserver, GameConnection, VisualizingObserver};
+use robbot::config::{Cli, Config, ConnectionConfig, GameConfig, ObserverKind};
+use robbot::eval;
+use robbot::infra::{
+    CompositeObserver, DefaultObserver, GameConnection, GameObserver, JsonlObserver,
+    ReplayGameConnection, VisualizingObserver,
+};
 use robbot::ui::{GameStateSnapshot, run_visualizer};
-use robbot::planners;
-
-fn get_env_var_i32(key: &str) -> Option<i32> {
-    env::var(key).ok().and_then(|val| val.parse::<i32>().ok())
-}
+use robbot::planners::GameRunner;
 
 fn init_logging() {
     let filter =
@@ -26,57 +28,115 @@ fn init_logging() {
     tracing::subscriber::set_global_default(subscriber).expect("setting default subscriber failed");
 }
 
-async fn run_heuristic_game_loop(
-    mut game: planners::heuristic::Game,
-    level: Option<i32>,
-    seed: Option<i32>,
-    loop_enabled: bool,
-) -> Result<(), Box<dyn std::error::Error>> {
-    // If loop is enabled, restart the game indefinitely when it ends
-    if loop_enabled {
-        loop {
-            tracing::info!("Starting game for level {:?}", level);
-            match game.run(level, seed).await {
-                Ok(_) => {
-                    tracing::info!("Game ended successfully, restarting...");
-                }
-                Err(e) => {
-                    tracing::error!("Game failed: {:?}", e);
-                    tracing::error!("HALTING: Loop mode stopped due to game failure");
-                    return Err(e);
-                }
-            }
-            tokio::time::sleep(tokio::time::Duration::from_secs(1)).await;
+async fn create_connection(
+    connection: &ConnectionConfig,
+) -> Result<GameConnection, Box<dyn std::error::Error>> {
+    match &connection.replay_file {
+        Some(path) => {
+            tracing::info!("Replaying {}", path);
+            let replay = ReplayGameConnection::open(Path::new(path))?;
+            Ok(GameConnection::from_replay(replay))
         }
-    } else {
-        game.run(level, seed).await
+        None => {
+            // Validated to be present when not replaying
+            GameConnection::new(
+                connection.user_id.clone().unwrap_or_default(),
+                connection.user_name.clone().unwrap_or_default(),
+                connection.host.clone().unwrap_or_default(),
+                connection.replays_folder.clone(),
+            )
+            .await
+        }
+    }
+}
+
+/// Evaluation mode: play every configured level and seed headless and write the report
+async fn run_evaluation(config: &Config) -> Result<(), Box<dyn std::error::Error>> {
+    let connection = &config.connection;
+    let report_path = &config.eval.report;
+    let config = config.eval_config();
+
+    tracing::info!("Evaluating {:?}", config);
+    let report = eval::run_evaluation(&config, || {
+        GameConnection::new(
+            connection.user_id.clone().unwrap_or_default(),
+            connection.user_name.clone().unwrap_or_default(),
+            connection.host.clone().unwrap_or_default(),
+            None,
+        )
+    })
+    .await?;
+
+    for stats in report.level_stats() {
+        tracing::info!(
+            "{} level {}: {}/{} succeeded, {} deaths, ticks p50={:?} p90={:?}",
+            stats.planner,
+            stats.level,
+            stats.successes,
+            stats.games,
+            stats.deaths,
+            stats.ticks_p50,
+            stats.ticks_p90
+        );
+    }
+
+    let csv_path = format!("{}.csv", report_path);
+    let json_path = format!("{}.json", report_path);
+    report.write_csv(Path::new(&csv_path))?;
+    report.write_json(Path::new(&json_path))?;
+    tracing::info!("Evaluation report written to {} and {}", csv_path, json_path);
+    Ok(())
+}
+
+fn create_runner(
+    config: &Config,
+    connection: GameConnection,
+    mut observers: Vec<Box<dyn GameObserver>>,
+) -> Result<GameRunner, Box<dyn std::error::Error>> {
+    if let Some(path) = &config.game.event_log {
+        tracing::info!("Writing game events to {}", path);
+        observers.push(Box::new(JsonlObserver::create(Path::new(path))?));
     }
+    let planner = config.game.planner.create(&config.goap, &config.rl)?;
+    let observer = CompositeObserver::new(observers);
+    Ok(GameRunner::new(connection, observer, planner).with_cbs_limits(config.cbs))
 }
 
-async fn run_goap_game_loop(
-    mut game: planners::goap::Game,
-    level: Option<i32>,
-    seed: Option<i32>,
-    loop_enabled: bool,
+/// Play every configured level and seed, `loop_count` times or forever when it is 0
+async fn run_game_loop(
+    mut runner: GameRunner,
+    game: &GameConfig,
 ) -> Result<(), Box<dyn std::error::Error>> {
-    // If loop is enabled, restart the game indefinitely when it ends
-    if loop_enabled {
-        loop {
-            tracing::info!("Starting game for level {:?}", level);
-            match game.run(level, seed).await {
-                Ok(_) => {
-                    tracing::info!("Game ended successfully, restarting...");
-                }
-                Err(e) => {
+    // An empty list lets the server choose
+    let choices = |values: &[i32]| -> Vec<Option<i32>> {
+        if values.is_empty() {
+            vec![None]
+        } else {
+            values.iter().copied().map(Some).collect()
+        }
+    };
+    let levels = choices(&game.levels);
+    let seeds = choices(&game.seeds);
+
+    let mut pass = 0;
+    loop {
+        for &level in &levels {
+            for &seed in &seeds {
+                tracing::info!("Starting game for level {:?} seed {:?}", level, seed);
+                if let Err(e) = runner.run(level, seed).await {
                     tracing::error!("Game failed: {:?}", e);
                     tracing::error!("HALTING: Loop mode stopped due to game failure");
                     return Err(e);
                 }
             }
-            tokio::time::sleep(tokio::time::Duration::from_secs(1)).await;
         }
-    } else {
-        game.run(level, seed).await
+
+        pass += 1;
+        if game.loop_count != 0 && pass >= game.loop_count {
+            return Ok(());
+        }
+        tracing::info!("Game ended successfully, restarting...");
+        tokio::time::sleep(tokio::time::Duration::from_secs(1)).await;
     }
 }
 
@@ -85,36 +145,22 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     dotenv().ok();
     init_logging();
 
-    let user_id = env::var("SWOQ_USER_ID")
-        .expect("SWOQ_USER_ID environment variable is required, see README.md");
-    let user_name = env::var("SWOQ_USER_NAME")
-        .expect("SWOQ_USER_NAME environment variable is required, see README.md");
-    let host =
-        env::var("SWOQ_HOST").expect("SWOQ_HOST environment variable is required, see README.md");
-    let level = get_env_var_i32("SWOQ_LEVEL");
-    let seed = get_env_var_i32("SWOQ_SEED");
-    let replays_folder = env::var("SWOQ_REPLAYS_FOLDER").ok();
-    let enable_viz = env::var("SWOQ_VISUALIZER")
-        .ok()
-        .and_then(|v| v.parse::<bool>().ok())
-        .unwrap_or(false);
-    let loop_enabled = env::var("SWOQ_LOOP")
-        .ok()
-        .and_then(|v| v.parse::<bool>().ok())
-        .unwrap_or(false);
-    let goap_enabled = env::var("SWOQ_GOAP_ENABLED")
-        .ok()
-        .and_then(|v| v.parse::<bool>().ok())
-        .unwrap_or(false);
-    let goap_max_depth = env::var("SWOQ_GOAP_MAX_DEPTH")
-        .ok()
-        .and_then(|v| v.parse::<usize>().ok())
-        .unwrap_or(10);
-
-    tracing::info!("Visualizer enabled: {}", enable_viz);
-    tracing::info!("GOAP enabled: {}", goap_enabled);
-
-    if enable_viz {
+    let config = match Cli::parse().resolve() {
+        Ok(config) => config,
+        Err(e) => {
+            eprintln!("robbot: {}", e);
+            std::process::exit(2);
+        }
+    };
+
+    if config.is_evaluation() {
+        return run_evaluation(&config).await;
+    }
+
+    tracing::info!("Observer: {:?}", config.game.observer);
+    tracing::info!("Planner: {}", config.game.planner.name());
+
+    if config.game.observer == ObserverKind::Visualizer {
         let shared_state: Arc<Mutex<Option<GameStateSnapshot>>> = Arc::new(Mutex::new(None));
         let game_state = Arc::clone(&shared_state);
 
@@ -126,36 +172,24 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
 
             let rt = tokio::runtime::Runtime::new().unwrap();
             rt.block_on(async move {
-                let connection = GameConnection::new(user_id, user_name, host, replays_folder)
-                    .await
-                    .unwrap();
-                let composite = CompositeObserver::new(vec![
+                let connection = create_connection(&config.connection).await.unwrap();
+                let observers: Vec<Box<dyn GameObserver>> = vec![
                     Box::new(DefaultObserver::default()),
                     Box::new(VisualizingObserver::new(game_state, log_tx)),
-                ]);
-
-                if goap_enabled {
-                    let game = planners::goap::Game::new(connection, composite, goap_max_depth);
-                    let _ = run_goap_game_loop(game, level, seed, loop_enabled).await;
-                } else {
-                    let game = planners::heuristic::Game::new(connection, composite);
-                    let _ = run_heuristic_game_loop(game, level, seed, loop_enabled).await;
-                }
+                ];
+
+                let runner = create_runner(&config, connection, observers).unwrap();
+                let _ = run_game_loop(runner, &config.game).await;
             });
         });
 
         run_visualizer(shared_state, ready_tx, log_rx);
     } else {
-        let connection = GameConnection::new(user_id, user_name, host, replays_folder).await?;
+        let connection = create_connection(&config.connection).await?;
 
-        if goap_enabled {
-            let game =
-                planners::goap::Game::new(connection, DefaultObserver::default(), goap_max_depth);
-            run_goap_game_loop(game, level, seed, loop_enabled).await?;
-        } else {
-            let game = planners::heuristic::Game::new(connection, DefaultObserver::default());
-            run_heuristic_game_loop(game, level, seed, loop_enabled).await?;
-        }
+        let runner =
+            create_runner(&config, connection, vec![Box::new(DefaultObserver::default())])?;
+        run_game_loop(runner, &config.game).await?;
     }
 
     Ok(())
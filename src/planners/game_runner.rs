@@ -0,0 +1,195 @@
+use std::time::{Duration, Instant};
+
+use crate::infra::{CbsLimits, GameConnection, GameObserver, GameSummary, Position};
+use crate::planners::Planner;
+use crate::state::WorldState;
+use crate::swoq_interface::{self, GameStatus};
+
+/// Plays games against a `GameConnection`, asking a `Planner` for the actions of every tick
+pub struct GameRunner {
+    connection: GameConnection,
+    observer: Box<dyn GameObserver>,
+    planner: Box<dyn Planner>,
+    world: WorldState,
+    cbs_limits: CbsLimits,
+    current_level: i32,
+    level_started: bool,
+
+    // Game statistics (persistent across levels)
+    pub successful_runs: i32,
+    pub failed_runs: i32,
+    pub game_count: i32,
+}
+
+impl GameRunner {
+    pub fn new(
+        connection: GameConnection,
+        observer: impl GameObserver + 'static,
+        planner: Box<dyn Planner>,
+    ) -> Self {
+        Self {
+            connection,
+            observer: Box::new(observer),
+            planner,
+            world: WorldState::new(0, 0, 0),
+            cbs_limits: CbsLimits::default(),
+            current_level: 0,
+            level_started: false,
+            successful_runs: 0,
+            failed_runs: 0,
+            game_count: 0,
+        }
+    }
+
+    /// Search limits for the multi-player paths of every level
+    pub fn with_cbs_limits(mut self, limits: CbsLimits) -> Self {
+        self.cbs_limits = limits;
+        self
+    }
+
+    pub async fn run(
+        &mut self,
+        level: Option<i32>,
+        seed: Option<i32>,
+    ) -> Result<GameSummary, Box<dyn std::error::Error>> {
+        let mut game = self.connection.start(level, seed).await?;
+
+        self.game_count += 1;
+
+        self.observer.on_game_start(
+            &game.game_id,
+            game.seed,
+            game.map_width,
+            game.map_height,
+            game.visibility_range,
+        );
+
+        self.new_world(&game);
+        self.current_level = game.state.level;
+        self.level_started = false;
+        let mut planning_time = Duration::ZERO;
+
+        loop {
+            if game.state.status != swoq_interface::GameStatus::Active as i32 {
+                break;
+            }
+
+            let tick_start = Instant::now();
+
+            self.check_level(&game);
+            self.update_world(&game.state);
+            // Planners see the level after its first state update, so players are known
+            if !self.level_started {
+                self.planner.on_level_start(&self.world);
+                self.level_started = true;
+            }
+
+            let planning_start = Instant::now();
+            let (action1, action2) = self.planner.decide(&mut self.world);
+            planning_time += planning_start.elapsed();
+            self.report_decision(action1);
+
+            let action2 = if self.world.players.len() > 1 {
+                action2
+            } else {
+                None
+            };
+            let action_result = game.act(action1, action2).await?;
+            self.observer
+                .on_action_result(action1, action2, action_result, &self.world);
+
+            // Log slow ticks
+            let tick_duration = tick_start.elapsed();
+            if tick_duration.as_millis() > 100 {
+                tracing::debug!(
+                    "⚠️  Tick {} took {:.2}ms result: {:?})",
+                    game.state.tick,
+                    tick_duration.as_secs_f64() * 1000.0,
+                    action_result
+                );
+            }
+
+            if action_result != swoq_interface::ActResult::Ok {
+                tracing::debug!("\n❌ Action failed with result: {:?}", action_result);
+                tracing::debug!("🛑 Stopping game due to action error");
+                break;
+            }
+        }
+
+        let status =
+            GameStatus::try_from(game.state.status).unwrap_or(GameStatus::FinishedCanceled);
+
+        // Update statistics
+        match status {
+            GameStatus::FinishedSuccess => self.successful_runs += 1,
+            _ => self.failed_runs += 1,
+        }
+
+        self.observer.on_game_finished(
+            status,
+            game.state.tick,
+            self.game_count,
+            self.successful_runs,
+            self.failed_runs,
+        );
+
+        let summary = GameSummary::new(&game, planning_time);
+        self.planner.on_game_finished(&summary);
+        Ok(summary)
+    }
+
+    fn check_level(&mut self, game: &crate::infra::swoq::Game) {
+        if game.state.level != self.current_level {
+            self.observer.on_new_level(game.state.level);
+            // Create a new WorldState for the new level
+            self.new_world(game);
+            self.level_started = false;
+            self.current_level = game.state.level;
+        }
+    }
+
+    fn new_world(&mut self, game: &crate::infra::swoq::Game) {
+        self.world = WorldState::new(game.map_width, game.map_height, game.visibility_range);
+        self.world.cbs_limits = self.cbs_limits;
+    }
+
+    fn update_world(&mut self, state: &swoq_interface::State) {
+        tracing::debug!("\n┌────────────────────────────────────────────────────────────┐");
+        tracing::debug!(
+            "│ 📊 STATE UPDATE - Tick {}                                  ",
+            state.tick
+        );
+        tracing::debug!("└────────────────────────────────────────────────────────────┘");
+
+        self.world.update(state);
+        self.observer.on_state_update(
+            state,
+            &self.world,
+            self.game_count,
+            self.successful_runs,
+            self.failed_runs,
+        );
+    }
+
+    /// Pass the planner's goals, paths and warnings on to the observer
+    fn report_decision(&mut self, action1: swoq_interface::DirectedAction) {
+        for (player_index, goal_name) in self.planner.goal_names().iter().enumerate() {
+            self.observer
+                .on_goal_selected(player_index, goal_name, &self.world);
+        }
+        self.observer.on_action_selected(action1, &self.world);
+
+        // Update observer with current paths for visualization
+        let paths: Vec<Option<Vec<Position>>> = self
+            .world
+            .players
+            .iter()
+            .map(|player| player.current_path.clone())
+            .collect();
+        self.observer.on_paths_updated(paths);
+
+        for warning in self.planner.take_warnings() {
+            self.observer.on_oscillation_detected(&warning);
+        }
+    }
+}
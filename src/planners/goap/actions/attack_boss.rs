@@ -0,0 +1,120 @@
+use crate::infra::Position;
+use crate::planners::goap::game_state::PlanningState;
+use crate::state::{BOSS_DAMAGE, BOSS_FIGHT_MIN_HEALTH, WorldState};
+use crate::swoq_interface::{DirectedAction, Tile};
+
+use super::helpers::execute_use_adjacent;
+use super::{ActionExecutionState, ExecutionStatus, GOAPActionTrait};
+
+/// Hits the boss is expected to land before it dies, for the planned health
+const BOSS_HITS_TAKEN: i32 = 2;
+
+#[derive(Debug, Clone)]
+pub struct AttackBossAction {
+    pub boss_pos: Position,
+    pub cached_distance: u32,
+}
+
+impl GOAPActionTrait for AttackBossAction {
+    fn precondition(
+        &self,
+        world: &WorldState,
+        _state: &PlanningState,
+        player_index: usize,
+    ) -> bool {
+        let player = &world.players[player_index];
+        // For planning: need sword, enough health and the boss must still be alive
+        player.has_sword
+            && player.health >= BOSS_FIGHT_MIN_HEALTH
+            && world.boss_position == Some(self.boss_pos)
+    }
+
+    fn effect_end(&self, world: &mut WorldState, _state: &mut PlanningState, player_index: usize) {
+        // The boss drops the treasure where it dies (for planning simulation)
+        world.boss_position = None;
+        world.treasure_position = Some(self.boss_pos);
+        world.map.insert(self.boss_pos, Tile::Treasure);
+
+        world.players[player_index].position = self.boss_pos;
+        // Boss hits back hard during the fight
+        world.players[player_index].health -= BOSS_HITS_TAKEN * BOSS_DAMAGE;
+    }
+
+    fn prepare(&mut self, world: &mut WorldState, _player_index: usize) -> Option<Position> {
+        // The boss moves, follow its latest known position
+        world.boss_position
+    }
+
+    fn execute(
+        &self,
+        world: &mut WorldState,
+        player_index: usize,
+        execution_state: &mut ActionExecutionState,
+    ) -> (DirectedAction, ExecutionStatus) {
+        // Health too low to continue attacking (would die)
+        if world.players[player_index].health < 2 {
+            execution_state.enemy_under_attack = None;
+            return (DirectedAction::None, ExecutionStatus::Complete);
+        }
+
+        let Some(boss_pos) = world.boss_position else {
+            // Boss is dead (or out of sight) - action complete
+            execution_state.enemy_under_attack = None;
+            return (DirectedAction::None, ExecutionStatus::Complete);
+        };
+
+        let (action, status) = execute_use_adjacent(world, player_index, boss_pos, execution_state);
+        if matches!(status, ExecutionStatus::Complete) && !matches!(action, DirectedAction::None) {
+            // Keep hitting until the boss disappears
+            execution_state.enemy_under_attack = Some(boss_pos);
+            (action, ExecutionStatus::InProgress)
+        } else {
+            (action, status)
+        }
+    }
+
+    fn cost(&self, _world: &WorldState, _state: &PlanningState, _player_index: usize) -> f32 {
+        15.0 + self.cached_distance as f32 * 0.1
+    }
+
+    fn duration(&self, _world: &WorldState, _state: &PlanningState, _player_index: usize) -> u32 {
+        // Walk up to the boss and hit it a number of times
+        self.cached_distance + 10
+    }
+
+    fn name(&self) -> String {
+        "AttackBoss".to_string()
+    }
+
+    fn reward(&self, _world: &WorldState, _state: &PlanningState, _player_index: usize) -> f32 {
+        // Killing the boss is the only way to get the treasure
+        40.0
+    }
+
+    fn is_combat_action(&self) -> bool {
+        true
+    }
+
+    fn generate(
+        world: &WorldState,
+        state: &PlanningState,
+        player_index: usize,
+    ) -> Vec<Box<dyn GOAPActionTrait>> {
+        let mut actions = Vec::new();
+        let player = &world.players[player_index];
+
+        if let Some(boss_pos) = world.boss_position {
+            let action = AttackBossAction {
+                boss_pos,
+                cached_distance: world
+                    .path_distance_to_enemy(player.position, boss_pos)
+                    .clamp(0, 100) as u32,
+            };
+            if action.precondition(world, state, player_index) {
+                actions.push(Box::new(action) as Box<dyn GOAPActionTrait>);
+            }
+        }
+
+        actions
+    }
+}
@@ -2,7 +2,7 @@ use crate::planners::goap::game_state::PlanningState;
 use crate::state::WorldState;
 use crate::swoq_interface::DirectedAction;
 
-use super::helpers::execute_avoid;
+use super::helpers::{closest_danger, execute_avoid};
 use super::{ActionExecutionState, ExecutionStatus, GOAPActionTrait};
 
 #[derive(Debug, Clone)]
@@ -13,7 +13,7 @@ impl AvoidEnemyAction {
 
 impl GOAPActionTrait for AvoidEnemyAction {
     fn precondition(&self, world: &WorldState, _state: &PlanningState, _player_index: usize) -> bool {
-        !world.enemies.is_empty()
+        !world.enemies.is_empty() || world.boss_position.is_some()
     }
 
     fn effect_end(&self, _world: &mut WorldState, _state: &mut PlanningState, _player_index: usize) {}
@@ -27,7 +27,7 @@ impl GOAPActionTrait for AvoidEnemyAction {
         let player = &world.players[player_index];
 
         // Find closest enemy in current world state (enemies move!)
-        if let Some(closest_enemy_pos) = world.enemies.closest_to(player.position) {
+        if let Some(closest_enemy_pos) = closest_danger(world, player.position) {
             let distance = world.path_distance_to_enemy(player.position, closest_enemy_pos);
 
             // Continue avoiding until enemy is at least 3 tiles away
@@ -46,7 +46,7 @@ impl GOAPActionTrait for AvoidEnemyAction {
 
     fn cost(&self, world: &WorldState, _state: &PlanningState, player_index: usize) -> f32 {
         let player = &world.players[player_index];
-        let distance = if let Some(closest_enemy) = world.enemies.closest_to(player.position) {
+        let distance = if let Some(closest_enemy) = closest_danger(world, player.position) {
             world.path_distance_to_enemy(player.position, closest_enemy)
         } else {
             1000
@@ -89,11 +89,16 @@ impl GOAPActionTrait for AvoidEnemyAction {
             return actions;
         }
 
-        // Check if any enemy is close (within 3 tiles)
-        let has_close_enemy = world.enemies.get_positions().iter().any(|enemy_pos| {
-            let dist = world.path_distance_to_enemy(player.position, *enemy_pos);
-            dist < 3
-        });
+        // Check if any enemy or the boss is close (within 3 tiles)
+        let has_close_enemy = world
+            .enemies
+            .get_positions()
+            .iter()
+            .chain(world.boss_position.iter())
+            .any(|enemy_pos| {
+                let dist = world.path_distance_to_enemy(player.position, *enemy_pos);
+                dist < 3
+            });
 
         if has_close_enemy {
             let action = AvoidEnemyAction {};
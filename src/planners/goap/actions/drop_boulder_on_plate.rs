@@ -1,4 +1,4 @@
-use crate::infra::{Color, Position, use_direction};
+use crate::infra::{Color, Position, path_length, use_direction};
 use crate::planners::goap::game_state::PlanningState;
 use crate::state::WorldState;
 use crate::swoq_interface::{DirectedAction, Inventory};
@@ -117,7 +117,7 @@ impl GOAPActionTrait for DropBoulderOnPlateAction {
                         if world.is_walkable(&adj, None)
                             && let Some(path) = world.find_path(player.position, adj)
                         {
-                            let distance = path.len() as u32;
+                            let distance = path_length(&path) as u32;
                             if best_option.is_none() || distance < best_option.unwrap().1 {
                                 best_option = Some((adj, distance));
                             }
@@ -1,24 +1,18 @@
+use crate::infra::path_length;
 use crate::planners::goap::actions::helpers::execute_move_to;
 use crate::planners::goap::game_state::PlanningState;
 use crate::state::WorldState;
+use crate::state::exploration::{exploration_clusters, frontier_owner, ranked_frontier};
 use crate::swoq_interface::DirectedAction;
 
 use super::{ActionExecutionState, ExecutionStatus, GOAPActionTrait};
 
 #[derive(Debug, Clone)]
 pub struct ExploreAction {
-    pub cached_distance: u32, // Distance to nearest frontier (for cost/duration)
+    pub cached_distance: u32, // Distance to best frontier (for cost/duration)
 }
 
 impl ExploreAction {
-    fn find_nearest_frontier(player: &crate::state::PlayerState) -> Option<crate::infra::Position> {
-        player
-            .unexplored_frontier
-            .iter()
-            .min_by_key(|p| player.position.distance(p))
-            .copied()
-    }
-
     fn count_objects(world: &WorldState) -> super::ObjectCounts {
         super::ObjectCounts {
             num_keys: [
@@ -74,12 +68,14 @@ impl GOAPActionTrait for ExploreAction {
         let player = &world.players[player_index];
         let player_pos = player.position;
         let current_dest = player.current_destination;
-        let frontier = player.unexplored_frontier.clone();
 
         // Check if we have a current destination
         if let Some(current_dest) = current_dest {
             // Check if destination is still valid (unknown, Unknown, or empty, and not reached yet)
-            if player_pos != current_dest {
+            // and was not reallocated to the other player
+            if player_pos != current_dest
+                && frontier_owner(world, current_dest).is_none_or(|owner| owner == player_index)
+            {
                 if let Some(tile) = world.map.get(&current_dest) {
                     // Tile is known - check if it's Unknown or empty
                     if matches!(
@@ -104,17 +100,10 @@ impl GOAPActionTrait for ExploreAction {
             // Destination reached or became non-empty/non-Unknown, find new one
         }
 
-        // Find nearest reachable frontier as new target
-        // Iterate through frontier sorted by distance and return first reachable one
-        let mut frontier_by_distance: Vec<_> = frontier
-            .iter()
-            .map(|&pos| (player_pos.distance(&pos), pos))
-            .collect();
-        frontier_by_distance.sort_by_key(|(dist, _)| *dist);
-
-        frontier_by_distance
+        // Find the most informative reachable frontier as new target
+        // Iterate through frontier ranked by information gain and return first reachable one
+        ranked_frontier(world, player_index)
             .into_iter()
-            .map(|(_, frontier_pos)| frontier_pos)
             .find(|&frontier_pos| world.find_path(player_pos, frontier_pos).is_some())
     }
 
@@ -253,21 +242,22 @@ impl GOAPActionTrait for ExploreAction {
             unexplored_frontier_size = player.unexplored_frontier.len(),
             "Generating ExploreAction"
         );
-        // Find nearest frontier and cache the distance for cost/duration
-        if let Some(nearest) = Self::find_nearest_frontier(player) {
+        // Find the best frontier cluster and cache the distance for cost/duration
+        if let Some(best) = exploration_clusters(world, player_index).first() {
             tracing::trace!(
                 player_index = player_index,
-                nearest_frontier = ?nearest,
+                best_frontier = ?best.target,
+                gain = best.gain,
                 "Generating ExploreAction"
             );
-            if let Some(path) = world.find_path(player.position, nearest) {
+            if let Some(path) = world.find_path(player.position, best.target) {
                 tracing::trace!(
                     player_index = player_index,
                     path_length = path.len(),
-                    "Found path to nearest frontier"
+                    "Found path to best frontier"
                 );
                 let action = ExploreAction {
-                    cached_distance: path.len() as u32,
+                    cached_distance: path_length(&path) as u32,
                 };
                 if action.precondition(world, state, player_index) {
                     return vec![Box::new(action)];
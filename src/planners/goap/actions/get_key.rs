@@ -1,4 +1,4 @@
-use crate::infra::{Color, Position};
+use crate::infra::{Color, Position, path_length};
 use crate::planners::goap::game_state::{PlanningState, ResourceClaim};
 use crate::state::WorldState;
 use crate::swoq_interface::{DirectedAction, Inventory};
@@ -42,12 +42,7 @@ impl GOAPActionTrait for GetKeyAction {
         !already_claimed
     }
 
-    fn effect_start(
-        &self,
-        _world: &mut WorldState,
-        state: &mut PlanningState,
-        player_index: usize,
-    ) {
+    fn effect_start(&self, _world: &WorldState, state: &mut PlanningState, player_index: usize) {
         // Claim this key to prevent other players from targeting it
         let claim = ResourceClaim::Key(self.color);
         state.resource_claims.insert(claim, player_index);
@@ -118,7 +113,7 @@ impl GOAPActionTrait for GetKeyAction {
                         let action = GetKeyAction {
                             color,
                             key_pos: *key_pos,
-                            cached_distance: path.len() as u32,
+                            cached_distance: path_length(&path) as u32,
                         };
                         // Check other preconditions
                         if action.precondition(world, state, player_index) {
@@ -1,3 +1,4 @@
+mod attack_boss;
 mod attack_enemy;
 mod avoid_enemy;
 mod drop_boulder;
@@ -11,11 +12,13 @@ mod pass_through_door_with_plate;
 mod pickup_boulder;
 mod pickup_health;
 mod pickup_sword;
+mod pickup_treasure;
 mod reach_exit;
 mod touch_plate;
 mod wait;
 mod wait_on_plate;
 
+pub use attack_boss::AttackBossAction;
 pub use attack_enemy::AttackEnemyAction;
 pub use avoid_enemy::AvoidEnemyAction;
 pub use drop_boulder::DropBoulderAction;
@@ -28,6 +31,7 @@ pub use pass_through_door_with_plate::PassThroughDoorWithPlateAction;
 pub use pickup_boulder::PickupBoulderAction;
 pub use pickup_health::PickupHealthAction;
 pub use pickup_sword::PickupSwordAction;
+pub use pickup_treasure::PickupTreasureAction;
 pub use reach_exit::ReachExitAction;
 pub use touch_plate::TouchPlateAction;
 pub use wait::WaitAction;
@@ -43,12 +47,7 @@ pub trait GOAPActionTrait: std::fmt::Debug + GOAPActionClone {
     fn precondition(&self, world: &WorldState, state: &PlanningState, player_index: usize) -> bool;
 
     /// Called when action is added to plan - claim resources to prevent conflicts
-    fn effect_start(
-        &self,
-        _world: &mut WorldState,
-        _state: &mut PlanningState,
-        _player_index: usize,
-    ) {
+    fn effect_start(&self, _world: &WorldState, _state: &mut PlanningState, _player_index: usize) {
         // Default: no claims needed
     }
 
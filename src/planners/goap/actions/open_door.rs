@@ -1,4 +1,4 @@
-use crate::infra::{Color, Position};
+use crate::infra::{Color, Position, path_length};
 use crate::planners::goap::game_state::{PlanningState, ResourceClaim};
 use crate::state::WorldState;
 use crate::swoq_interface::{DirectedAction, Inventory};
@@ -57,12 +57,7 @@ impl GOAPActionTrait for OpenDoorAction {
         !already_claimed
     }
 
-    fn effect_start(
-        &self,
-        _world: &mut WorldState,
-        state: &mut PlanningState,
-        player_index: usize,
-    ) {
+    fn effect_start(&self, _world: &WorldState, state: &mut PlanningState, player_index: usize) {
         // Claim this door to prevent other players from targeting it
         let claim = ResourceClaim::Door(self.color);
         state.resource_claims.insert(claim, player_index);
@@ -132,7 +127,7 @@ impl GOAPActionTrait for OpenDoorAction {
                         let action = OpenDoorAction {
                             color,
                             door_pos: *door_pos,
-                            cached_distance: path.len() as u32,
+                            cached_distance: path_length(&path) as u32,
                         };
                         if action.precondition(world, state, player_index) {
                             actions.push(Box::new(action) as Box<dyn GOAPActionTrait>);
@@ -45,12 +45,7 @@ impl GOAPActionTrait for PickupHealthAction {
         !already_claimed
     }
 
-    fn effect_start(
-        &self,
-        _world: &mut WorldState,
-        state: &mut PlanningState,
-        player_index: usize,
-    ) {
+    fn effect_start(&self, _world: &WorldState, state: &mut PlanningState, player_index: usize) {
         // Claim this health pickup to prevent other players from targeting it
         let claim = ResourceClaim::Health(self.health_pos);
         state.resource_claims.insert(claim, player_index);
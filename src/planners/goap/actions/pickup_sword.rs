@@ -38,12 +38,7 @@ impl GOAPActionTrait for PickupSwordAction {
         !already_claimed
     }
 
-    fn effect_start(
-        &self,
-        _world: &mut WorldState,
-        state: &mut PlanningState,
-        player_index: usize,
-    ) {
+    fn effect_start(&self, _world: &WorldState, state: &mut PlanningState, player_index: usize) {
         // Claim this sword to prevent other players from targeting it
         let claim = ResourceClaim::Sword(self.sword_pos);
         state.resource_claims.insert(claim, player_index);
@@ -0,0 +1,116 @@
+use crate::infra::{Position, path_length};
+use crate::planners::goap::game_state::{PlanningState, ResourceClaim};
+use crate::state::WorldState;
+use crate::swoq_interface::{DirectedAction, Inventory, Tile};
+
+use super::helpers::execute_move_to;
+use super::{ActionExecutionState, ExecutionStatus, GOAPActionTrait};
+
+#[derive(Debug, Clone)]
+pub struct PickupTreasureAction {
+    pub treasure_pos: Position,
+    pub cached_distance: u32,
+}
+
+impl GOAPActionTrait for PickupTreasureAction {
+    fn precondition(&self, world: &WorldState, state: &PlanningState, player_index: usize) -> bool {
+        let player = &world.players[player_index];
+
+        // Treasure must be lying on the map and the inventory must be free
+        if world.treasure_position != Some(self.treasure_pos) || player.inventory != Inventory::None
+        {
+            return false;
+        }
+
+        // Validate path exists
+        if world
+            .find_path(player.position, self.treasure_pos)
+            .is_none()
+        {
+            return false;
+        }
+
+        // Check if the treasure is already claimed by another player
+        let already_claimed = state
+            .resource_claims
+            .get(&ResourceClaim::Treasure)
+            .is_some_and(|&claimer| claimer != player_index);
+
+        !already_claimed
+    }
+
+    fn effect_start(&self, _world: &WorldState, state: &mut PlanningState, player_index: usize) {
+        state
+            .resource_claims
+            .insert(ResourceClaim::Treasure, player_index);
+    }
+
+    fn effect_end(&self, world: &mut WorldState, _state: &mut PlanningState, player_index: usize) {
+        let player = &mut world.players[player_index];
+        player.inventory = Inventory::Treasure;
+        player.position = self.treasure_pos;
+        // Remove treasure from the map (for planning simulation)
+        world.treasure_position = None;
+        world.map.insert(self.treasure_pos, Tile::Empty);
+    }
+
+    fn prepare(&mut self, world: &mut WorldState, player_index: usize) -> Option<Position> {
+        let player = &world.players[player_index];
+        if world
+            .find_path(player.position, self.treasure_pos)
+            .is_some()
+        {
+            Some(self.treasure_pos)
+        } else {
+            None
+        }
+    }
+
+    fn execute(
+        &self,
+        world: &mut WorldState,
+        player_index: usize,
+        execution_state: &mut ActionExecutionState,
+    ) -> (DirectedAction, ExecutionStatus) {
+        execute_move_to(world, player_index, self.treasure_pos, execution_state)
+    }
+
+    fn cost(&self, _world: &WorldState, _state: &PlanningState, _player_index: usize) -> f32 {
+        self.cached_distance as f32 * 0.1
+    }
+
+    fn duration(&self, _world: &WorldState, _state: &PlanningState, _player_index: usize) -> u32 {
+        self.cached_distance
+    }
+
+    fn name(&self) -> String {
+        "PickupTreasure".to_string()
+    }
+
+    fn reward(&self, _world: &WorldState, _state: &PlanningState, _player_index: usize) -> f32 {
+        40.0
+    }
+
+    fn generate(
+        world: &WorldState,
+        state: &PlanningState,
+        player_index: usize,
+    ) -> Vec<Box<dyn GOAPActionTrait>> {
+        let mut actions = Vec::new();
+        let player = &world.players[player_index];
+
+        if let Some(treasure_pos) = world.treasure_position
+            && let Some(path) = world.find_path(player.position, treasure_pos)
+        {
+            let action = PickupTreasureAction {
+                treasure_pos,
+                cached_distance: path_length(&path) as u32,
+            };
+            if action.precondition(world, state, player_index) {
+                actions.push(Box::new(action) as Box<dyn GOAPActionTrait>);
+            }
+        }
+
+        actions
+    }
+}
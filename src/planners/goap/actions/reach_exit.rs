@@ -1,7 +1,7 @@
-use crate::infra::Position;
+use crate::infra::{Position, path_length};
 use crate::planners::goap::game_state::PlanningState;
 use crate::state::WorldState;
-use crate::swoq_interface::DirectedAction;
+use crate::swoq_interface::{DirectedAction, Inventory};
 
 use super::helpers::execute_move_to;
 use super::{ActionExecutionState, ExecutionStatus, GOAPActionTrait};
@@ -26,8 +26,10 @@ impl GOAPActionTrait for ReachExitAction {
             return false;
         }
 
-        // For planning: player must have empty inventory
-        if player.inventory != crate::swoq_interface::Inventory::None {
+        // For planning: player must have empty inventory, or carry the treasure out
+        if !matches!(player.inventory, Inventory::None | Inventory::Treasure)
+            || !world.treasure_allows_exit(player)
+        {
             return false;
         }
 
@@ -55,7 +57,12 @@ impl GOAPActionTrait for ReachExitAction {
     }
 
     fn effect_end(&self, world: &mut WorldState, _state: &mut PlanningState, player_index: usize) {
-        world.players[player_index].position = self.exit_pos;
+        let player = &mut world.players[player_index];
+        player.position = self.exit_pos;
+        if player.inventory == Inventory::Treasure {
+            player.inventory = Inventory::None;
+            world.treasure_delivered = true;
+        }
     }
 
     fn prepare(&mut self, world: &mut WorldState, player_index: usize) -> Option<Position> {
@@ -129,7 +136,7 @@ impl GOAPActionTrait for ReachExitAction {
             if let Some(path) = world.find_path(player.position, exit_pos) {
                 let action = ReachExitAction {
                     exit_pos,
-                    cached_distance: path.len() as u32,
+                    cached_distance: path_length(&path) as u32,
                 };
                 if action.precondition(world, state, player_index) {
                     actions.push(Box::new(action) as Box<dyn GOAPActionTrait>);
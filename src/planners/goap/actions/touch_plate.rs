@@ -1,3 +1,5 @@
+use std::sync::Arc;
+
 use crate::infra::{Color, Position};
 use crate::planners::goap::game_state::{PlanningState, ResourceClaim};
 use crate::state::WorldState;
@@ -54,12 +56,7 @@ impl GOAPActionTrait for TouchPlateAction {
         !already_claimed
     }
 
-    fn effect_start(
-        &self,
-        _world: &mut WorldState,
-        state: &mut PlanningState,
-        player_index: usize,
-    ) {
+    fn effect_start(&self, _world: &WorldState, state: &mut PlanningState, player_index: usize) {
         // Claim this pressure plate to prevent other players from targeting it
         let claim = ResourceClaim::PressurePlate(self.plate_color);
         state.resource_claims.insert(claim, player_index);
@@ -96,7 +93,7 @@ impl GOAPActionTrait for TouchPlateAction {
 
             if execution_state.wait_ticks >= 2 {
                 // We've waited 2 ticks, now complete
-                world.plates_touched.insert(self.plate_color);
+                Arc::make_mut(&mut world.plates_touched).insert(self.plate_color);
                 tracing::info!("Recorded plate touch: {:?}", self.plate_color);
                 return (DirectedAction::None, ExecutionStatus::Complete);
             } else {
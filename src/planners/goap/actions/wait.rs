@@ -28,12 +28,7 @@ impl GOAPActionTrait for WaitAction {
         true // Can always wait as fallback
     }
 
-    fn effect_start(
-        &self,
-        _world: &mut WorldState,
-        _state: &mut PlanningState,
-        _player_index: usize,
-    ) {
+    fn effect_start(&self, _world: &WorldState, _state: &mut PlanningState, _player_index: usize) {
         // No state changes
     }
 
@@ -48,12 +48,7 @@ impl GOAPActionTrait for WaitOnPlateAction {
         plate_exists && reachable && !already_claimed
     }
 
-    fn effect_start(
-        &self,
-        _world: &mut WorldState,
-        state: &mut PlanningState,
-        player_index: usize,
-    ) {
+    fn effect_start(&self, _world: &WorldState, state: &mut PlanningState, player_index: usize) {
         // Claim this pressure plate color to prevent other players from targeting it
         let claim = ResourceClaim::PressurePlate(self.color);
         state.resource_claims.insert(claim, player_index);
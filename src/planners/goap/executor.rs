@@ -13,6 +13,9 @@ pub struct PlayerExecutionState {
 
     /// Execution state for tracking multi-tick actions
     pub execution_state: ActionExecutionState,
+
+    /// Whether the current action was executed at least once
+    pub action_started: bool,
 }
 
 impl PlayerExecutionState {
@@ -21,6 +24,7 @@ impl PlayerExecutionState {
             plan_sequence: plan,
             current_action_index: 0,
             execution_state: ActionExecutionState::default(),
+            action_started: false,
         }
     }
 }
@@ -129,6 +133,7 @@ impl Executor {
                 let current_action =
                     player_state.plan_sequence[player_state.current_action_index].clone();
 
+                player_state.action_started = true;
                 let (action, status) =
                     current_action.execute(world, player_id, &mut player_state.execution_state);
 
@@ -148,6 +153,7 @@ impl Executor {
 
                         player_state.current_action_index += 1;
                         player_state.execution_state = ActionExecutionState::default();
+                        player_state.action_started = false;
 
                         if matches!(action, DirectedAction::None) {
                             continue; // Try next action in plan
@@ -236,6 +242,74 @@ impl Executor {
         self.player_states = plans.into_iter().map(PlayerExecutionState::new).collect();
     }
 
+    /// Forget the executed actions of every player, so that the plans of a search started
+    /// from the current world line up with the remaining ones
+    pub fn drop_executed_actions(&mut self) {
+        for player_state in &mut self.player_states {
+            player_state
+                .plan_sequence
+                .drain(..player_state.current_action_index);
+            player_state.current_action_index = 0;
+        }
+    }
+
+    /// Switch to better plans from the search that produced the current ones, at action
+    /// boundaries. Every player must have executed the start of its new plan, and a
+    /// player whose current action changes must not have started it yet. A player that
+    /// keeps its current action keeps its progress. An executor without plans takes the
+    /// new ones as they are. Returns false and leaves the plans alone when that does not
+    /// hold.
+    pub fn swap_plans(&mut self, plans: Vec<Vec<Box<dyn GOAPActionTrait>>>) -> bool {
+        if self.player_states.is_empty() {
+            self.set_plans(plans);
+            return true;
+        }
+        if plans.len() != self.player_states.len() {
+            return false;
+        }
+
+        // Number of actions of each current plan to keep
+        let mut keep = Vec::with_capacity(plans.len());
+        for (player_state, plan) in self.player_states.iter().zip(&plans) {
+            let index = player_state.current_action_index;
+            let same_name = |i: usize| match (player_state.plan_sequence.get(i), plan.get(i)) {
+                (Some(current), Some(new)) => current.name() == new.name(),
+                _ => false,
+            };
+            if !(0..index).all(same_name) {
+                return false;
+            }
+            if same_name(index) {
+                keep.push(index + 1);
+            } else if player_state.action_started {
+                return false;
+            } else {
+                keep.push(index);
+            }
+        }
+
+        for (player_id, (player_state, plan)) in
+            self.player_states.iter_mut().zip(plans).enumerate()
+        {
+            let kept = keep[player_id];
+            player_state.plan_sequence.truncate(kept);
+            player_state
+                .plan_sequence
+                .extend(plan.into_iter().skip(kept));
+            tracing::info!(
+                "GOAP: Player {} improved plan ({} actions): {:?}",
+                player_id,
+                player_state.plan_sequence.len(),
+                player_state
+                    .plan_sequence
+                    .iter()
+                    .map(|a| a.name())
+                    .collect::<Vec<_>>()
+            );
+        }
+        true
+    }
+
     pub fn needs_replan(&self, world: &WorldState) -> (bool, bool) {
         // Only replan when all plans are complete (empty)
         // ExploreAction will mark itself complete when new objects are discovered
@@ -284,8 +358,13 @@ impl Executor {
             let has_sword = player.has_sword;
             let danger_threshold = if has_sword { 2 } else { 3 };
 
-            // Check distance to any enemy
-            for enemy_pos in world.enemies.get_positions() {
+            // Check distance to any enemy, including the boss
+            for enemy_pos in world
+                .enemies
+                .get_positions()
+                .iter()
+                .chain(world.boss_position.iter())
+            {
                 tracing::debug!(
                     "Checking distance from Player {} at {:?} to Enemy at {:?}",
                     player_id,
@@ -327,3 +406,67 @@ impl Default for Executor {
         Self::new()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::planners::goap::actions::WaitAction;
+
+    fn plan(durations: &[u32]) -> Vec<Box<dyn GOAPActionTrait>> {
+        durations
+            .iter()
+            .map(|&duration| Box::new(WaitAction::new(duration)) as Box<dyn GOAPActionTrait>)
+            .collect()
+    }
+
+    fn names(executor: &Executor, player_id: usize) -> Vec<String> {
+        let sequence = &executor.player_states[player_id].plan_sequence;
+        sequence.iter().map(|a| a.name()).collect()
+    }
+
+    #[test]
+    fn test_swap_keeps_the_started_action() {
+        let mut executor = Executor::new();
+        executor.set_plans(vec![plan(&[1, 2])]);
+        executor.player_states[0].action_started = true;
+
+        assert!(executor.swap_plans(vec![plan(&[1, 3, 4])]));
+        assert_eq!(names(&executor, 0), vec!["Wait(1)", "Wait(3)", "Wait(4)"]);
+        assert_eq!(executor.player_states[0].current_action_index, 0);
+        assert!(executor.player_states[0].action_started);
+
+        assert!(!executor.swap_plans(vec![plan(&[5])]));
+        assert_eq!(names(&executor, 0), vec!["Wait(1)", "Wait(3)", "Wait(4)"]);
+    }
+
+    #[test]
+    fn test_swap_at_action_boundary() {
+        let mut executor = Executor::new();
+        executor.set_plans(vec![plan(&[1, 2]), plan(&[7])]);
+        executor.player_states[0].current_action_index = 1;
+
+        // The executed action must stay at the start of the new plan
+        assert!(!executor.swap_plans(vec![plan(&[5, 6]), plan(&[7])]));
+
+        assert!(executor.swap_plans(vec![plan(&[1, 6]), plan(&[7, 8])]));
+        assert_eq!(names(&executor, 0), vec!["Wait(1)", "Wait(6)"]);
+        assert_eq!(names(&executor, 1), vec!["Wait(7)", "Wait(8)"]);
+        assert_eq!(executor.player_states[0].current_action_index, 1);
+    }
+
+    #[test]
+    fn test_swap_in_plans_of_a_new_search() {
+        let mut executor = Executor::new();
+        assert!(executor.swap_plans(vec![plan(&[1, 2])]));
+        executor.player_states[0].current_action_index = 1;
+        executor.player_states[0].action_started = true;
+
+        // The new search starts at the current action
+        executor.drop_executed_actions();
+        assert_eq!(names(&executor, 0), vec!["Wait(2)"]);
+        assert!(!executor.swap_plans(vec![plan(&[3])]));
+        assert!(executor.swap_plans(vec![plan(&[2, 3])]));
+        assert_eq!(names(&executor, 0), vec!["Wait(2)", "Wait(3)"]);
+        assert!(executor.player_states[0].action_started);
+    }
+}
@@ -9,6 +9,7 @@ pub enum ResourceClaim {
     Sword(Position),
     PressurePlate(Color),
     Health(Position),
+    Treasure,
     // Can add more claim types as needed
 }
 
@@ -47,7 +48,7 @@ pub struct PlanningState {
 impl PlanningState {
     pub fn new(world: &WorldState) -> Self {
         let num_players = world.players.len();
-        let plates_touched = world.plates_touched.clone();
+        let plates_touched = (*world.plates_touched).clone();
         tracing::info!("Initializing PlanningState for {} players", num_players);
 
         // Initialize player states, checking for boulders in inventory
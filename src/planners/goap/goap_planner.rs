@@ -0,0 +1,289 @@
+use std::fs;
+use std::path::PathBuf;
+use std::time::{Duration, Instant};
+
+use crate::infra::timed_path_to_action;
+use crate::planners::goap::{Executor, GoapSearch};
+use crate::state::WorldState;
+use crate::state::exploration::ranked_frontier;
+use crate::swoq_interface::DirectedAction;
+
+/// Alternative plans listed in each plan report
+const REPORT_ALTERNATIVES: usize = 5;
+
+/// Plan length an anytime search must have searched completely before its plan is
+/// executed, so that the executor does not start on a single action of one player
+const MIN_PLAN_DEPTH: usize = 3;
+
+/// Goal oriented action planner: searches for action sequences and executes them until
+/// the executor asks for a replan
+///
+/// With a tick budget the search is anytime and deepens one action at a time. A replan
+/// starts the search and the current plans keep running. Every tick executes the plans
+/// first and searches for what is left of the budget, checked after each expanded node.
+/// Once all plans of `MIN_PLAN_DEPTH` actions are searched, the best plan so far takes
+/// over at the next action boundary, and better plans found later are swapped in the
+/// same way.
+pub struct GoapPlanner {
+    executor: Executor,
+
+    /// Search still improving the executed plans, with a tick budget
+    search: Option<GoapSearch>,
+    /// Revision of the search's best plan the executor runs, `None` until the search
+    /// hands over its first plan
+    executed_revision: Option<u64>,
+
+    // Planner configuration
+    planner_max_depth: usize,
+    planner_timeout_ms: u64,
+    tick_budget_ms: Option<u64>,
+    report_dir: Option<PathBuf>,
+}
+
+impl GoapPlanner {
+    pub fn new(goap_max_depth: usize) -> Self {
+        Self {
+            executor: Executor::new(),
+            search: None,
+            executed_revision: None,
+            planner_max_depth: goap_max_depth,
+            planner_timeout_ms: 5000,
+            tick_budget_ms: None,
+            report_dir: None,
+        }
+    }
+
+    /// Limit the time spent on each replan
+    pub fn with_timeout(mut self, timeout_ms: u64) -> Self {
+        self.planner_timeout_ms = timeout_ms;
+        self
+    }
+
+    /// Spend at most `tick_budget_ms` on each tick, up to the node the search expands last,
+    /// and keep improving the plans over the following ticks instead of blocking until
+    /// the search is done
+    pub fn with_tick_budget(mut self, tick_budget_ms: Option<u64>) -> Self {
+        self.tick_budget_ms = tick_budget_ms;
+        self
+    }
+
+    /// Write a `PlanReport` of every search to this directory, as JSON and Graphviz DOT
+    pub fn with_report_dir(mut self, report_dir: Option<String>) -> Self {
+        self.report_dir = report_dir.map(PathBuf::from);
+        self
+    }
+
+    fn new_search(&self) -> GoapSearch {
+        let mut search = GoapSearch::new(self.planner_max_depth, self.planner_timeout_ms);
+        if self.tick_budget_ms.is_some() {
+            search = search.with_deepening();
+        }
+        if self.report_dir.is_some() {
+            search.with_report(REPORT_ALTERNATIVES)
+        } else {
+            search
+        }
+    }
+
+    /// Write the report of a search that ended
+    fn write_report(&self, search: &GoapSearch) {
+        let (Some(dir), Some(report)) = (&self.report_dir, search.report()) else {
+            return;
+        };
+        let name = format!("level-{}-tick-{}", report.level, report.tick);
+        let result = fs::create_dir_all(dir)
+            .and_then(|()| report.write_json(&dir.join(format!("{}.json", name))))
+            .and_then(|()| report.write_dot(&dir.join(format!("{}.dot", name))));
+        if let Err(e) = result {
+            tracing::warn!(
+                "GOAP: Failed to write plan report {} to {}: {}",
+                name,
+                dir.display(),
+                e
+            );
+        }
+    }
+
+    /// Continue the running search for `budget`. Its first plan takes over once it is
+    /// deep enough, better plans are swapped in when the executor allows.
+    fn improve_plans(&mut self, budget: Duration) {
+        let Some(search) = self.search.as_mut() else {
+            return;
+        };
+        let finished = search.search(budget);
+        let handed_over = match self.executed_revision {
+            None if finished || search.searched_depth() >= MIN_PLAN_DEPTH => {
+                // The search started from the world at the replan, the plans executed
+                // since then make way for its plans
+                let plans = search.best_plan();
+                self.executor.drop_executed_actions();
+                plans.is_empty() || self.executor.swap_plans(plans)
+            }
+            Some(revision) if revision != search.revision() => {
+                self.executor.swap_plans(search.best_plan())
+            }
+            _ => false,
+        };
+        if handed_over {
+            self.executed_revision = Some(search.revision());
+        }
+        // A better plan that changes a started action waits for the next action boundary
+        if finished
+            && self.executed_revision == Some(search.revision())
+            && let Some(search) = self.search.take()
+        {
+            self.write_report(&search);
+        }
+    }
+
+    fn plan_and_execute(&mut self, world: &mut WorldState) -> Option<Vec<DirectedAction>> {
+        tracing::info!("GOAP: Check replan");
+        let (should_replan, is_emergency) = self.executor.needs_replan(world);
+        // A search that has not handed over its first plan yet is not restarted
+        let awaiting_plan = self.search.is_some() && self.executed_revision.is_none();
+        if should_replan && (is_emergency || !awaiting_plan) {
+            if is_emergency {
+                tracing::info!("GOAP: EMERGENCY replanning (enemy/health change)");
+            } else {
+                tracing::info!("GOAP: Scheduled replanning");
+            }
+            if let Some(search) = self.search.take() {
+                self.write_report(&search);
+            }
+            let mut search = self.new_search();
+            search.start(world);
+            if self.tick_budget_ms.is_some() {
+                // The current plans keep running until the search hands over its plans
+                self.executed_revision = None;
+                self.search = Some(search);
+            } else {
+                search.search(search.timeout);
+                self.executor.set_plans(search.best_plan());
+                self.write_report(&search);
+                tracing::info!("GOAP: Done replanning");
+            }
+        } else {
+            tracing::info!("GOAP: No replanning needed");
+        }
+
+        // Execute current plans
+        self.executor.step(world)
+    }
+
+    /// Actions of this tick from the current plans, or exploring without them
+    fn next_actions(&mut self, world: &mut WorldState) -> (DirectedAction, Option<DirectedAction>) {
+        // The executor replans on the next tick, this tick falls back to exploring
+        let Some(actions) = self.plan_and_execute(world) else {
+            tracing::debug!("No executable actions for tick {}, exploring", world.tick);
+            let action2 = (world.players.len() > 1).then(|| fallback_action(world, 1));
+            return (fallback_action(world, 0), action2);
+        };
+
+        tracing::debug!(
+            "GOAP: Executing actions {} for tick {}",
+            actions
+                .iter()
+                .map(|a| format!("{:?}", a))
+                .collect::<Vec<String>>()
+                .join(", "),
+            world.tick
+        );
+
+        // Log actions for debugging
+        for (idx, action) in actions.iter().enumerate() {
+            let player = &world.players[idx];
+            tracing::debug!(
+                "Player {}: GOAP Action: {:?} at ({}, {})",
+                idx + 1,
+                action,
+                player.position.x,
+                player.position.y
+            );
+        }
+
+        let action1 = actions.first().copied().unwrap_or(DirectedAction::None);
+        let action2 = if world.players.len() > 1 {
+            actions.get(1).copied()
+        } else {
+            None
+        };
+        (action1, action2)
+    }
+}
+
+/// Action of a player without an executable plan: a step towards its best frontier
+/// tile, or waiting when there is nothing to explore
+fn fallback_action(world: &WorldState, player_index: usize) -> DirectedAction {
+    let position = world.players[player_index].position;
+    ranked_frontier(world, player_index)
+        .first()
+        .and_then(|&target| world.find_path(position, target))
+        .and_then(|path| timed_path_to_action(position, &path))
+        .unwrap_or(DirectedAction::None)
+}
+
+impl crate::planners::Planner for GoapPlanner {
+    fn on_level_start(&mut self, _world: &WorldState) {
+        self.executor = Executor::new();
+        self.executed_revision = None;
+        if let Some(search) = self.search.take() {
+            self.write_report(&search);
+        }
+    }
+
+    fn decide(&mut self, world: &mut WorldState) -> (DirectedAction, Option<DirectedAction>) {
+        let tick_start = Instant::now();
+        let actions = self.next_actions(world);
+        // The search gets what is left of the tick budget
+        if let Some(tick_budget_ms) = self.tick_budget_ms {
+            let budget = Duration::from_millis(tick_budget_ms).saturating_sub(tick_start.elapsed());
+            self.improve_plans(budget);
+        }
+        actions
+    }
+
+    fn goal_names(&self) -> Vec<String> {
+        self.executor.current_goal_names()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::planners::Planner;
+
+    const FIXTURE: &str = "\
+#############
+#1 r R s b E#
+#    B      #
+#############
+";
+
+    fn names(planner: &GoapPlanner) -> Vec<String> {
+        let sequence = &planner.executor.player_states[0].plan_sequence;
+        sequence.iter().map(|a| a.name()).collect()
+    }
+
+    #[test]
+    fn test_better_plan_on_a_later_tick_replaces_the_first_one() {
+        let mut world = WorldState::from_fixture(FIXTURE);
+        let mut planner = GoapPlanner::new(10).with_tick_budget(Some(0));
+        planner.on_level_start(&world);
+
+        while planner.executed_revision.is_none() {
+            planner.decide(&mut world);
+        }
+        let first_revision = planner.executed_revision;
+        let first_plan = names(&planner);
+        assert!(planner.search.is_some());
+        assert!(first_plan.len() >= MIN_PLAN_DEPTH);
+
+        while planner.search.is_some() {
+            planner.decide(&mut world);
+        }
+        assert!(planner.executed_revision > first_revision);
+        assert_ne!(names(&planner), first_plan);
+        assert_eq!(names(&planner)[0], first_plan[0]);
+        assert_eq!(names(&planner).last().unwrap(), "ReachExit");
+    }
+}
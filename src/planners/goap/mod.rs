@@ -1,10 +1,15 @@
 mod actions;
 mod executor;
-mod game;
 mod game_state;
-mod planner;
+mod goap_planner;
+mod report;
+mod search;
 mod state_evaluator;
 
 pub use executor::Executor;
-pub use game::Game;
-pub use planner::Planner;
+pub use goap_planner::GoapPlanner;
+pub use report::{
+    ActionStep, NodeStatus, PlanReport, ReportedAction, ReportedPlan, SearchStats, SearchTreeNode,
+};
+pub use search::GoapSearch;
+pub use state_evaluator::{ScoreTerm, StateScore};
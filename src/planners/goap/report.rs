@@ -0,0 +1,380 @@
+//! Account of a GOAP search: the chosen plan with the numbers behind every action, the
+//! runner-up plans and the search tree, written as JSON or Graphviz DOT
+
+use serde::Serialize;
+use std::collections::HashSet;
+use std::fmt::Write as _;
+use std::fs::{self, File};
+use std::io;
+use std::path::Path;
+
+use crate::planners::goap::state_evaluator::StateScore;
+
+/// What the search did with a node
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum NodeStatus {
+    /// Queued, not searched yet
+    Open,
+    /// Children were generated
+    Expanded,
+    /// Searched, but no player had an action to add
+    DeadEnd,
+    /// Dropped unexpanded at the maximum depth or when the search timed out
+    Pruned,
+}
+
+/// Action added to a plan, with the numbers the search used for it
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ActionStep {
+    pub player: usize,
+    pub action: String,
+    /// Tick at which the action starts
+    pub start: u32,
+    pub cost: f32,
+    pub duration: u32,
+    pub reward: f32,
+}
+
+/// Node of the search tree
+#[derive(Debug, Clone, Serialize)]
+pub struct SearchTreeNode {
+    pub id: usize,
+    pub parent: Option<usize>,
+    /// Action the node added to its parent's plan, `None` for the root
+    pub step: Option<ActionStep>,
+    /// Cost of all actions of the plan
+    pub cost: f32,
+    /// Rewards of all actions of the plan
+    pub action_rewards: f32,
+    /// Latest evaluation of the plan's end state
+    pub score: Option<StateScore>,
+    pub status: NodeStatus,
+}
+
+impl SearchTreeNode {
+    /// State and action rewards, `None` before evaluation or when disqualified
+    pub fn total_reward(&self) -> Option<f32> {
+        let score = self.score.as_ref().filter(|score| !score.disqualified)?;
+        Some(score.total() + self.action_rewards)
+    }
+}
+
+/// Counters of a search, kept whether it records a report or not
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
+pub struct SearchStats {
+    pub generated: usize,
+    pub expanded: usize,
+    /// End states scored by `evaluate_state`
+    pub evaluated: usize,
+    /// Evaluations that ruled the plan out
+    pub disqualified: usize,
+    /// Nodes dropped unexpanded at the maximum depth or when the search timed out
+    pub pruned: usize,
+}
+
+/// Action of a reported plan, with the score of the plan up to and including it
+#[derive(Debug, Clone, Serialize)]
+pub struct ReportedAction {
+    #[serde(flatten)]
+    pub step: ActionStep,
+    pub score: Option<StateScore>,
+}
+
+/// Plan found by the search
+#[derive(Debug, Clone, Serialize)]
+pub struct ReportedPlan {
+    /// Search tree node the plan ends in
+    pub node: usize,
+    pub total_reward: f32,
+    pub state_reward: f32,
+    pub action_rewards: f32,
+    pub cost: f32,
+    pub score: StateScore,
+    /// Actions of each player, in order
+    pub players: Vec<Vec<ReportedAction>>,
+}
+
+/// Why the GOAP planner chose its plan
+#[derive(Debug, Clone, Serialize)]
+pub struct PlanReport {
+    pub level: i32,
+    pub tick: i32,
+    pub max_depth: usize,
+    /// Longest plans searched so far
+    pub depth_limit: usize,
+    pub finished: bool,
+    pub search_ms: f64,
+    pub stats: SearchStats,
+    pub best: Option<ReportedPlan>,
+    /// Next best plans, best first
+    pub alternatives: Vec<ReportedPlan>,
+    pub tree: Vec<SearchTreeNode>,
+}
+
+impl PlanReport {
+    /// Write the report as JSON
+    pub fn write_json(&self, path: &Path) -> io::Result<()> {
+        let file = File::create(path)?;
+        serde_json::to_writer_pretty(file, self)?;
+        Ok(())
+    }
+
+    /// Write the search tree as Graphviz DOT
+    pub fn write_dot(&self, path: &Path) -> io::Result<()> {
+        fs::write(path, self.to_dot())
+    }
+
+    /// The search tree as a Graphviz digraph. The best plan is drawn in bold green, the
+    /// ends of the alternatives in blue and pruned or unsearched nodes dashed.
+    pub fn to_dot(&self) -> String {
+        let best_path: HashSet<usize> = self
+            .best
+            .as_ref()
+            .map(|plan| self.path(plan.node).collect())
+            .unwrap_or_default();
+        let alternatives: HashSet<usize> = self.alternatives.iter().map(|plan| plan.node).collect();
+
+        let mut dot = String::new();
+        let _ = writeln!(dot, "digraph goap_search {{");
+        let _ = writeln!(dot, "    node [shape=box, fontname=\"monospace\"];");
+        for node in &self.tree {
+            let mut label = match &node.step {
+                Some(step) => format!(
+                    "P{} {}\\nstart {} duration {} cost {:.1}",
+                    step.player + 1,
+                    escape(&step.action),
+                    step.start,
+                    step.duration,
+                    step.cost
+                ),
+                None => "root".to_string(),
+            };
+            match (&node.score, node.total_reward()) {
+                (Some(_), Some(total)) => {
+                    let _ = write!(label, "\\nreward {:.1} total cost {:.1}", total, node.cost);
+                }
+                (Some(_), None) => label.push_str("\\ndisqualified"),
+                (None, _) => {}
+            }
+
+            let style = if best_path.contains(&node.id) {
+                ", color=green, penwidth=2"
+            } else if alternatives.contains(&node.id) {
+                ", color=blue"
+            } else if matches!(node.status, NodeStatus::Pruned | NodeStatus::Open) {
+                ", style=dashed, color=gray"
+            } else {
+                ""
+            };
+            let _ = writeln!(dot, "    n{} [label=\"{}\"{}];", node.id, label, style);
+            if let Some(parent) = node.parent {
+                let _ = writeln!(dot, "    n{} -> n{};", parent, node.id);
+            }
+        }
+        dot.push_str("}\n");
+        dot
+    }
+
+    /// Node ids from `id` up to the root
+    fn path(&self, id: usize) -> impl Iterator<Item = usize> + '_ {
+        std::iter::successors(Some(id), |&id| self.tree[id].parent)
+    }
+}
+
+/// Escape a DOT label
+fn escape(text: &str) -> String {
+    text.replace('\\', "\\\\").replace('"', "\\\"")
+}
+
+/// Search tree and best plans recorded by a planner for its report
+pub(super) struct SearchRecord {
+    top_k: usize,
+    pub(super) tree: Vec<SearchTreeNode>,
+    /// Best evaluated nodes, best first, with their total reward and cost
+    ranking: Vec<(usize, f32, f32)>,
+}
+
+impl SearchRecord {
+    pub(super) fn new(top_k: usize) -> Self {
+        Self {
+            top_k,
+            tree: Vec::new(),
+            ranking: Vec::new(),
+        }
+    }
+
+    /// Add a node, with an id one past the last node's
+    pub(super) fn add(&mut self, node: SearchTreeNode) {
+        debug_assert_eq!(node.id, self.tree.len());
+        self.tree.push(node);
+    }
+
+    pub(super) fn set_status(&mut self, id: usize, status: NodeStatus) {
+        self.tree[id].status = status;
+    }
+
+    /// Store the latest evaluation of a node and rank its plan
+    pub(super) fn set_score(&mut self, id: usize, score: StateScore) {
+        let node = &mut self.tree[id];
+        node.score = Some(score);
+        let entry = node.total_reward().map(|total| (id, total, node.cost));
+
+        self.ranking.retain(|&(ranked, _, _)| ranked != id);
+        if let Some(entry) = entry {
+            self.ranking.push(entry);
+            self.ranking
+                .sort_by(|a, b| b.1.total_cmp(&a.1).then(a.2.total_cmp(&b.2)));
+            self.ranking.truncate(self.top_k + 1);
+        }
+    }
+
+    /// The plan ending in `best` and the `top_k` best other plans
+    pub(super) fn plans(
+        &self,
+        best: Option<usize>,
+        num_players: usize,
+    ) -> (Option<ReportedPlan>, Vec<ReportedPlan>) {
+        let alternatives = self
+            .ranking
+            .iter()
+            .map(|&(id, _, _)| id)
+            .filter(|&id| Some(id) != best)
+            .take(self.top_k)
+            .filter_map(|id| self.plan(id, num_players))
+            .collect();
+        (best.and_then(|id| self.plan(id, num_players)), alternatives)
+    }
+
+    fn plan(&self, id: usize, num_players: usize) -> Option<ReportedPlan> {
+        let end = &self.tree[id];
+        let score = end.score.clone()?;
+        let mut players = vec![Vec::new(); num_players];
+        let mut path: Vec<&SearchTreeNode> =
+            std::iter::successors(Some(end), |node| node.parent.map(|parent| &self.tree[parent]))
+                .collect();
+        path.reverse();
+        for node in path {
+            if let Some(step) = &node.step {
+                players[step.player].push(ReportedAction {
+                    step: step.clone(),
+                    score: node.score.clone(),
+                });
+            }
+        }
+        Some(ReportedPlan {
+            node: id,
+            total_reward: score.total() + end.action_rewards,
+            state_reward: score.total(),
+            action_rewards: end.action_rewards,
+            cost: end.cost,
+            score,
+            players,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::planners::goap::state_evaluator::ScoreTerm;
+
+    fn node(id: usize, parent: Option<usize>, player: usize, action: &str) -> SearchTreeNode {
+        SearchTreeNode {
+            id,
+            parent,
+            step: parent.map(|_| ActionStep {
+                player,
+                action: action.to_string(),
+                start: 0,
+                cost: 2.0,
+                duration: 2,
+                reward: 1.0,
+            }),
+            cost: 2.0 * id as f32,
+            action_rewards: 1.0,
+            score: None,
+            status: NodeStatus::Open,
+        }
+    }
+
+    fn score(reward: f32) -> StateScore {
+        StateScore {
+            terms: vec![ScoreTerm {
+                goal: "keys_found",
+                reward,
+            }],
+            disqualified: false,
+        }
+    }
+
+    /// Root with "Explore" and "GetKey(Red)", the latter followed by "OpenDoor(Red)"
+    fn record() -> SearchRecord {
+        let mut record = SearchRecord::new(1);
+        record.add(node(0, None, 0, ""));
+        record.add(node(1, Some(0), 0, "Explore"));
+        record.add(node(2, Some(0), 0, "GetKey(Red)"));
+        record.add(node(3, Some(2), 1, "OpenDoor(\"Red\")"));
+        record.set_score(1, score(5.0));
+        record.set_score(
+            2,
+            StateScore {
+                disqualified: true,
+                ..score(50.0)
+            },
+        );
+        record.set_score(3, score(25.0));
+        record
+    }
+
+    #[test]
+    fn test_plans_are_ranked_and_broken_down() {
+        let (best, alternatives) = record().plans(Some(3), 2);
+
+        let best = best.unwrap();
+        assert_eq!(best.node, 3);
+        assert_eq!((best.total_reward, best.state_reward, best.cost), (26.0, 25.0, 6.0));
+        assert!(
+            best.players[0]
+                .iter()
+                .map(|a| &a.step.action)
+                .eq(["GetKey(Red)"])
+        );
+        assert!(best.players[0][0].score.as_ref().unwrap().disqualified);
+        assert_eq!(best.players[1][0].step.action, "OpenDoor(\"Red\")");
+
+        assert_eq!(alternatives.len(), 1);
+        assert_eq!(alternatives[0].node, 1);
+    }
+
+    #[test]
+    fn test_dot_marks_the_best_plan() {
+        let mut record = record();
+        record.set_status(0, NodeStatus::Expanded);
+        let (best, alternatives) = record.plans(Some(3), 2);
+        let report = PlanReport {
+            level: 1,
+            tick: 0,
+            max_depth: 2,
+            depth_limit: 2,
+            finished: true,
+            search_ms: 1.0,
+            stats: SearchStats::default(),
+            best,
+            alternatives,
+            tree: record.tree,
+        };
+
+        let dot = report.to_dot();
+        assert!(dot.starts_with("digraph goap_search {\n"));
+        assert!(dot.contains("n2 -> n3;"));
+        assert!(dot.contains("OpenDoor(\\\"Red\\\")"));
+        assert!(dot.contains("\\ndisqualified\", color=green"));
+        assert!(dot.contains("n1 [label=\"P1 Explore\\nstart 0 duration 2 cost 2.0\\nreward 6.0"));
+        assert!(dot.contains("color=blue"));
+
+        let json = serde_json::to_value(&report).unwrap();
+        assert_eq!(json["best"]["players"][1][0]["action"], "OpenDoor(\"Red\")");
+        assert_eq!(json["tree"][0]["status"], "expanded");
+    }
+}
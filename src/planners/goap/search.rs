@@ -1,8 +1,12 @@
 use crate::planners::goap::actions::*;
 use crate::planners::goap::game_state::PlanningState;
+use crate::planners::goap::report::{
+    ActionStep, NodeStatus, PlanReport, SearchRecord, SearchStats, SearchTreeNode,
+};
 use crate::planners::goap::state_evaluator::evaluate_state;
 use crate::state::WorldState;
 use std::collections::BinaryHeap;
+use std::rc::Rc;
 use std::time::{Duration, Instant};
 
 pub type PlayerPlan = Vec<Box<dyn GOAPActionTrait>>;
@@ -15,6 +19,9 @@ const REWARD_COMPARISON_EPSILON: f32 = 0.001;
 /// Each node contains plans for ALL players in shared state
 #[derive(Clone)]
 struct PlanNode {
+    /// Position of the node in the search, the root is 0
+    id: usize,
+
     /// Action sequences for each player (indexed by player_id)
     player_sequences: Plan,
 
@@ -28,11 +35,11 @@ struct PlanNode {
     #[allow(dead_code)]
     player: Option<usize>,
 
-    /// World state before the most recently added action was applied
-    world_before_last_action: WorldState,
+    /// World state before the most recently added action was applied, shared with the parent
+    world_before_last_action: Rc<WorldState>,
 
     /// World state after the most recently added action (computed lazily)
-    world_after_last_action: Option<WorldState>,
+    world_after_last_action: Option<Rc<WorldState>>,
 
     /// State before the most recently added action was applied
     state_before_last_action: PlanningState,
@@ -40,11 +47,11 @@ struct PlanNode {
     /// State after the most recently added action (computed lazily)
     state_after_last_action: Option<PlanningState>,
 
-    /// Initial world state for comparison (state evaluation)
-    initial_world: WorldState,
+    /// Initial world state for comparison (state evaluation), shared by all nodes
+    initial_world: Rc<WorldState>,
 
-    /// Initial state for comparison (state evaluation)
-    initial_state: PlanningState,
+    /// Initial state for comparison (state evaluation), shared by all nodes
+    initial_state: Rc<PlanningState>,
 
     /// Cumulative cost for all players (just action costs, no rewards)
     cost: f32,
@@ -119,7 +126,7 @@ impl PlanNode {
         let mut simulated_world = self
             .world_after_last_action
             .clone()
-            .unwrap_or_else(|| self.world_before_last_action.clone());
+            .unwrap_or_else(|| Rc::clone(&self.world_before_last_action));
         let mut simulated_state = self
             .state_after_last_action
             .clone()
@@ -139,7 +146,11 @@ impl PlanNode {
                 player_sequence.last().unwrap().name()
             );
             let previous_action = player_sequence.last().unwrap();
-            previous_action.effect_end(&mut simulated_world, &mut simulated_state, player_id);
+            previous_action.effect_end(
+                Rc::make_mut(&mut simulated_world),
+                &mut simulated_state,
+                player_id,
+            );
         }
         self.world_after_last_action = Some(simulated_world);
         self.state_after_last_action = Some(simulated_state);
@@ -171,39 +182,85 @@ impl Ord for PlanNode {
     }
 }
 
-/// GOAP Planner using A* search to find action sequences
-pub struct Planner {
+/// GOAP search using A* to find action sequences
+///
+/// `plan` runs the search to the end, while `start` and `search` let the caller spread
+/// it over several ticks and pick up `best_plan` in between. `with_deepening` makes the
+/// search deepen one action at a time: all plans of `depth_limit` actions are searched
+/// before any longer plan, so a plan is available early and only improves.
+pub struct GoapSearch {
     pub max_depth: usize,
     pub timeout: Duration,
 
     // A* search state
     open_set: BinaryHeap<PlanNode>,
+    /// Children longer than `depth_limit`, searched once the limit is raised
+    deferred: Vec<PlanNode>,
+    /// `max_depth` unless the search deepens
+    depth_limit: usize,
+    deepening: bool,
+    num_players: usize,
+    /// Time spent searching so far, over all `search` calls
+    search_time: Duration,
     best_plan: Option<PlanNode>,
     best_state_reward: f32,
     best_cost: f32,
+    /// Incremented whenever a better plan is found
+    revision: u64,
+    stats: SearchStats,
+    /// Number of alternative plans to report, `None` records no report
+    report_top_k: Option<usize>,
+    record: Option<SearchRecord>,
+    level: i32,
+    tick: i32,
 }
 
-impl Planner {
+impl GoapSearch {
     pub fn new(max_depth: usize, timeout_ms: u64) -> Self {
         Self {
             max_depth,
             timeout: Duration::from_millis(timeout_ms),
             open_set: BinaryHeap::new(),
+            deferred: Vec::new(),
+            depth_limit: max_depth,
+            deepening: false,
+            num_players: 0,
+            search_time: Duration::ZERO,
             best_plan: None,
             best_state_reward: f32::MIN,
             best_cost: f32::MAX,
+            revision: 0,
+            stats: SearchStats::default(),
+            report_top_k: None,
+            record: None,
+            level: 0,
+            tick: 0,
         }
     }
 
+    /// Record the search tree, so `report` can explain the chosen plan next to the
+    /// `top_k` best alternatives
+    pub fn with_report(mut self, top_k: usize) -> Self {
+        self.report_top_k = Some(top_k);
+        self
+    }
+
+    /// Search all plans of one action before plans of two, and so on
+    pub fn with_deepening(mut self) -> Self {
+        self.deepening = true;
+        self.depth_limit = self.max_depth.min(1);
+        self
+    }
+
     fn evaluate(&mut self, node: &PlanNode) {
         if node.total_actions() > 0 {
             // For evaluation, we need to apply ALL actions (including those beyond last_processed_time)
             // Start from world_after_last_action which has actions up to last_processed_time applied
-            let mut eval_world = node
-                .world_after_last_action
-                .as_ref()
-                .unwrap_or(&node.world_before_last_action)
-                .clone();
+            let mut eval_world = WorldState::clone(
+                node.world_after_last_action
+                    .as_ref()
+                    .unwrap_or(&node.world_before_last_action),
+            );
             let mut eval_state = node
                 .state_after_last_action
                 .as_ref()
@@ -227,8 +284,16 @@ impl Planner {
                 }
             }
 
-            let state_reward =
+            let score =
                 evaluate_state(&eval_world, &eval_state, &node.initial_world, &node.initial_state);
+            let state_reward = score.total();
+            self.stats.evaluated += 1;
+            if score.disqualified {
+                self.stats.disqualified += 1;
+            }
+            if let Some(record) = self.record.as_mut() {
+                record.set_score(node.id, score);
+            }
 
             // Skip invalid plans (NEG_INFINITY) - don't store them as best_plan
             if state_reward.is_infinite() && state_reward.is_sign_negative() {
@@ -240,7 +305,7 @@ impl Planner {
             }
 
             let total_reward = state_reward + node.action_rewards;
-            tracing::info!(
+            tracing::debug!(
                 total_actions = node.total_actions(),
                 state_reward = state_reward,
                 action_rewards = node.action_rewards,
@@ -251,9 +316,8 @@ impl Planner {
 
             // Log player positions and destinations
             for (player_id, player) in eval_world.players.iter().enumerate() {
-                tracing::info!(
+                tracing::debug!(
                     player_id = player_id,
-
                     position = ?(player.position.x, player.position.y),
                     destination = ?player.current_destination.map(|d| (d.x, d.y)),
                     "Player state"
@@ -281,6 +345,7 @@ impl Planner {
                 self.best_state_reward = total_reward;
                 self.best_cost = node.cost;
                 self.best_plan = Some(node.clone());
+                self.revision += 1;
             }
         }
     }
@@ -368,6 +433,16 @@ impl Planner {
         let attack_count = attack_actions.len();
         candidates.extend(attack_actions);
 
+        let boss_actions =
+            AttackBossAction::generate(simulated_world, simulated_state, player_index);
+        let boss_count = boss_actions.len();
+        candidates.extend(boss_actions);
+
+        let treasure_actions =
+            PickupTreasureAction::generate(simulated_world, simulated_state, player_index);
+        let treasure_count = treasure_actions.len();
+        candidates.extend(treasure_actions);
+
         let hunt_actions =
             HuntEnemyAction::generate(simulated_world, simulated_state, player_index);
         let hunt_count = hunt_actions.len();
@@ -436,6 +511,8 @@ impl Planner {
                 pickup_sword = sword_count,
                 pickup_health = health_count,
                 attack = attack_count,
+                attack_boss = boss_count,
+                pickup_treasure = treasure_count,
                 hunt = hunt_count,
                 avoid = avoid_count,
                 plate_door = plate_door_count,
@@ -497,17 +574,14 @@ impl Planner {
             child_end_times[idle_player] = action_end_time;
 
             // Create child state and apply effect_start to claim resources
-            let mut child_world = current_node
-                .world_after_last_action
-                .as_ref()
-                .unwrap()
-                .clone();
+            // Starting an action only claims resources, so the child shares the world
+            let child_world = Rc::clone(current_node.world_after_last_action.as_ref().unwrap());
             let mut child_state = current_node
                 .state_after_last_action
                 .as_ref()
                 .unwrap()
                 .clone();
-            action.effect_start(&mut child_world, &mut child_state, idle_player);
+            action.effect_start(&child_world, &mut child_state, idle_player);
 
             tracing::trace!(
                 player_id = idle_player,
@@ -528,7 +602,29 @@ impl Planner {
                 "Queueing child node"
             );
 
+            let id = self.stats.generated;
+            self.stats.generated += 1;
+            if let Some(record) = self.record.as_mut() {
+                record.add(SearchTreeNode {
+                    id,
+                    parent: Some(current_node.id),
+                    step: Some(ActionStep {
+                        player: idle_player,
+                        action: action.name(),
+                        start: action_start_time,
+                        cost,
+                        duration,
+                        reward: action_reward,
+                    }),
+                    cost: child_cost,
+                    action_rewards: child_action_rewards,
+                    score: None,
+                    status: NodeStatus::Open,
+                });
+            }
+
             let child_node = PlanNode {
+                id,
                 player_sequences: child_sequences,
                 player_end_times: child_end_times,
                 last_processed_time: action_start_time,
@@ -536,21 +632,31 @@ impl Planner {
                 world_after_last_action: None,
                 state_before_last_action: child_state,
                 state_after_last_action: None,
-                initial_world: current_node.initial_world.clone(),
-                initial_state: current_node.initial_state.clone(),
+                initial_world: Rc::clone(&current_node.initial_world),
+                initial_state: Rc::clone(&current_node.initial_state),
                 cost: child_cost,
                 action_rewards: child_action_rewards,
                 player: None,
             };
-            self.open_set.push(child_node);
+            if child_node.total_actions() > self.depth_limit {
+                self.deferred.push(child_node);
+            } else {
+                self.open_set.push(child_node);
+            }
         }
     }
 
     #[tracing::instrument(skip(self, world))]
     pub fn plan(mut self, world: &WorldState) -> Plan {
+        self.start(world);
+        self.search(self.timeout);
+        self.best_plan()
+    }
+
+    /// Start a new search from `world`, dropping any previous search
+    pub fn start(&mut self, world: &WorldState) {
         let num_players = world.players.len();
         let current_tick = world.tick as u32;
-        let start_time = Instant::now();
         let game_state = PlanningState::new(world);
 
         tracing::debug!(
@@ -560,80 +666,258 @@ impl Planner {
         );
 
         let root_node = PlanNode {
+            id: 0,
             player_sequences: vec![Vec::new(); num_players],
             player_end_times: vec![current_tick; num_players],
             last_processed_time: 0,
-            world_before_last_action: world.clone(),
+            world_before_last_action: Rc::new(world.clone()),
             world_after_last_action: None,
             state_before_last_action: game_state.clone(),
             state_after_last_action: None,
-            initial_world: world.clone(),
-            initial_state: game_state.clone(),
+            initial_world: Rc::new(world.clone()),
+            initial_state: Rc::new(game_state.clone()),
             player: None,
             cost: 0.0,
             action_rewards: 0.0,
         };
 
+        *self = Self {
+            num_players,
+            report_top_k: self.report_top_k,
+            record: self.report_top_k.map(SearchRecord::new),
+            deepening: self.deepening,
+            depth_limit: if self.deepening {
+                self.max_depth.min(1)
+            } else {
+                self.max_depth
+            },
+            level: world.level,
+            tick: world.tick,
+            ..Self::new(self.max_depth, self.timeout.as_millis() as u64)
+        };
+        self.stats.generated = 1;
+        if let Some(record) = self.record.as_mut() {
+            record.add(SearchTreeNode {
+                id: 0,
+                parent: None,
+                step: None,
+                cost: 0.0,
+                action_rewards: 0.0,
+                score: None,
+                status: NodeStatus::Open,
+            });
+        }
         self.open_set.push(root_node);
+    }
 
-        while let Some(mut current_node) = self.open_set.pop() {
-            tracing::debug!(
-                total_actions = current_node.total_actions(),
-                cost = current_node.cost,
-                score = -current_node.cost,
-                player_end_times = ?current_node.player_end_times,
-                all_plans = ?current_node.all_plans(),
-                "Exploring node"
-            );
-
-            if start_time.elapsed() > self.timeout {
-                tracing::warn!(total_actions = current_node.total_actions(), "Planning timeout");
+    /// Continue the search for at most `budget`, returns true once it is finished.
+    ///
+    /// Every call expands at least one node, so the search makes progress even when the
+    /// budget is spent before it starts. It may have no plan yet when the budget runs out.
+    pub fn search(&mut self, budget: Duration) -> bool {
+        let start_time = Instant::now();
+        while !self.is_finished() {
+            if self.search_time + start_time.elapsed() > self.timeout {
+                tracing::warn!(depth_limit = self.depth_limit, "Planning timeout");
+                let dropped = self.open_set.drain().chain(self.deferred.drain(..));
+                for node in dropped {
+                    self.stats.pruned += 1;
+                    if let Some(record) = self.record.as_mut() {
+                        record.set_status(node.id, NodeStatus::Pruned);
+                    }
+                }
                 break;
             }
-
-            let idle_players = current_node.get_idle_players(num_players);
-
-            if idle_players.is_empty() {
-                tracing::debug!("All players have completed their plans");
+            if self.open_set.is_empty() {
+                self.depth_limit += 1;
+                tracing::debug!(
+                    depth_limit = self.depth_limit,
+                    nodes = self.deferred.len(),
+                    "Deepening search"
+                );
+                self.open_set.extend(self.deferred.drain(..));
                 continue;
             }
+            let Some(current_node) = self.open_set.pop() else {
+                break;
+            };
+            self.expand(current_node);
+            if start_time.elapsed() >= budget {
+                break;
+            }
+        }
+        self.search_time += start_time.elapsed();
 
-            let action_start_time = current_node.player_end_times[idle_players[0]];
-            let current_node_time = *current_node.player_end_times.iter().min().unwrap();
+        if self.is_finished() {
+            tracing::info!(
+                best_state_reward = self.best_state_reward,
+                best_cost = self.best_cost,
+                plan_found = self.best_plan.is_some(),
+                "A* search completed"
+            );
+        }
+        self.is_finished()
+    }
+
+    /// True when every node was searched or the search ran out of time
+    pub fn is_finished(&self) -> bool {
+        self.open_set.is_empty() && self.deferred.is_empty()
+    }
+
+    /// Best plan found so far, empty before any plan is found
+    pub fn best_plan(&self) -> Plan {
+        if let Some(plan) = self.best_plan.as_ref() {
+            plan.player_sequences.clone()
+        } else {
+            Vec::new()
+        }
+    }
+
+    /// Length up to which every plan has been searched, so no plan of at most that many
+    /// actions beats `best_plan`
+    pub fn searched_depth(&self) -> usize {
+        if self.is_finished() {
+            self.max_depth
+        } else if self.deepening {
+            self.depth_limit.saturating_sub(1)
+        } else {
+            0
+        }
+    }
 
+    /// Changes whenever `best_plan` improves
+    pub fn revision(&self) -> u64 {
+        self.revision
+    }
+
+    pub fn stats(&self) -> SearchStats {
+        self.stats
+    }
+
+    /// Report of the search so far, when it was created `with_report`
+    pub fn report(&self) -> Option<PlanReport> {
+        let record = self.record.as_ref()?;
+        let best_node = self.best_plan.as_ref().map(|node| node.id);
+        let (best, alternatives) = record.plans(best_node, self.num_players);
+        Some(PlanReport {
+            level: self.level,
+            tick: self.tick,
+            max_depth: self.max_depth,
+            depth_limit: self.depth_limit,
+            finished: self.is_finished(),
+            search_ms: self.search_time.as_secs_f64() * 1000.0,
+            stats: self.stats,
+            best,
+            alternatives,
+            tree: record.tree.clone(),
+        })
+    }
+
+    fn set_status(&mut self, node: &PlanNode, status: NodeStatus) {
+        match status {
+            NodeStatus::Expanded => self.stats.expanded += 1,
+            NodeStatus::Pruned => self.stats.pruned += 1,
+            NodeStatus::Open | NodeStatus::DeadEnd => {}
+        }
+        if let Some(record) = self.record.as_mut() {
+            record.set_status(node.id, status);
+        }
+    }
+
+    fn expand(&mut self, mut current_node: PlanNode) {
+        let num_players = self.num_players;
+        let world = Rc::clone(&current_node.initial_world);
+        tracing::debug!(
+            total_actions = current_node.total_actions(),
+            cost = current_node.cost,
+            score = -current_node.cost,
+            player_end_times = ?current_node.player_end_times,
+            all_plans = ?current_node.all_plans(),
+            "Exploring node"
+        );
+
+        let idle_players = current_node.get_idle_players(num_players);
+
+        if idle_players.is_empty() {
+            tracing::debug!("All players have completed their plans");
+            self.set_status(&current_node, NodeStatus::DeadEnd);
+            return;
+        }
+
+        let action_start_time = current_node.player_end_times[idle_players[0]];
+        let current_node_time = *current_node.player_end_times.iter().min().unwrap();
+
+        tracing::debug!(
+            idle_players = ?idle_players,
+            action_start_time = action_start_time,
+            current_node_time = current_node_time,
+            all_player_plans = ?current_node.all_plans(),
+            "Processing players at time point"
+        );
+
+        // Update end state for ALL idle players at this time point to get complete state
+        for &idle_player in &idle_players {
+            current_node.update_end_state(idle_player);
+        }
+
+        // Always evaluate the state after updating all idle players
+        // This ensures terminal states (like all players at exit) are evaluated
+        self.evaluate(&current_node);
+
+        // If we've reached max depth, don't expand further
+        if current_node.total_actions() >= self.max_depth {
             tracing::debug!(
-                idle_players = ?idle_players,
-                action_start_time = action_start_time,
-                current_node_time = current_node_time,
-                all_player_plans = ?current_node.all_plans(),
-                "Processing players at time point"
+                total_actions = current_node.total_actions(),
+                "Reached max depth, not expanding further"
             );
+            self.set_status(&current_node, NodeStatus::Pruned);
+            return;
+        }
 
-            // Update end state for ALL idle players at this time point to get complete state
-            for &idle_player in &idle_players {
-                current_node.update_end_state(idle_player);
+        // Now try to expand ONE player (trying all players in order until we find candidates)
+        // Start with idle players, then try other players if needed
+        let mut candidates = Vec::new();
+        let mut selected_player = None;
+
+        // First, try idle players (already have their end state updated)
+        for &player_id in &idle_players {
+            if current_node.is_player_terminal(player_id) {
+                tracing::debug!(
+                    player_id = player_id,
+                    "Player has reached terminal action, skipping"
+                );
+                continue;
             }
 
-            // Always evaluate the state after updating all idle players
-            // This ensures terminal states (like all players at exit) are evaluated
-            self.evaluate(&current_node);
+            tracing::debug!(
+                player_id = player_id,
+                end_time = current_node.player_end_times[player_id],
+                position = ?(world.players[player_id].position.x, world.players[player_id].position.y),
+                player_plan = ?current_node.plan_for_player(player_id),
+                "Trying to generate candidates for idle player"
+            );
 
-            // If we've reached max depth, don't expand further
-            if current_node.total_actions() >= self.max_depth {
+            candidates = self.generate_candidates(&current_node, player_id);
+
+            if !candidates.is_empty() {
+                selected_player = Some(player_id);
                 tracing::debug!(
-                    total_actions = current_node.total_actions(),
-                    "Reached max depth, not expanding further"
+                    player_id = player_id,
+                    candidate_count = candidates.len(),
+                    "Found candidates for idle player"
                 );
-                continue;
+                break;
             }
+        }
 
-            // Now try to expand ONE player (trying all players in order until we find candidates)
-            // Start with idle players, then try other players if needed
-            let mut candidates = Vec::new();
-            let mut selected_player = None;
+        // If no idle player has candidates, try all other players
+        if selected_player.is_none() {
+            for player_id in 0..num_players {
+                // Skip if already tried (was in idle_players)
+                if idle_players.contains(&player_id) {
+                    continue;
+                }
 
-            // First, try idle players (already have their end state updated)
-            for &player_id in &idle_players {
                 if current_node.is_player_terminal(player_id) {
                     tracing::debug!(
                         player_id = player_id,
@@ -647,9 +931,15 @@ impl Planner {
                     end_time = current_node.player_end_times[player_id],
                     position = ?(world.players[player_id].position.x, world.players[player_id].position.y),
                     player_plan = ?current_node.plan_for_player(player_id),
-                    "Trying to generate candidates for idle player"
+                    "Trying to generate candidates for non-idle player"
                 );
 
+                // Update this player's end state before generating candidates
+                current_node.update_end_state(player_id);
+
+                // Re-evaluate after updating this player's state
+                self.evaluate(&current_node);
+
                 candidates = self.generate_candidates(&current_node, player_id);
 
                 if !candidates.is_empty() {
@@ -657,75 +947,169 @@ impl Planner {
                     tracing::debug!(
                         player_id = player_id,
                         candidate_count = candidates.len(),
-                        "Found candidates for idle player"
+                        "Found candidates for non-idle player"
                     );
                     break;
                 }
             }
+        }
 
-            // If no idle player has candidates, try all other players
-            if selected_player.is_none() {
-                for player_id in 0..num_players {
-                    // Skip if already tried (was in idle_players)
-                    if idle_players.contains(&player_id) {
-                        continue;
-                    }
+        if let Some(player_id) = selected_player {
+            self.set_status(&current_node, NodeStatus::Expanded);
+            self.generate_child_nodes(candidates, &current_node, player_id);
+        } else {
+            tracing::debug!("No candidates generated for any player, state already evaluated");
+            self.set_status(&current_node, NodeStatus::DeadEnd);
+        }
+    }
+}
 
-                    if current_node.is_player_terminal(player_id) {
-                        tracing::debug!(
-                            player_id = player_id,
-                            "Player has reached terminal action, skipping"
-                        );
-                        continue;
-                    }
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
 
-                    tracing::debug!(
-                        player_id = player_id,
-                        end_time = current_node.player_end_times[player_id],
-                        position = ?(world.players[player_id].position.x, world.players[player_id].position.y),
-                        player_plan = ?current_node.plan_for_player(player_id),
-                        "Trying to generate candidates for non-idle player"
-                    );
+    use super::*;
+    use crate::state::{BOSS_FIGHT_MIN_HEALTH, BOSS_LEVEL, WorldStateBuilder};
 
-                    // Update this player's end state before generating candidates
-                    current_node.update_end_state(player_id);
+    const FIXTURE: &str = "\
+#########
+#1 r R E#
+#########
+";
 
-                    // Re-evaluate after updating this player's state
-                    self.evaluate(&current_node);
+    fn names(plan: &Plan) -> Vec<Vec<String>> {
+        plan.iter()
+            .map(|sequence| sequence.iter().map(|a| a.name()).collect())
+            .collect()
+    }
 
-                    candidates = self.generate_candidates(&current_node, player_id);
+    #[test]
+    fn test_search_stays_within_the_budget() {
+        let world = WorldState::from_fixture(FIXTURE);
+        let mut search = GoapSearch::new(10, 5000).with_deepening();
+        search.start(&world);
 
-                    if !candidates.is_empty() {
-                        selected_player = Some(player_id);
-                        tracing::debug!(
-                            player_id = player_id,
-                            candidate_count = candidates.len(),
-                            "Found candidates for non-idle player"
-                        );
-                        break;
-                    }
-                }
-            }
+        // A spent budget still expands one node, the root has no plan to offer
+        assert!(!search.search(Duration::ZERO));
+        assert_eq!(search.stats().expanded, 1);
+        assert!(search.best_plan().is_empty());
+        assert_eq!(search.searched_depth(), 0);
 
-            if let Some(player_id) = selected_player {
-                self.generate_child_nodes(candidates, &current_node, player_id);
-            } else {
-                tracing::debug!("No candidates generated for any player, state already evaluated");
-            }
+        while search.searched_depth() < 1 {
+            search.search(Duration::ZERO);
         }
+        assert_eq!(names(&search.best_plan())[0][0], "GetKey(Red)");
+    }
 
-        // Return best plan found
-        tracing::info!(
-            best_state_reward = self.best_state_reward,
-            best_cost = self.best_cost,
-            plan_found = self.best_plan.is_some(),
-            "A* search completed"
-        );
+    #[test]
+    fn test_search_over_ticks_finds_the_blocking_plan() {
+        let world = WorldState::from_fixture(FIXTURE);
+        let blocking = GoapSearch::new(10, 5000).plan(&world);
 
-        if let Some(plan) = self.best_plan.as_ref() {
-            plan.player_sequences.clone()
-        } else {
-            Vec::new()
+        let mut search = GoapSearch::new(10, 5000).with_deepening();
+        search.start(&world);
+        let mut ticks = 1;
+        while !search.search(Duration::ZERO) {
+            ticks += 1;
+        }
+
+        assert!(ticks > 1);
+        assert_eq!(names(&search.best_plan()), names(&blocking));
+        assert_eq!(names(&blocking), vec![vec!["GetKey(Red)", "OpenDoor(Red)", "ReachExit"]]);
+    }
+
+    #[test]
+    fn test_search_without_deepening_defers_nothing() {
+        let world = WorldState::from_fixture(FIXTURE);
+        let mut search = GoapSearch::new(10, 5000);
+        search.start(&world);
+        while !search.search(Duration::ZERO) {
+            assert!(search.deferred.is_empty());
+            assert_eq!(search.searched_depth(), 0);
         }
+        assert_eq!(search.searched_depth(), 10);
+    }
+
+    #[test]
+    fn test_child_nodes_share_the_world_of_their_parent() {
+        let world = WorldState::from_fixture("#########\n#E 1 r R#\n#########");
+        let mut search = GoapSearch::new(10, 5000);
+        search.start(&world);
+        let root = search.open_set.pop().unwrap();
+        search.expand(root);
+
+        // The children only differ in their planning state, they share one world
+        let children: Vec<&PlanNode> = search.open_set.iter().chain(&search.deferred).collect();
+        assert!(children.len() > 1);
+        let shared = &children[0].world_before_last_action;
+        for child in children {
+            let child_world = &child.world_before_last_action;
+            assert!(Rc::ptr_eq(child_world, shared));
+            assert!(Arc::ptr_eq(&child_world.enemy_tracker, &world.enemy_tracker));
+            assert!(Arc::ptr_eq(
+                &child_world.potential_enemy_locations,
+                &world.potential_enemy_locations
+            ));
+            assert!(Arc::ptr_eq(&child_world.plates_touched, &world.plates_touched));
+            assert!(Arc::ptr_eq(&child_world.threats, &world.threats));
+        }
+    }
+
+    #[test]
+    fn test_boss_fight_plan_carries_the_treasure_out() {
+        let world = WorldStateBuilder::new("#########\n#1  X  E#\n#########")
+            .level(BOSS_LEVEL)
+            .sword(0)
+            .health(0, 10)
+            .build();
+        assert!(world.must_deliver_treasure());
+
+        let plan = GoapSearch::new(10, 5000).plan(&world);
+        assert_eq!(names(&plan), vec![vec!["AttackBoss", "PickupTreasure", "ReachExit"]]);
+    }
+
+    #[test]
+    fn test_no_boss_fight_without_sword_and_health() {
+        let fixture = "#########\n#1  X  E#\n#########";
+        for builder in [
+            WorldStateBuilder::new(fixture).health(0, 10),
+            WorldStateBuilder::new(fixture)
+                .sword(0)
+                .health(0, BOSS_FIGHT_MIN_HEALTH - 1),
+        ] {
+            let world = builder.level(BOSS_LEVEL).build();
+            let plan = GoapSearch::new(10, 5000).plan(&world);
+            assert!(
+                plan.iter()
+                    .flatten()
+                    .all(|action| action.name() != "AttackBoss")
+            );
+        }
+    }
+
+    #[test]
+    fn test_report_explains_the_best_plan() {
+        let world = WorldState::from_fixture(FIXTURE);
+        let mut search = GoapSearch::new(10, 5000).with_report(2);
+        search.start(&world);
+        assert!(search.search(Duration::from_secs(5)));
+        let report = search.report().unwrap();
+
+        let best = report.best.unwrap();
+        let actions: Vec<&str> = best.players[0]
+            .iter()
+            .map(|action| action.step.action.as_str())
+            .collect();
+        assert_eq!(vec![actions], names(&search.best_plan()));
+        assert_eq!(best.total_reward, best.state_reward + best.action_rewards);
+        assert!(best.score.terms.iter().any(|term| term.goal == "exit"));
+        assert!(best.players[0].iter().all(|action| action.score.is_some()));
+        assert!(report.alternatives.len() <= 2);
+
+        let stats = report.stats;
+        assert_eq!(stats.generated, report.tree.len());
+        assert!(stats.expanded > 0 && stats.evaluated > 0);
+        assert!(!report.tree.iter().any(|n| n.status == NodeStatus::Open));
+        assert!(GoapSearch::new(10, 5000).report().is_none());
     }
 }
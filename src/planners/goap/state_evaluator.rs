@@ -1,7 +1,40 @@
+use serde::Serialize;
+
 use crate::planners::goap::game_state::PlanningState;
 use crate::swoq_interface::Inventory;
 use crate::state::WorldState;
 
+/// Reward of one goal in a state score
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ScoreTerm {
+    pub goal: &'static str,
+    pub reward: f32,
+}
+
+/// Score of a world state, split into the goals that made progress
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct StateScore {
+    pub terms: Vec<ScoreTerm>,
+    /// A player ends holding an item, which rules the plan out
+    pub disqualified: bool,
+}
+
+impl StateScore {
+    fn add(&mut self, goal: &'static str, reward: f32) {
+        self.terms.push(ScoreTerm { goal, reward });
+    }
+
+    /// Sum of the terms, `NEG_INFINITY` when disqualified
+    pub fn total(&self) -> f32 {
+        if self.disqualified {
+            return f32::NEG_INFINITY;
+        }
+        self.terms
+            .iter()
+            .fold(0.0, |total, term| total + term.reward)
+    }
+}
+
 /// Evaluate the reward/score of a world state
 /// This compares the current state to determine progress toward goals
 pub fn evaluate_state(
@@ -9,8 +42,8 @@ pub fn evaluate_state(
     state: &PlanningState,
     initial_world: &WorldState,
     initial_state: &PlanningState,
-) -> f32 {
-    let mut score = 0.0;
+) -> StateScore {
+    let mut score = StateScore::default();
 
     // Goal: Reach the exit with all players (ultimate goal)
     if world.exit_position.is_some() {
@@ -30,7 +63,7 @@ pub fn evaluate_state(
                 && p.inventory == Inventory::None
         });
         if all_at_exit {
-            score += 1000.0; // Massive reward for winning
+            score.add("exit", 1000.0); // Massive reward for winning
         }
     }
 
@@ -38,7 +71,18 @@ pub fn evaluate_state(
     let enemies_killed = initial_world.enemies.get_positions().len() as i32
         - world.enemies.get_positions().len() as i32;
     if enemies_killed > 0 {
-        score += enemies_killed as f32 * 30.0; // High reward per enemy killed
+        score.add("enemies_killed", enemies_killed as f32 * 30.0); // High reward per enemy killed
+    }
+
+    // Goal: Kill the boss and get hold of the treasure it drops (boss levels)
+    if initial_world.boss_position.is_some() && world.boss_position.is_none() {
+        score.add("boss_killed", 40.0);
+    }
+    let carrying_treasure = |w: &WorldState| {
+        w.treasure_delivered || w.players.iter().any(|p| p.inventory == Inventory::Treasure)
+    };
+    if carrying_treasure(world) && !carrying_treasure(initial_world) {
+        score.add("treasure", 40.0);
     }
 
     // Goal: Open doors (permanent progress)
@@ -64,7 +108,7 @@ pub fn evaluate_state(
         }
     }
     if doors_opened > 0 {
-        score += doors_opened as f32 * 25.0; // High reward for opening doors
+        score.add("doors_opened", doors_opened as f32 * 25.0); // High reward for opening doors
     }
 
     // Goal: Place boulders on pressure plates (level 6+ puzzle solving)
@@ -82,7 +126,7 @@ pub fn evaluate_state(
 
         let new_plates_covered = plates_with_boulders as i32 - initial_plates_with_boulders as i32;
         if new_plates_covered > 0 {
-            score += new_plates_covered as f32 * 50.0; // Very high reward for solving pressure plate puzzles
+            score.add("plates_covered", new_plates_covered as f32 * 50.0); // Very high reward for solving pressure plate puzzles
         }
 
         // Goal: Move unexplored boulders (discovering what's behind them)
@@ -91,7 +135,7 @@ pub fn evaluate_state(
             initial_world.boulders.get_original_boulders().len();
         let boulders_explored = initial_unexplored_boulders as i32 - unexplored_boulders as i32;
         if boulders_explored > 0 {
-            score += boulders_explored as f32 * 10.0; // Reward for moving unexplored boulders
+            score.add("boulders_moved", boulders_explored as f32 * 10.0); // Reward for moving unexplored boulders
         }
     }
 
@@ -102,13 +146,13 @@ pub fn evaluate_state(
     // Goal: Discover new objects (keys, swords, etc.)
     let new_keys = count_total_keys(world) as i32 - count_total_keys(initial_world) as i32;
     if new_keys > 0 {
-        score += new_keys as f32 * 5.0; // Reward for discovering keys
+        score.add("keys_found", new_keys as f32 * 5.0); // Reward for discovering keys
     }
 
     let new_swords = world.swords.get_positions().len() as i32
         - initial_world.swords.get_positions().len() as i32;
     if new_swords > 0 {
-        score += new_swords as f32 * 5.0; // Reward for discovering swords
+        score.add("swords_found", new_swords as f32 * 5.0); // Reward for discovering swords
     }
 
     // Goal: Pick up swords (equipping players for combat)
@@ -119,7 +163,7 @@ pub fn evaluate_state(
             .filter(|p| p.has_sword)
             .count() as i32;
     if swords_picked_up > 0 {
-        score += swords_picked_up as f32 * 15.0; // Reward for picking up swords
+        score.add("swords_picked_up", swords_picked_up as f32 * 15.0); // Reward for picking up swords
     }
 
     // Goal: Increase health (healing players)
@@ -131,7 +175,7 @@ pub fn evaluate_state(
         .filter(|&delta| delta > 0)
         .sum();
     if total_health_gained > 0 {
-        score += total_health_gained as f32 * 3.0; // Reward for health gained (3 per HP)
+        score.add("health_gained", total_health_gained as f32 * 3.0); // Reward for health gained (3 per HP)
     }
 
     // Small reward for idle activity (touching plates when nothing else to do)
@@ -139,18 +183,19 @@ pub fn evaluate_state(
     let new_plate_colors_touched =
         state.plates_touched.len() as i32 - initial_state.plates_touched.len() as i32;
     if new_plate_colors_touched > 0 {
-        score += new_plate_colors_touched as f32 * 2.0; // Small reward to encourage idle exploration
+        score.add("plates_touched", new_plate_colors_touched as f32 * 2.0); // Small reward to encourage idle exploration
     }
 
     // Disqualify plans where players end with non-empty inventory
+    // (the treasure is fine, it is carried straight to the exit)
     let holding_items = world
         .players
         .iter()
-        .filter(|p| p.inventory != Inventory::None)
+        .filter(|p| !matches!(p.inventory, Inventory::None | Inventory::Treasure))
         .count();
     if holding_items > 0 {
         tracing::debug!("Disqualifying plan: {} players holding items in inventory", holding_items);
-        return f32::NEG_INFINITY; // Never select plans with occupied inventory
+        score.disqualified = true; // Never select plans with occupied inventory
     }
 
     score
@@ -1,7 +1,7 @@
 use tracing::debug;
 
 use crate::infra::Position;
-use crate::infra::{path_to_action, use_direction};
+use crate::infra::{timed_path_to_action, use_direction};
 use crate::planners::heuristic::goals::goal::ExecuteGoal;
 use super::super::pathfinding::find_path_for_player;
 use crate::planners::heuristic::planner_state::PlannerState;
@@ -25,6 +25,6 @@ impl ExecuteGoal for DropBoulderOnPlateGoal {
         state.world.players[player_index].current_destination = Some(plate_pos);
         let path = find_path_for_player(&state.world, player_index, player_pos, plate_pos)?;
         state.world.players[player_index].current_path = Some(path.clone());
-        path_to_action(player_pos, &path)
+        timed_path_to_action(player_pos, &path)
     }
 }
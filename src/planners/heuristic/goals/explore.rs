@@ -1,10 +1,11 @@
 use tracing::debug;
 
 use super::super::pathfinding::find_path_for_player;
-use crate::infra::path_to_action;
+use crate::infra::timed_path_to_action;
 use crate::planners::heuristic::goals::goal::ExecuteGoal;
 use crate::planners::heuristic::goals::{try_keep_destination, validate_destination};
 use crate::planners::heuristic::planner_state::PlannerState;
+use crate::state::exploration::{frontier_owner, ranked_frontier};
 use crate::swoq_interface::DirectedAction;
 
 pub struct ExploreGoal;
@@ -13,19 +14,26 @@ impl ExecuteGoal for ExploreGoal {
     fn execute(&self, state: &mut PlannerState, player_index: usize) -> Option<DirectedAction> {
         let player_pos = state.world.players[player_index].position;
 
-        // Step 1: Validate destination
+        // Step 1: Validate destination, and drop it once the other player explores there
         validate_destination(state, player_index);
+        if let Some(dest) = state.world.players[player_index].current_destination
+            && frontier_owner(&state.world, dest).is_some_and(|owner| owner != player_index)
+        {
+            debug!("Frontier at {:?} is allocated to the other player, clearing destination", dest);
+            state.world.players[player_index].current_destination = None;
+            state.world.players[player_index].current_path = None;
+        }
 
         // Step 2: Try to reuse existing destination
         if try_keep_destination(state, player_index) {
-            return path_to_action(
+            return timed_path_to_action(
                 player_pos,
                 state.world.players[player_index].current_path.as_ref()?,
             );
         }
 
         // Step 3: Search for new frontier destination
-        let sorted_frontier = &state.world.players[player_index].sorted_unexplored();
+        let sorted_frontier = &ranked_frontier(&state.world, player_index);
         debug!("Searching for new frontier destination from {} tiles", sorted_frontier.len());
         let mut attempts = 0;
         for (i, target) in sorted_frontier.iter().enumerate() {
@@ -49,7 +57,7 @@ impl ExecuteGoal for ExploreGoal {
                 );
                 state.world.players[player_index].current_destination = Some(*target);
                 state.world.players[player_index].current_path = Some(path.clone());
-                return path_to_action(player_pos, &path);
+                return timed_path_to_action(player_pos, &path);
             }
         }
         debug!(
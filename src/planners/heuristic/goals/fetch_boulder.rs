@@ -2,7 +2,7 @@ use tracing::debug;
 
 use super::super::pathfinding::find_path_for_player;
 use crate::infra::Position;
-use crate::infra::{path_to_action, use_direction};
+use crate::infra::{timed_path_to_action, use_direction};
 use crate::planners::heuristic::goals::goal::ExecuteGoal;
 use crate::planners::heuristic::planner_state::PlannerState;
 use crate::swoq_interface::DirectedAction;
@@ -30,7 +30,7 @@ impl ExecuteGoal for FetchBoulderGoal {
                 debug!("Moving to adjacent position {:?} to reach boulder", adjacent);
                 state.world.players[player_index].current_destination = Some(adjacent);
                 state.world.players[player_index].current_path = Some(path.clone());
-                return path_to_action(player_pos, &path);
+                return timed_path_to_action(player_pos, &path);
             }
         }
         debug!("No walkable position adjacent to boulder at {:?}", boulder_pos);
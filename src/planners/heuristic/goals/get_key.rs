@@ -1,6 +1,6 @@
 use super::super::pathfinding::find_path_for_player;
 use crate::infra::Color;
-use crate::infra::path_to_action;
+use crate::infra::timed_path_to_action;
 use crate::planners::heuristic::goals::goal::ExecuteGoal;
 use crate::planners::heuristic::goals::validate_destination;
 use crate::planners::heuristic::planner_state::PlannerState;
@@ -21,6 +21,6 @@ impl ExecuteGoal for GetKeyGoal {
         state.world.players[player_index].current_destination = Some(key_pos);
         let path = find_path_for_player(&state.world, player_index, player_pos, key_pos)?;
         state.world.players[player_index].current_path = Some(path.clone());
-        path_to_action(player_pos, &path)
+        timed_path_to_action(player_pos, &path)
     }
 }
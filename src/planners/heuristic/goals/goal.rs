@@ -11,6 +11,7 @@ use crate::planners::heuristic::goals::open_door::OpenDoorGoal;
 use crate::planners::heuristic::goals::pass_through_door::PassThroughDoorGoal;
 use crate::planners::heuristic::goals::pickup_health::PickupHealthGoal;
 use crate::planners::heuristic::goals::pickup_sword::PickupSwordGoal;
+use crate::planners::heuristic::goals::pickup_treasure::PickupTreasureGoal;
 use crate::planners::heuristic::goals::random_explore::RandomExploreGoal;
 use crate::planners::heuristic::goals::reach_exit::ReachExitGoal;
 use crate::planners::heuristic::goals::wait_on_tile::WaitOnTileGoal;
@@ -33,6 +34,8 @@ pub enum Goal {
     PickupHealth(Position),
     AvoidEnemy(Position),
     KillEnemy(Position),
+    KillBoss(Position),
+    PickupTreasure(Position),
     FetchBoulder(Position),
     DropBoulder,
     DropBoulderOnPlate(Color, Position),
@@ -59,6 +62,8 @@ impl Goal {
             Goal::ReachExit => ReachExitGoal.execute(state, player_index),
             Goal::KillEnemy(pos) => KillEnemyGoal(*pos).execute(state, player_index),
             Goal::AvoidEnemy(pos) => AvoidEnemyGoal(*pos).execute(state, player_index),
+            Goal::KillBoss(pos) => KillEnemyGoal(*pos).execute(state, player_index),
+            Goal::PickupTreasure(pos) => PickupTreasureGoal(*pos).execute(state, player_index),
             Goal::FetchBoulder(pos) => FetchBoulderGoal(*pos).execute(state, player_index),
             Goal::DropBoulderOnPlate(_color, pos) => {
                 DropBoulderOnPlateGoal(*pos).execute(state, player_index)
@@ -92,6 +97,8 @@ impl Goal {
             Goal::PickupHealth(_pos) => "PickupHealth".to_string(),
             Goal::AvoidEnemy(_pos) => "AvoidEnemy".to_string(),
             Goal::KillEnemy(_pos) => "KillEnemy".to_string(),
+            Goal::KillBoss(_pos) => "KillBoss".to_string(),
+            Goal::PickupTreasure(_pos) => "PickupTreasure".to_string(),
             Goal::FetchBoulder(_pos) => "FetchBoulder".to_string(),
             Goal::DropBoulder => "DropBoulder".to_string(),
             Goal::DropBoulderOnPlate(color, _pos) => format!("DropOnPlate({:?})", color),
@@ -1,5 +1,5 @@
 use crate::infra::Position;
-use crate::infra::{path_to_action, use_direction};
+use crate::infra::{timed_path_to_action, use_direction};
 use crate::planners::heuristic::goals::goal::ExecuteGoal;
 use super::super::pathfinding::find_path_for_player;
 use crate::planners::heuristic::planner_state::PlannerState;
@@ -25,7 +25,7 @@ impl ExecuteGoal for KillEnemyGoal {
                     find_path_for_player(&state.world, player_index, player_pos, adjacent)
             {
                 state.world.players[player_index].current_path = Some(path.clone());
-                return path_to_action(player_pos, &path);
+                return timed_path_to_action(player_pos, &path);
             }
         }
         None
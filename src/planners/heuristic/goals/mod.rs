@@ -18,6 +18,7 @@ pub mod open_door;
 pub mod pass_through_door;
 pub mod pickup_health;
 pub mod pickup_sword;
+pub mod pickup_treasure;
 pub mod random_explore;
 pub mod reach_exit;
 pub mod wait_on_tile;
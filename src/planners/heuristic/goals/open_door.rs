@@ -1,7 +1,7 @@
 use tracing::debug;
 
 use crate::infra::Color;
-use crate::infra::{path_to_action, use_direction};
+use crate::infra::{path_length, timed_path_to_action, use_direction};
 use crate::planners::heuristic::goals::goal::ExecuteGoal;
 use super::super::pathfinding::find_path_for_player;
 use crate::planners::heuristic::planner_state::PlannerState;
@@ -46,7 +46,7 @@ impl ExecuteGoal for OpenDoorGoal {
 
                 // Try to path to this neighbor
                 if let Some(path) = state.world.find_path(player_pos, neighbor) {
-                    let path_len = path.len();
+                    let path_len = path_length(&path);
                     if best_target.is_none() || path_len < best_target.unwrap().2 {
                         best_target = Some((door_pos, neighbor, path_len));
                     }
@@ -67,6 +67,6 @@ impl ExecuteGoal for OpenDoorGoal {
         state.world.players[player_index].current_destination = Some(target_pos);
         let path = find_path_for_player(&state.world, player_index, player_pos, target_pos)?;
         state.world.players[player_index].current_path = Some(path.clone());
-        path_to_action(player_pos, &path)
+        timed_path_to_action(player_pos, &path)
     }
 }
@@ -1,7 +1,7 @@
 use tracing::debug;
 
 use crate::infra::Position;
-use crate::infra::path_to_action;
+use crate::infra::timed_path_to_action;
 use crate::planners::heuristic::goals::goal::ExecuteGoal;
 use super::super::pathfinding::find_path_for_player;
 use crate::planners::heuristic::planner_state::PlannerState;
@@ -94,7 +94,7 @@ impl ExecuteGoal for PassThroughDoorGoal {
             debug!("Navigating to neighbor {:?} before door at {:?}", neighbor_pos, door_pos);
             state.world.players[player_index].current_destination = Some(neighbor_pos);
             state.world.players[player_index].current_path = Some(path.clone());
-            return path_to_action(player_pos, &path);
+            return timed_path_to_action(player_pos, &path);
         }
 
         debug!("Cannot find path to neighbor {:?} for door at {:?}", neighbor_pos, door_pos);
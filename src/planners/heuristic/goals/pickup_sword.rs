@@ -1,4 +1,4 @@
-use crate::infra::path_to_action;
+use crate::infra::timed_path_to_action;
 use crate::planners::heuristic::goals::goal::ExecuteGoal;
 use crate::planners::heuristic::goals::validate_destination;
 use super::super::pathfinding::find_path_for_player;
@@ -20,6 +20,6 @@ impl ExecuteGoal for PickupSwordGoal {
         state.world.players[player_index].current_destination = Some(sword_pos);
         let path = find_path_for_player(&state.world, player_index, player_pos, sword_pos)?;
         state.world.players[player_index].current_path = Some(path.clone());
-        path_to_action(player_pos, &path)
+        timed_path_to_action(player_pos, &path)
     }
 }
@@ -0,0 +1,22 @@
+use tracing::debug;
+
+use super::super::pathfinding::find_path_for_player;
+use crate::infra::Position;
+use crate::infra::timed_path_to_action;
+use crate::planners::heuristic::goals::goal::ExecuteGoal;
+use crate::planners::heuristic::planner_state::PlannerState;
+use crate::swoq_interface::DirectedAction;
+
+pub struct PickupTreasureGoal(pub Position);
+
+impl ExecuteGoal for PickupTreasureGoal {
+    fn execute(&self, state: &mut PlannerState, player_index: usize) -> Option<DirectedAction> {
+        let player_pos = state.world.players[player_index].position;
+        let treasure_pos = self.0;
+        debug!("PickupTreasure: going to destination {:?}", treasure_pos);
+        state.world.players[player_index].current_destination = Some(treasure_pos);
+        let path = find_path_for_player(&state.world, player_index, player_pos, treasure_pos)?;
+        state.world.players[player_index].current_path = Some(path.clone());
+        timed_path_to_action(player_pos, &path)
+    }
+}
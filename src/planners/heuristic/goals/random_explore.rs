@@ -1,7 +1,7 @@
 use tracing::debug;
 
 use crate::infra::Position;
-use crate::infra::path_to_action;
+use crate::infra::timed_path_to_action;
 use crate::planners::heuristic::goals::goal::ExecuteGoal;
 use super::super::pathfinding::find_path_for_player;
 use crate::planners::heuristic::planner_state::PlannerState;
@@ -22,6 +22,6 @@ impl ExecuteGoal for RandomExploreGoal {
         let path =
             find_path_for_player(&state.world, player_index, player_position, target_position)?;
         state.world.players[player_index].current_path = Some(path.clone());
-        path_to_action(player_position, &path)
+        timed_path_to_action(player_position, &path)
     }
 }
@@ -1,145 +1,72 @@
-use std::time::Instant;
-
-use crate::infra::{GameConnection, GameObserver, Position};
+use crate::infra::Position;
+use crate::planners::Planner;
 use crate::planners::heuristic::goals::Goal;
 use crate::planners::heuristic::planner_state::PlannerState;
 use crate::planners::heuristic::strategies::StrategyPlanner;
 use crate::state::WorldState;
-use crate::swoq_interface::{self, DirectedAction, GameStatus};
+use crate::swoq_interface::DirectedAction;
 
-pub struct Game {
-    connection: GameConnection,
-    observer: Box<dyn GameObserver>,
+/// Strategy based planner: selects a goal per player every tick and executes it
+pub struct HeuristicPlanner {
     state: PlannerState,
-    current_level: i32,
     planner: StrategyPlanner,
-
-    // Game statistics (persistent across levels)
-    pub successful_runs: i32,
-    pub failed_runs: i32,
-    pub game_count: i32,
+    goal_names: Vec<String>,
+    warnings: Vec<String>,
 }
 
-impl Game {
-    pub fn new(connection: GameConnection, observer: impl GameObserver + 'static) -> Self {
-        let world = WorldState::new(0, 0, 0);
+impl HeuristicPlanner {
+    pub fn new() -> Self {
         Self {
-            connection,
-            observer: Box::new(observer),
-            state: PlannerState::new(world),
-            current_level: 0,
+            state: PlannerState::new(WorldState::new(0, 0, 0)),
             planner: StrategyPlanner::new(),
-            successful_runs: 0,
-            failed_runs: 0,
-            game_count: 0,
+            goal_names: Vec::new(),
+            warnings: Vec::new(),
         }
     }
+}
 
-    pub async fn run(
-        &mut self,
-        level: Option<i32>,
-        seed: Option<i32>,
-    ) -> Result<(), Box<dyn std::error::Error>> {
-        let mut game = self.connection.start(level, seed).await?;
-
-        self.game_count += 1;
-
-        self.observer.on_game_start(
-            &game.game_id,
-            game.seed,
-            game.map_width,
-            game.map_height,
-            game.visibility_range,
-        );
-
-        let world = WorldState::new(game.map_width, game.map_height, game.visibility_range);
-        self.state = PlannerState::new(world);
-        self.current_level = game.state.level;
-
-        loop {
-            if game.state.status != swoq_interface::GameStatus::Active as i32 {
-                break;
-            }
-
-            let tick_start = Instant::now();
-
-            self.check_level(&game);
-            self.update_world(&game.state);
-
-            let goals = self.plan();
-            let actions = self.excute(goals);
-            let action_result = self.act(&mut game, actions).await?;
-
-            // Log slow ticks
-            let tick_duration = tick_start.elapsed();
-            if tick_duration.as_millis() > 100 {
-                tracing::debug!(
-                    "⚠️  Tick {} took {:.2}ms result: {:?})",
-                    game.state.tick,
-                    tick_duration.as_secs_f64() * 1000.0,
-                    action_result
-                );
-            }
-
-            if action_result != swoq_interface::ActResult::Ok {
-                tracing::debug!("\n❌ Action failed with result: {:?}", action_result);
-                tracing::debug!("🛑 Stopping game due to action error");
-                break;
-            }
-        }
-
-        let status =
-            GameStatus::try_from(game.state.status).unwrap_or(GameStatus::FinishedCanceled);
-
-        // Update statistics
-        match status {
-            GameStatus::FinishedSuccess => self.successful_runs += 1,
-            _ => self.failed_runs += 1,
-        }
-
-        self.observer.on_game_finished(
-            status,
-            game.state.tick,
-            self.game_count,
-            self.successful_runs,
-            self.failed_runs,
-        );
-
-        Ok(())
+impl Default for HeuristicPlanner {
+    fn default() -> Self {
+        Self::new()
     }
+}
 
-    fn check_level(&mut self, game: &crate::infra::swoq::Game) {
-        if game.state.level != self.current_level {
-            self.observer.on_new_level(game.state.level);
-            // Create a new WorldState for the new level
-            let world = WorldState::new(game.map_width, game.map_height, game.visibility_range);
-            self.state = PlannerState::new(world);
-
-            // Reset the planner for the new level
-            self.planner = StrategyPlanner::new();
+impl Planner for HeuristicPlanner {
+    fn on_level_start(&mut self, world: &WorldState) {
+        self.state = PlannerState::new(world.clone());
+        self.planner = StrategyPlanner::new();
+    }
 
-            self.current_level = game.state.level;
-        }
+    fn decide(&mut self, world: &mut WorldState) -> (DirectedAction, Option<DirectedAction>) {
+        // The goals operate on the world owned by PlannerState, so swap the
+        // up-to-date world in for this decision and hand it back afterwards
+        std::mem::swap(&mut self.state.world, world);
+        let goals = self.plan();
+        let actions = self.excute(goals);
+        std::mem::swap(&mut self.state.world, world);
+
+        // Players that have exited still get goals, but the server rejects their actions
+        let action_of = |index: usize| {
+            actions
+                .get(index)
+                .filter(|_| world.players[index].is_active)
+                .map_or(DirectedAction::None, |(_, a)| *a)
+        };
+        let action1 = action_of(0);
+        let action2 = (world.players.len() > 1).then(|| action_of(1));
+        (action1, action2)
     }
 
-    fn update_world(&mut self, state: &swoq_interface::State) {
-        tracing::debug!("\n┌────────────────────────────────────────────────────────────┐");
-        tracing::debug!(
-            "│ 📊 STATE UPDATE - Tick {}                                  ",
-            state.tick
-        );
-        tracing::debug!("└────────────────────────────────────────────────────────────┘");
+    fn goal_names(&self) -> Vec<String> {
+        self.goal_names.clone()
+    }
 
-        self.state.world.update(state);
-        self.observer.on_state_update(
-            state,
-            &self.state.world,
-            self.game_count,
-            self.successful_runs,
-            self.failed_runs,
-        );
+    fn take_warnings(&mut self) -> Vec<String> {
+        std::mem::take(&mut self.warnings)
     }
+}
 
+impl HeuristicPlanner {
     fn plan(&mut self) -> Vec<Goal> {
         tracing::debug!("\n┌────────────────────────────────────────────────────────────┐");
         tracing::debug!("│ 🧠 PLANNING PHASE - Selecting goals                        ");
@@ -226,7 +153,7 @@ impl Game {
                     history[3].1
                 );
                 tracing::warn!("{}", log_message);
-                self.observer.on_oscillation_detected(&log_message);
+                self.warnings.push(log_message);
 
                 // Force player 1 to random exploration for 10 ticks
                 self.state.player_states[0].force_random_explore_ticks = 10;
@@ -261,50 +188,16 @@ impl Game {
                 player.position.x,
                 player.position.y
             );
-
-            self.observer
-                .on_goal_selected(idx, &goal.to_display_string(), &self.state.world);
-            if idx == 0 {
-                self.observer.on_action_selected(*action, &self.state.world);
-            }
         }
 
-        // Update observer with current paths for visualization
-        let paths: Vec<Option<Vec<crate::infra::Position>>> = self
-            .state
-            .world
-            .players
+        self.goal_names = results
             .iter()
-            .map(|player| player.current_path.clone())
+            .map(|(goal, _)| goal.to_display_string())
             .collect();
-        self.observer.on_paths_updated(paths);
 
         results
     }
 
-    async fn act(
-        &mut self,
-        game: &mut crate::infra::swoq::Game,
-        actions: Vec<(Goal, DirectedAction)>,
-    ) -> Result<swoq_interface::ActResult, Box<dyn std::error::Error + 'static>> {
-        let player_actions: &[(Goal, DirectedAction)] = &actions;
-        let action1 = player_actions
-            .first()
-            .map(|(_, a)| *a)
-            .unwrap_or(DirectedAction::None);
-        let action2 = if self.state.world.players.len() > 1 {
-            player_actions.get(1).map(|(_, a)| Some(*a)).unwrap_or(None)
-        } else {
-            None
-        };
-
-        let action_result = game.act(action1, action2).await?;
-
-        self.observer
-            .on_action_result(action1, action2, action_result, &self.state.world);
-        Ok(action_result)
-    }
-
     /// Post-execution safety check: If one player is on a plate and another is near a door,
     /// only force evacuation if the player near the door is actually moving toward it
     fn check_door_crush_safety(world: &WorldState, results: &mut [(Goal, DirectedAction)]) {
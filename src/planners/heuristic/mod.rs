@@ -1,7 +1,7 @@
-mod game;
 mod goals;
+mod heuristic_planner;
 mod pathfinding;
 mod planner_state;
 mod strategies;
 
-pub use game::Game;
+pub use heuristic_planner::HeuristicPlanner;
@@ -4,7 +4,7 @@
 
 use tracing::debug;
 
-use crate::infra::{AStar, Color, Position};
+use crate::infra::{AStar, Color, Position, ReservationTable};
 use crate::state::{Map, WorldState};
 use crate::swoq_interface::Tile;
 
@@ -141,7 +141,9 @@ fn find_path_avoiding_player(
         return None;
     }
 
-    AStar::find_path_with_cost(
+    let reservations = reservations_for_player(world, goal, other_player_path);
+
+    AStar::find_timed_path(
         map,
         start,
         goal,
@@ -180,13 +182,66 @@ fn find_path_avoiding_player(
 
             true
         },
-        |pos, _goal_pos, _tick| {
-            // Use enemy-aware movement cost
-            world.movement_cost(pos)
+        |pos, goal_pos, tick| {
+            // Use enemy-aware movement cost, including predicted enemy positions
+            world.path_cost(pos, goal_pos, tick)
         },
+        &reservations,
     )
 }
 
+/// Time-varying obstacles for a player planning around the other player's path:
+/// the other player itself, enemies that are predicted with certainty, and doors
+/// that close when the other player steps off the pressure plate holding them open
+fn reservations_for_player(
+    world: &WorldState,
+    goal: Position,
+    other_player_path: &[Position],
+) -> ReservationTable {
+    let mut reservations = ReservationTable::new();
+    if !world.is_enemy_target(&goal) {
+        reservations.merge(&world.enemy_reservations);
+    }
+    reservations.reserve_path(other_player_path, 0);
+
+    for color in [Color::Red, Color::Green, Color::Blue] {
+        let Some(closing_tick) = door_closing_tick(world, color, other_player_path) else {
+            continue;
+        };
+        for &door_pos in world.doors.get_positions(color).unwrap_or_default() {
+            debug!(
+                "  Door {:?} at {:?} closes at tick {} when the other player leaves its plate",
+                color, door_pos, closing_tick
+            );
+            reservations.block_from(door_pos, closing_tick);
+        }
+    }
+    reservations
+}
+
+/// Tick at which the doors of `color` close because the other player walks off
+/// the pressure plate that holds them open
+fn door_closing_tick(
+    world: &WorldState,
+    color: Color,
+    other_player_path: &[Position],
+) -> Option<i32> {
+    let plates = world.pressure_plates.get_positions(color)?;
+    if plates
+        .iter()
+        .any(|plate| matches!(world.map.get(plate), Some(Tile::Boulder)))
+    {
+        return None;
+    }
+    if !plates.contains(other_player_path.first()?) {
+        return None;
+    }
+    other_player_path
+        .iter()
+        .position(|pos| !plates.contains(pos))
+        .map(|tick| tick as i32)
+}
+
 /// Find a path for a player, avoiding collision with other player's path
 pub fn find_path_for_player(
     world: &WorldState,
@@ -328,7 +383,7 @@ pub fn find_path_with_custom_walkability<F>(
 where
     F: Fn(&Position, Position, i32) -> bool,
 {
-    AStar::find_path_with_cost(&world.map, start, goal, is_walkable, |pos, _goal_pos, _tick| {
-        world.movement_cost(pos)
+    AStar::find_path_with_cost(&world.map, start, goal, is_walkable, |pos, goal_pos, tick| {
+        world.path_cost(pos, goal_pos, tick)
     })
 }
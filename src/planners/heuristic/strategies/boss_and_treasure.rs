@@ -0,0 +1,136 @@
+use std::collections::HashSet;
+use tracing::debug;
+
+use crate::infra::Position;
+use crate::planners::heuristic::goals::Goal;
+use crate::planners::heuristic::planner_state::PlannerState;
+use crate::planners::heuristic::strategies::planner::{SelectGoal, StrategyType};
+use crate::state::BOSS_FIGHT_MIN_HEALTH;
+use crate::swoq_interface::Inventory;
+
+/// Once next to the boss, keep fighting until health drops to this (fleeing from an adjacent
+/// chaser only helps to reach health)
+const BOSS_RETREAT_HEALTH: i32 = 2;
+
+/// Players that cannot fight the boss keep at least this distance
+const BOSS_FLEE_DISTANCE: i32 = 3;
+
+/// Boss levels: fight the boss when armed and healthy, pick up the treasure it drops
+/// and carry it to the exit
+pub struct BossAndTreasureStrategy;
+
+impl SelectGoal for BossAndTreasureStrategy {
+    fn strategy_type(&self) -> StrategyType {
+        StrategyType::Coop
+    }
+
+    fn is_emergency(&self) -> bool {
+        true
+    }
+
+    #[tracing::instrument(
+        level = "debug",
+        skip(self, state, current_goals),
+        fields(strategy = "BossAndTreasureStrategy")
+    )]
+    fn try_select_coop(
+        &mut self,
+        state: &PlannerState,
+        current_goals: &[Option<Goal>],
+    ) -> Vec<Option<Goal>> {
+        let mut goals = vec![None; state.world.players.len()];
+        if !state.world.treasure_required {
+            return goals;
+        }
+
+        debug!(
+            "BossAndTreasureStrategy: boss at {:?}, treasure at {:?}, delivered={}",
+            state.world.boss_position,
+            state.world.treasure_position,
+            state.world.treasure_delivered
+        );
+
+        // Only one player needs to go for the treasure
+        let mut targeted_treasure: HashSet<Position> = HashSet::new();
+        for goal in current_goals.iter().flatten() {
+            if let Goal::PickupTreasure(pos) = goal {
+                targeted_treasure.insert(*pos);
+            }
+        }
+
+        for (player_index, player) in state.world.players.iter().enumerate() {
+            if !player.is_active || current_goals[player_index].is_some() {
+                continue;
+            }
+
+            // Carry the treasure out
+            if player.inventory == Inventory::Treasure {
+                if let Some(exit_pos) = state.world.exit_position
+                    && state.world.find_path(player.position, exit_pos).is_some()
+                {
+                    debug!(
+                        "BossAndTreasureStrategy: Player {} carrying treasure to exit",
+                        player_index + 1
+                    );
+                    goals[player_index] = Some(Goal::ReachExit);
+                }
+                continue;
+            }
+
+            // Pick up the treasure once the boss dropped it
+            if player.inventory == Inventory::None
+                && let Some(treasure_pos) = state.world.treasure_position
+                && !targeted_treasure.contains(&treasure_pos)
+                && state
+                    .world
+                    .find_path(player.position, treasure_pos)
+                    .is_some()
+            {
+                debug!(
+                    "BossAndTreasureStrategy: Player {} picking up treasure at {:?}",
+                    player_index + 1,
+                    treasure_pos
+                );
+                goals[player_index] = Some(Goal::PickupTreasure(treasure_pos));
+                targeted_treasure.insert(treasure_pos);
+                continue;
+            }
+
+            let Some(boss_pos) = state.world.boss_position else {
+                continue;
+            };
+
+            // Both players may attack the boss together
+            let dist = state
+                .world
+                .path_distance_to_enemy(player.position, boss_pos);
+            let engaged = dist <= 1 && player.health > BOSS_RETREAT_HEALTH;
+            if player.has_sword && (player.health >= BOSS_FIGHT_MIN_HEALTH || engaged) {
+                debug!(
+                    "BossAndTreasureStrategy: Player {} fighting boss at {:?} (health={})",
+                    player_index + 1,
+                    boss_pos,
+                    player.health
+                );
+                goals[player_index] = Some(Goal::KillBoss(boss_pos));
+                continue;
+            }
+
+            // Not ready for the fight: keep away while other strategies find a sword or health
+            if dist <= BOSS_FLEE_DISTANCE {
+                debug!(
+                    "BossAndTreasureStrategy: Player {} avoiding boss at {:?} (has_sword={}, health={}, distance={})",
+                    player_index + 1,
+                    boss_pos,
+                    player.has_sword,
+                    player.health,
+                    dist
+                );
+                goals[player_index] = Some(Goal::AvoidEnemy(boss_pos));
+            }
+        }
+
+        debug!("BossAndTreasureStrategy: Final goals: {:?}", goals);
+        goals
+    }
+}
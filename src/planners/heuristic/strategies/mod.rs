@@ -2,6 +2,7 @@
 pub mod planner;
 
 pub mod attack_or_flee_enemy;
+pub mod boss_and_treasure;
 pub mod boulder_on_plate;
 pub mod cooperative_door_passage;
 pub mod fallback_pressure_plate;
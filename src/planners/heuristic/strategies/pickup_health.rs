@@ -1,5 +1,6 @@
 use tracing::debug;
 
+use crate::infra::path_length;
 use crate::planners::heuristic::goals::Goal;
 use crate::planners::heuristic::planner_state::PlannerState;
 use crate::planners::heuristic::strategies::planner::{SelectGoal, StrategyType};
@@ -63,7 +64,7 @@ impl SelectGoal for PickupHealthStrategy {
 
                 // Check if this player can reach this health potion
                 if let Some(path) = state.world.find_path(player.position, *health_pos) {
-                    let distance = path.len();
+                    let distance = path_length(&path);
                     let should_select = match best_player {
                         None => true,
                         Some((_, best_health, best_distance)) => {
@@ -1,5 +1,6 @@
 use tracing::debug;
 
+use crate::infra::path_length;
 use crate::planners::heuristic::goals::Goal;
 use crate::planners::heuristic::planner_state::PlannerState;
 use crate::planners::heuristic::strategies::planner::{SelectGoal, StrategyType};
@@ -51,7 +52,7 @@ impl SelectGoal for PickupSwordStrategy {
 
                 // Check if this player can reach this sword
                 if let Some(path) = state.world.find_path(player.position, *sword_pos) {
-                    let distance = path.len();
+                    let distance = path_length(&path);
                     let should_select = match best_player {
                         None => true,
                         Some((_, best_distance)) => {
@@ -66,6 +66,7 @@ impl StrategyPlanner {
         Self {
             strategies: vec![
                 Box::new(attack_or_flee_enemy::AttackOrFleeEnemyStrategy),
+                Box::new(boss_and_treasure::BossAndTreasureStrategy),
                 Box::new(pickup_health::PickupHealthStrategy),
                 Box::new(pickup_sword::PickupSwordStrategy),
                 Box::new(reach_exit::ReachExitStrategy),
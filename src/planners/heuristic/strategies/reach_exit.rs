@@ -62,6 +62,15 @@ impl SelectGoal for ReachExitStrategy {
 
                 let all_can_reach = reachability.iter().all(|(_, can_reach)| *can_reach);
 
+                // On boss levels the treasure has to be carried out before anyone else exits
+                let all_may_exit = active_players
+                    .iter()
+                    .all(|(_, p)| state.world.treasure_allows_exit(p));
+                if !all_may_exit {
+                    debug!("ReachExitStrategy: Treasure must be delivered first, continuing");
+                    return goals;
+                }
+
                 // If not all active players can reach the exit, don't assign exit goal to anyone
                 if !all_can_reach {
                     debug!(
@@ -129,6 +138,14 @@ impl SelectGoal for ReachExitStrategy {
                 continue;
             }
 
+            if !state.world.treasure_allows_exit(player) {
+                debug!(
+                    "ReachExitStrategy: Player {} must wait for the treasure to be delivered",
+                    player_idx + 1
+                );
+                continue;
+            }
+
             // Check if we can actually path to the exit
             let can_path_to_exit = state.world.find_path(player.position, exit_pos).is_some();
             debug!(
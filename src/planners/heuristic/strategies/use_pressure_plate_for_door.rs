@@ -1,6 +1,6 @@
 use tracing::debug;
 
-use crate::infra::Color;
+use crate::infra::{Color, path_length};
 use crate::planners::heuristic::goals::Goal;
 use crate::planners::heuristic::planner_state::PlannerState;
 use crate::planners::heuristic::strategies::planner::{SelectGoal, StrategyType};
@@ -83,7 +83,7 @@ impl SelectGoal for UsePressurePlateForDoorStrategy {
                         0 // Already adjacent
                     } else {
                         match state.world.find_path(player.position, plate_pos) {
-                            Some(path) => path.len() as i32,
+                            Some(path) => path_length(&path) as i32,
                             None => continue, // Can't reach this plate
                         }
                     };
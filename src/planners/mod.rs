@@ -1,5 +1,11 @@
+mod game_runner;
+mod planner;
+
 pub mod goap;
 pub mod heuristic;
 
 #[cfg(feature = "rl")]
 pub mod rl;
+
+pub use game_runner::GameRunner;
+pub use planner::Planner;
@@ -0,0 +1,25 @@
+use crate::infra::GameSummary;
+use crate::state::WorldState;
+use crate::swoq_interface::DirectedAction;
+
+/// Decision making of a bot, driven tick by tick by the `GameRunner`
+pub trait Planner {
+    /// Called with a fresh world at the start of a game and whenever the level changes
+    fn on_level_start(&mut self, world: &WorldState);
+
+    /// Choose the actions of player 1 and, in two-player levels, player 2 for this tick
+    fn decide(&mut self, world: &mut WorldState) -> (DirectedAction, Option<DirectedAction>);
+
+    /// Display names of the goals the players are pursuing
+    fn goal_names(&self) -> Vec<String> {
+        Vec::new()
+    }
+
+    /// Warnings raised during the last decision, such as detected oscillation
+    fn take_warnings(&mut self) -> Vec<String> {
+        Vec::new()
+    }
+
+    /// Called once the game is over, with its outcome
+    fn on_game_finished(&mut self, _summary: &GameSummary) {}
+}
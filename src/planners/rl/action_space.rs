@@ -4,10 +4,11 @@ use crate::infra::Position;
 use crate::state::WorldState;
 
 use super::actions::{
-    ActionType, AttackEnemyAction, AvoidEnemyAction, DropBoulderAction, DropBoulderOnPlateAction,
-    ExploreAction, GetKeyAction, HuntEnemyAction, OpenDoorAction, PassThroughDoorWithPlateAction,
-    PickupBoulderAction, PickupHealthAction, PickupSwordAction, RLActionTrait, ReachExitAction,
-    TouchPlateAction, WaitAction, WaitOnPlateAction,
+    ActionType, AttackBossAction, AttackEnemyAction, AvoidEnemyAction, DropBoulderAction,
+    DropBoulderOnPlateAction, ExploreAction, GetKeyAction, HuntEnemyAction, OpenDoorAction,
+    PassThroughDoorWithPlateAction, PickupBoulderAction, PickupHealthAction, PickupSwordAction,
+    PickupTreasureAction, RLActionTrait, ReachExitAction, TouchPlateAction, WaitAction,
+    WaitOnPlateAction,
 };
 
 /// Maximum number of actions in the action space (padded with no-ops for masking)
@@ -62,6 +63,8 @@ impl ActionSpace {
         generated_actions.extend(TouchPlateAction::generate(world, player_index));
         generated_actions.extend(ReachExitAction::generate(world, player_index));
         generated_actions.extend(WaitAction::generate(world, player_index));
+        generated_actions.extend(AttackBossAction::generate(world, player_index));
+        generated_actions.extend(PickupTreasureAction::generate(world, player_index));
 
         // Fill in the action space with valid actions
         for action in generated_actions {
@@ -255,7 +258,7 @@ mod tests {
 
     #[test]
     fn test_action_encoder_feature_size() {
-        assert_eq!(ActionEncoder::action_feature_size(), 19); // 16 types + 3 position features
+        assert_eq!(ActionEncoder::action_feature_size(), 21); // 18 types + 3 position features
     }
 
     #[test]
@@ -0,0 +1,83 @@
+//! AttackBoss action - fight the boss to make it drop the treasure
+
+use crate::infra::Position;
+use crate::state::{BOSS_FIGHT_MIN_HEALTH, WorldState};
+use crate::swoq_interface::DirectedAction;
+
+use super::helpers::execute_use_adjacent;
+use super::{ActionExecutionState, ActionType, ExecutionStatus, RLActionTrait};
+
+#[derive(Debug, Clone)]
+pub struct AttackBossAction {
+    pub boss_pos: Position,
+}
+
+impl RLActionTrait for AttackBossAction {
+    fn precondition(&self, world: &WorldState, player_index: usize) -> bool {
+        let player = &world.players[player_index];
+        // Need sword, enough health and the boss must still be alive
+        player.has_sword
+            && player.health >= BOSS_FIGHT_MIN_HEALTH
+            && world.boss_position == Some(self.boss_pos)
+    }
+
+    fn prepare(&mut self, world: &mut WorldState, _player_index: usize) -> Option<Position> {
+        world.boss_position
+    }
+
+    fn execute(
+        &self,
+        world: &mut WorldState,
+        player_index: usize,
+        execution_state: &mut ActionExecutionState,
+    ) -> (DirectedAction, ExecutionStatus) {
+        // Check if we should stop attacking: health too low
+        if world.players[player_index].health < 2 {
+            execution_state.enemy_under_attack = None;
+            return (DirectedAction::None, ExecutionStatus::Complete);
+        }
+
+        // Follow the boss (it moves!) and keep hitting until it disappears
+        let Some(boss_pos) = world.boss_position else {
+            execution_state.enemy_under_attack = None;
+            return (DirectedAction::None, ExecutionStatus::Complete);
+        };
+
+        let (action, status) = execute_use_adjacent(world, player_index, boss_pos, execution_state);
+        if matches!(status, ExecutionStatus::Complete) && !matches!(action, DirectedAction::None) {
+            execution_state.enemy_under_attack = Some(boss_pos);
+            (action, ExecutionStatus::InProgress)
+        } else {
+            (action, status)
+        }
+    }
+
+    fn name(&self) -> String {
+        "AttackBoss".to_string()
+    }
+
+    fn is_combat_action(&self) -> bool {
+        true
+    }
+
+    fn action_type_index(&self) -> usize {
+        ActionType::AttackBoss as usize
+    }
+
+    fn target_position(&self) -> Option<Position> {
+        Some(self.boss_pos)
+    }
+
+    fn generate(world: &WorldState, player_index: usize) -> Vec<Box<dyn RLActionTrait>> {
+        let mut actions = Vec::new();
+
+        if let Some(boss_pos) = world.boss_position {
+            let action = AttackBossAction { boss_pos };
+            if action.precondition(world, player_index) {
+                actions.push(Box::new(action) as Box<dyn RLActionTrait>);
+            }
+        }
+
+        actions
+    }
+}
@@ -1,7 +1,8 @@
 //! Explore action - move toward unexplored frontier
 
-use crate::infra::Position;
+use crate::infra::{Position, path_length};
 use crate::state::WorldState;
+use crate::state::exploration::{exploration_clusters, frontier_owner, ranked_frontier};
 use crate::swoq_interface::DirectedAction;
 
 use super::helpers::execute_move_to;
@@ -13,14 +14,6 @@ pub struct ExploreAction {
 }
 
 impl ExploreAction {
-    fn find_nearest_frontier(player: &crate::state::PlayerState) -> Option<Position> {
-        player
-            .unexplored_frontier
-            .iter()
-            .min_by_key(|p| player.position.distance(p))
-            .copied()
-    }
-
     fn count_objects(world: &WorldState) -> ObjectCounts {
         ObjectCounts {
             num_keys: [
@@ -58,11 +51,12 @@ impl RLActionTrait for ExploreAction {
         let player = &world.players[player_index];
         let player_pos = player.position;
         let current_dest = player.current_destination;
-        let frontier = player.unexplored_frontier.clone();
 
         // Check if we have a current destination
         if let Some(current_dest) = current_dest {
-            if player_pos != current_dest {
+            if player_pos != current_dest
+                && frontier_owner(world, current_dest).is_none_or(|owner| owner == player_index)
+            {
                 if let Some(tile) = world.map.get(&current_dest) {
                     if matches!(
                         tile,
@@ -80,16 +74,9 @@ impl RLActionTrait for ExploreAction {
             }
         }
 
-        // Find nearest reachable frontier as new target
-        let mut frontier_by_distance: Vec<_> = frontier
-            .iter()
-            .map(|&pos| (player_pos.distance(&pos), pos))
-            .collect();
-        frontier_by_distance.sort_by_key(|(dist, _)| *dist);
-
-        frontier_by_distance
+        // Find the most informative reachable frontier as new target
+        ranked_frontier(world, player_index)
             .into_iter()
-            .map(|(_, frontier_pos)| frontier_pos)
             .find(|&frontier_pos| world.find_path(player_pos, frontier_pos).is_some())
     }
 
@@ -153,10 +140,10 @@ impl RLActionTrait for ExploreAction {
     fn generate(world: &WorldState, player_index: usize) -> Vec<Box<dyn RLActionTrait>> {
         let player = &world.players[player_index];
 
-        if let Some(nearest) = Self::find_nearest_frontier(player) {
-            if let Some(path) = world.find_path(player.position, nearest) {
+        if let Some(best) = exploration_clusters(world, player_index).first() {
+            if let Some(path) = world.find_path(player.position, best.target) {
                 let action = ExploreAction {
-                    cached_distance: path.len() as u32,
+                    cached_distance: path_length(&path) as u32,
                 };
                 if action.precondition(world, player_index) {
                     return vec![Box::new(action)];
@@ -1,6 +1,6 @@
 //! GetKey action - pick up a colored key
 
-use crate::infra::{Color, Position};
+use crate::infra::{Color, Position, path_length};
 use crate::state::WorldState;
 use crate::swoq_interface::{DirectedAction, Inventory};
 
@@ -72,7 +72,7 @@ impl RLActionTrait for GetKeyAction {
                         let action = GetKeyAction {
                             color,
                             key_pos: *key_pos,
-                            cached_distance: path.len() as u32,
+                            cached_distance: path_length(&path) as u32,
                         };
                         if action.precondition(world, player_index) {
                             actions.push(Box::new(action) as Box<dyn RLActionTrait>);
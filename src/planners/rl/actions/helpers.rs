@@ -1,6 +1,6 @@
 //! Helper functions for action execution - copied from GOAP
 
-use crate::infra::{Position, path_to_action, use_direction};
+use crate::infra::{Position, timed_path_to_action, use_direction};
 use crate::state::WorldState;
 use crate::swoq_interface::DirectedAction;
 
@@ -23,12 +23,12 @@ pub(super) fn execute_move_to(
     let player = &mut world.players[player_index];
     if let Some(path) = &player.current_path {
         // CBS path format: [current_pos, next_pos, ..., goal]
-        // path_to_action expects path[0] = current and path[1] = next
+        // timed_path_to_action expects path[0] = current and path[1] = next
 
         // Check if we have a valid path with at least current and next position
         if path.len() >= 2 && path[0] == player.position {
             // Path starts at current position, use it directly
-            if let Some(action) = path_to_action(player.position, path) {
+            if let Some(action) = timed_path_to_action(player.position, path) {
                 // Remove the first position (current) to advance the path
                 let remaining_path: Vec<Position> = path.iter().skip(1).copied().collect();
                 player.current_path = Some(remaining_path);
@@ -40,7 +40,7 @@ pub(super) fn execute_move_to(
             full_path.extend_from_slice(path);
 
             if full_path.len() >= 2
-                && let Some(action) = path_to_action(player.position, &full_path)
+                && let Some(action) = timed_path_to_action(player.position, &full_path)
             {
                 // Keep the path as-is (it's already advanced)
                 return (action, ExecutionStatus::InProgress);
@@ -1,5 +1,6 @@
 //! Simplified action trait for RL - removes planning-specific methods from GOAPActionTrait
 
+mod attack_boss;
 mod attack_enemy;
 mod avoid_enemy;
 mod drop_boulder;
@@ -13,11 +14,13 @@ mod pass_through_door_with_plate;
 mod pickup_boulder;
 mod pickup_health;
 mod pickup_sword;
+mod pickup_treasure;
 mod reach_exit;
 mod touch_plate;
 mod wait;
 mod wait_on_plate;
 
+pub use attack_boss::AttackBossAction;
 pub use attack_enemy::AttackEnemyAction;
 pub use avoid_enemy::AvoidEnemyAction;
 pub use drop_boulder::DropBoulderAction;
@@ -30,6 +33,7 @@ pub use pass_through_door_with_plate::PassThroughDoorWithPlateAction;
 pub use pickup_boulder::PickupBoulderAction;
 pub use pickup_health::PickupHealthAction;
 pub use pickup_sword::PickupSwordAction;
+pub use pickup_treasure::PickupTreasureAction;
 pub use reach_exit::ReachExitAction;
 pub use touch_plate::TouchPlateAction;
 pub use wait::WaitAction;
@@ -160,10 +164,12 @@ pub enum ActionType {
     TouchPlate = 13,
     ReachExit = 14,
     Wait = 15,
+    AttackBoss = 16,
+    PickupTreasure = 17,
 }
 
 impl ActionType {
-    pub const COUNT: usize = 16;
+    pub const COUNT: usize = 18;
 
     pub fn from_index(index: usize) -> Option<Self> {
         match index {
@@ -183,6 +189,8 @@ impl ActionType {
             13 => Some(ActionType::TouchPlate),
             14 => Some(ActionType::ReachExit),
             15 => Some(ActionType::Wait),
+            16 => Some(ActionType::AttackBoss),
+            17 => Some(ActionType::PickupTreasure),
             _ => None,
         }
     }
@@ -1,6 +1,6 @@
 //! OpenDoor action - use a key to open a door
 
-use crate::infra::{Color, Position};
+use crate::infra::{Color, Position, path_length};
 use crate::state::WorldState;
 use crate::swoq_interface::{DirectedAction, Inventory};
 
@@ -88,7 +88,7 @@ impl RLActionTrait for OpenDoorAction {
                         let action = OpenDoorAction {
                             color,
                             door_pos: *door_pos,
-                            cached_distance: path.len() as u32,
+                            cached_distance: path_length(&path) as u32,
                         };
                         if action.precondition(world, player_index) {
                             actions.push(Box::new(action) as Box<dyn RLActionTrait>);
@@ -0,0 +1,76 @@
+//! PickupTreasure action - pick up the treasure dropped by the boss
+
+use crate::infra::Position;
+use crate::state::WorldState;
+use crate::swoq_interface::{DirectedAction, Inventory};
+
+use super::helpers::execute_move_to;
+use super::{ActionExecutionState, ActionType, ExecutionStatus, RLActionTrait};
+
+#[derive(Debug, Clone)]
+pub struct PickupTreasureAction {
+    pub treasure_pos: Position,
+}
+
+impl RLActionTrait for PickupTreasureAction {
+    fn precondition(&self, world: &WorldState, player_index: usize) -> bool {
+        let player = &world.players[player_index];
+
+        // Treasure must be lying on the map and the inventory must be free
+        if world.treasure_position != Some(self.treasure_pos) || player.inventory != Inventory::None
+        {
+            return false;
+        }
+
+        // Validate path exists
+        world
+            .find_path(player.position, self.treasure_pos)
+            .is_some()
+    }
+
+    fn prepare(&mut self, world: &mut WorldState, player_index: usize) -> Option<Position> {
+        let player = &world.players[player_index];
+        if world
+            .find_path(player.position, self.treasure_pos)
+            .is_some()
+        {
+            Some(self.treasure_pos)
+        } else {
+            None
+        }
+    }
+
+    fn execute(
+        &self,
+        world: &mut WorldState,
+        player_index: usize,
+        execution_state: &mut ActionExecutionState,
+    ) -> (DirectedAction, ExecutionStatus) {
+        execute_move_to(world, player_index, self.treasure_pos, execution_state)
+    }
+
+    fn name(&self) -> String {
+        "PickupTreasure".to_string()
+    }
+
+    fn action_type_index(&self) -> usize {
+        ActionType::PickupTreasure as usize
+    }
+
+    fn target_position(&self) -> Option<Position> {
+        Some(self.treasure_pos)
+    }
+
+    fn generate(world: &WorldState, player_index: usize) -> Vec<Box<dyn RLActionTrait>> {
+        let mut actions = Vec::new();
+
+        if let Some(treasure_pos) = world.treasure_position {
+            let action = PickupTreasureAction { treasure_pos };
+            if action.precondition(world, player_index) {
+                actions.push(Box::new(action) as Box<dyn RLActionTrait>);
+            }
+        }
+
+        actions
+    }
+}
@@ -1,8 +1,8 @@
 //! ReachExit action - move to the exit to complete the level
 
-use crate::infra::Position;
+use crate::infra::{Position, path_length};
 use crate::state::WorldState;
-use crate::swoq_interface::DirectedAction;
+use crate::swoq_interface::{DirectedAction, Inventory};
 
 use super::helpers::execute_move_to;
 use super::{ActionExecutionState, ActionType, ExecutionStatus, RLActionTrait};
@@ -22,8 +22,10 @@ impl RLActionTrait for ReachExitAction {
             return false;
         }
 
-        // Player must have empty inventory
-        if player.inventory != crate::swoq_interface::Inventory::None {
+        // Player must have empty inventory, or carry the treasure out
+        if !matches!(player.inventory, Inventory::None | Inventory::Treasure)
+            || !world.treasure_allows_exit(player)
+        {
             return false;
         }
 
@@ -109,7 +111,7 @@ impl RLActionTrait for ReachExitAction {
             if let Some(path) = world.find_path(player.position, exit_pos) {
                 let action = ReachExitAction {
                     exit_pos,
-                    cached_distance: path.len() as u32,
+                    cached_distance: path_length(&path) as u32,
                 };
                 if action.precondition(world, player_index) {
                     actions.push(Box::new(action) as Box<dyn RLActionTrait>);
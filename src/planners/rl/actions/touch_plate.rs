@@ -1,5 +1,7 @@
 //! TouchPlate action - stand on a pressure plate (idle activity)
 
+use std::sync::Arc;
+
 use crate::infra::{Color, Position};
 use crate::state::WorldState;
 use crate::swoq_interface::{DirectedAction, Inventory};
@@ -70,7 +72,7 @@ impl RLActionTrait for TouchPlateAction {
             execution_state.wait_ticks += 1;
 
             if execution_state.wait_ticks >= 2 {
-                world.plates_touched.insert(self.plate_color);
+                Arc::make_mut(&mut world.plates_touched).insert(self.plate_color);
                 return (DirectedAction::None, ExecutionStatus::Complete);
             } else {
                 return (DirectedAction::None, ExecutionStatus::InProgress);
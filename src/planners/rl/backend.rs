@@ -0,0 +1,123 @@
+//! Burn backends for RL training and inference
+//!
+//! The CPU (ndarray) backend is always available. Building with the `rl-metal`
+//! feature adds the Metal backend for Apple GPUs and makes it the default.
+//! Code that is generic over the backend implements `BackendTask` and is run
+//! with the `BackendKind` chosen at run time.
+
+use burn::backend::Autodiff;
+use burn::backend::ndarray::{NdArray, NdArrayDevice};
+use burn::tensor::backend::AutodiffBackend;
+
+/// CPU backend, works on every machine
+pub type CpuBackend = NdArray<f32>;
+
+/// Apple GPU backend
+#[cfg(feature = "rl-metal")]
+pub type MetalBackend = burn::backend::Metal;
+
+/// Backend for inference when nothing else is chosen
+#[cfg(not(feature = "rl-metal"))]
+pub type DefaultBackend = CpuBackend;
+#[cfg(feature = "rl-metal")]
+pub type DefaultBackend = MetalBackend;
+
+/// Backend for the trainers when nothing else is chosen
+pub type DefaultAutodiffBackend = Autodiff<DefaultBackend>;
+
+/// Backend of the RL unit tests
+#[cfg(test)]
+pub(crate) type TestBackend = CpuBackend;
+
+/// Backend chosen at run time, defaults to Metal when it is built in
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum BackendKind {
+    #[cfg_attr(not(feature = "rl-metal"), default)]
+    Cpu,
+    #[cfg(feature = "rl-metal")]
+    #[default]
+    Metal,
+}
+
+/// Work that is generic over the backend, see `BackendKind::run`
+pub trait BackendTask {
+    type Output;
+
+    /// Run on backend `B`, inference can use `B::InnerBackend`
+    fn run<B: AutodiffBackend>(self, device: B::Device) -> Self::Output;
+}
+
+impl BackendKind {
+    pub fn name(&self) -> &'static str {
+        match self {
+            BackendKind::Cpu => "cpu",
+            #[cfg(feature = "rl-metal")]
+            BackendKind::Metal => "metal",
+        }
+    }
+
+    pub fn parse(name: &str) -> Result<Self, String> {
+        match name.trim().to_ascii_lowercase().as_str() {
+            "cpu" | "ndarray" => Ok(BackendKind::Cpu),
+            #[cfg(feature = "rl-metal")]
+            "metal" => Ok(BackendKind::Metal),
+            #[cfg(not(feature = "rl-metal"))]
+            "metal" => Err("The metal backend needs a build with the rl-metal feature".to_string()),
+            other => Err(format!("Unknown RL backend {:?}", other)),
+        }
+    }
+
+    /// Run `task` on this backend's default device
+    pub fn run<T: BackendTask>(self, task: T) -> T::Output {
+        match self {
+            BackendKind::Cpu => task.run::<Autodiff<CpuBackend>>(NdArrayDevice::Cpu),
+            #[cfg(feature = "rl-metal")]
+            BackendKind::Metal => task.run::<Autodiff<MetalBackend>>(Default::default()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::planners::rl::policy::{Critic, MAPPOConfig};
+    use burn::tensor::Tensor;
+
+    /// Critic forward and backward pass, checks autodiff works on the chosen backend
+    struct CriticGradient;
+
+    impl BackendTask for CriticGradient {
+        type Output = Vec<f32>;
+
+        fn run<B: AutodiffBackend>(self, device: B::Device) -> Vec<f32> {
+            let config = MAPPOConfig {
+                critic_hidden_size: 8,
+                critic_num_layers: 2,
+                ..Default::default()
+            };
+            let critic = Critic::<B>::new(&device, 4, &config);
+            let obs = Tensor::<B, 2>::ones([2, 4], &device).require_grad();
+            let value = critic.forward(obs.clone());
+            let grads = value.clone().sum().backward();
+
+            assert!(obs.grad(&grads).is_some());
+            value.into_data().to_vec().unwrap()
+        }
+    }
+
+    #[test]
+    fn test_parse_backend() {
+        assert_eq!(BackendKind::parse("CPU"), Ok(BackendKind::Cpu));
+        assert_eq!(BackendKind::parse("ndarray"), Ok(BackendKind::Cpu));
+        assert!(BackendKind::parse("tpu").is_err());
+        #[cfg(not(feature = "rl-metal"))]
+        assert!(BackendKind::parse("metal").is_err());
+    }
+
+    #[test]
+    fn test_cpu_backend_trains() {
+        let values = BackendKind::Cpu.run(CriticGradient);
+        assert_eq!(values.len(), 2);
+        assert!(values.iter().all(|value| value.is_finite()));
+    }
+}
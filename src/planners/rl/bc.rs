@@ -5,13 +5,22 @@ use burn::prelude::*;
 use burn::record::{FullPrecisionSettings, NamedMpkFileRecorder};
 use burn::tensor::backend::AutodiffBackend;
 use rand::seq::IndexedRandom;
+use serde::{Deserialize, Serialize};
 
-use super::action_space::MAX_ACTIONS;
-use super::encoder::EncoderConfig;
+use crate::infra::ReplayGameConnection;
+use crate::planners::Planner;
+use crate::sim::{Level, SimConfig, Simulator};
+use crate::state::WorldState;
+use crate::swoq_interface::{ActResult, DirectedAction, GameStatus, State};
+
+use super::action_space::{ActionSpace, MAX_ACTIONS, MultiAgentActionSpace};
+use super::actions::ActionExecutionState;
+use super::encoder::{EncoderConfig, StateEncoder};
 use super::policy::{MAPPOConfig, MAPPOModel};
 
 /// Configuration for behavioral cloning
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
 pub struct BCConfig {
     /// Number of training epochs
     pub epochs: usize,
@@ -38,8 +47,11 @@ impl Default for BCConfig {
 }
 
 /// A single expert demonstration
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ExpertDemo {
+    /// Level and tick the observations were made in
+    pub level: i32,
+    pub tick: i32,
     /// Local observations [num_agents, obs_size]
     pub local_obs: Vec<Vec<f32>>,
     /// Global observation [global_obs_size]
@@ -50,20 +62,39 @@ pub struct ExpertDemo {
     pub actions: Vec<i64>,
 }
 
-/// Dataset of expert demonstrations
+/// Dataset of expert demonstrations, with the games they were taken from
 pub struct DemoDataset {
     demos: Vec<ExpertDemo>,
+    games: Vec<ExpertGame>,
 }
 
 impl DemoDataset {
     pub fn new() -> Self {
-        Self { demos: Vec::new() }
+        Self {
+            demos: Vec::new(),
+            games: Vec::new(),
+        }
     }
 
     pub fn add(&mut self, demo: ExpertDemo) {
         self.demos.push(demo);
     }
 
+    pub fn add_game(&mut self, game: ExpertGame) {
+        self.games.push(game);
+    }
+
+    /// Games played or converted for the demos
+    pub fn games(&self) -> &[ExpertGame] {
+        &self.games
+    }
+
+    /// Move the demos and games of `other` into this dataset
+    pub fn append(&mut self, mut other: DemoDataset) {
+        self.demos.append(&mut other.demos);
+        self.games.append(&mut other.games);
+    }
+
     pub fn len(&self) -> usize {
         self.demos.len()
     }
@@ -266,6 +297,45 @@ impl<B: AutodiffBackend> BCTrainer<B> {
     }
 }
 
+/// Outcome of one game played by an expert planner for demonstrations
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ExpertGame {
+    /// Where the game was played: `simulator` or the replay file it was converted from
+    pub source: String,
+    /// Level the game started in
+    pub level: i32,
+    #[serde(with = "super::demos::game_status")]
+    pub status: GameStatus,
+    pub ticks: i32,
+    /// Ticks recorded as demos
+    pub demos: usize,
+    /// Ticks skipped because an expert move matched no action in the action space
+    pub unmatched: usize,
+}
+
+/// Index of the first valid action in `space` whose next move from `world` is `action`.
+/// Each candidate is prepared with a single-player path, standing in for the CBS phase.
+pub fn match_action(
+    world: &WorldState,
+    player_index: usize,
+    space: &ActionSpace,
+    action: DirectedAction,
+) -> Option<usize> {
+    space.iter_valid().find_map(|(index, candidate)| {
+        let mut scratch = world.clone();
+        let mut candidate = candidate.clone();
+        if let Some(target) = candidate.prepare(&mut scratch, player_index) {
+            let path = scratch.find_path(scratch.players[player_index].position, target);
+            let player = &mut scratch.players[player_index];
+            player.current_destination = Some(target);
+            player.current_path = path;
+        }
+        let mut execution_state = ActionExecutionState::default();
+        let (next_move, _) = candidate.execute(&mut scratch, player_index, &mut execution_state);
+        (next_move == action).then_some(index)
+    })
+}
+
 /// Collector for expert demonstrations from GOAP
 pub struct DemoCollector {
     dataset: DemoDataset,
@@ -281,12 +351,16 @@ impl DemoCollector {
     /// Add a demonstration from GOAP execution
     pub fn add_demo(
         &mut self,
+        level: i32,
+        tick: i32,
         local_obs: Vec<Vec<f32>>,
         global_obs: Vec<f32>,
         action_masks: Vec<Vec<f32>>,
         actions: Vec<i64>,
     ) {
         self.dataset.add(ExpertDemo {
+            level,
+            tick,
             local_obs,
             global_obs,
             action_masks,
@@ -312,6 +386,155 @@ impl DemoCollector {
     pub fn is_empty(&self) -> bool {
         self.dataset.is_empty()
     }
+
+    /// Record the expert's moves from the `observed` world when every player's move
+    /// maps onto its action space. Returns whether the tick was recorded.
+    fn record_tick(
+        &mut self,
+        observed: &WorldState,
+        tick: i32,
+        moves: [Option<DirectedAction>; 2],
+        encoder: &StateEncoder,
+    ) -> bool {
+        let spaces = MultiAgentActionSpace::generate(observed);
+        let actions: Option<Vec<i64>> = spaces
+            .player_spaces
+            .iter()
+            .enumerate()
+            .map(|(player, space)| {
+                let next_move = moves.get(player).copied().flatten();
+                let next_move = next_move.unwrap_or(DirectedAction::None);
+                match_action(observed, player, space, next_move).map(|index| index as i64)
+            })
+            .collect();
+        let Some(actions) = actions else {
+            return false;
+        };
+
+        self.add_demo(
+            observed.level,
+            tick,
+            (0..observed.players.len())
+                .map(|player| encoder.encode_local_obs(observed, player))
+                .collect(),
+            encoder.encode_global_obs(observed),
+            spaces.all_masks(),
+            actions,
+        );
+        true
+    }
+
+    /// Play `level` in the simulator with the expert `planner`, recording every tick
+    /// whose moves all map onto the action space
+    pub fn collect_game(
+        &mut self,
+        planner: &mut dyn Planner,
+        level: &Level,
+        sim_config: SimConfig,
+        encoder: &StateEncoder,
+    ) -> ExpertGame {
+        let mut simulator = Simulator::new(level, sim_config);
+        let mut world = WorldState::new(
+            simulator.map_width(),
+            simulator.map_height(),
+            simulator.visibility_range(),
+        );
+        world.update(&simulator.state());
+        planner.on_level_start(&world);
+
+        let mut demos = 0;
+        let mut unmatched = 0;
+        while simulator.status() == GameStatus::Active {
+            // Planners may annotate the world while deciding, demos see it as observed
+            let observed = world.clone();
+            let (action1, action2) = planner.decide(&mut world);
+            let action2 = action2.filter(|_| observed.players.len() > 1);
+
+            match self.record_tick(&observed, simulator.tick(), [Some(action1), action2], encoder) {
+                true => demos += 1,
+                false => unmatched += 1,
+            }
+
+            let result = simulator.act(action1, action2);
+            if result != ActResult::Ok {
+                tracing::debug!("Simulator rejected {:?}/{:?}: {:?}", action1, action2, result);
+                break;
+            }
+            world.update(&simulator.state());
+        }
+
+        let game = ExpertGame {
+            source: "simulator".to_string(),
+            level: level.number,
+            status: simulator.status(),
+            ticks: simulator.tick(),
+            demos,
+            unmatched,
+        };
+        self.dataset.add_game(game.clone());
+        game
+    }
+
+    /// Turn a recorded game into demos: the recorded states are replayed into a
+    /// `WorldState` and every recorded move is matched to the action space.
+    /// The replay should come from an expert, the heuristic or GOAP planner.
+    pub fn collect_replay(
+        &mut self,
+        replay: &ReplayGameConnection,
+        source: &str,
+        encoder: &StateEncoder,
+    ) -> ExpertGame {
+        let start = &replay.start_response;
+        let new_world = || {
+            WorldState::new(
+                start.map_width.unwrap_or_default(),
+                start.map_height.unwrap_or_default(),
+                start.visibility_range.unwrap_or_default(),
+            )
+        };
+        let mut state: State = start.state.clone().unwrap_or_default();
+        let first_level = state.level;
+        let mut world = new_world();
+        world.update(&state);
+
+        let mut demos = 0;
+        let mut unmatched = 0;
+        for (request, response) in &replay.steps {
+            let action1 = request
+                .action
+                .and_then(|action| DirectedAction::try_from(action).ok());
+            let action2 = request
+                .action2
+                .and_then(|action| DirectedAction::try_from(action).ok());
+            match self.record_tick(&world, state.tick, [action1, action2], encoder) {
+                true => demos += 1,
+                false => unmatched += 1,
+            }
+
+            let Some(next) = &response.state else {
+                break;
+            };
+            if next.level != state.level {
+                world = new_world();
+            }
+            state = next.clone();
+            world.update(&state);
+            if response.result != ActResult::Ok as i32 {
+                break;
+            }
+        }
+
+        let game = ExpertGame {
+            source: source.to_string(),
+            level: first_level,
+            status: GameStatus::try_from(state.status).unwrap_or(GameStatus::FinishedCanceled),
+            ticks: state.tick,
+            demos,
+            unmatched,
+        };
+        self.dataset.add_game(game.clone());
+        game
+    }
 }
 
 impl Default for DemoCollector {
@@ -337,6 +560,8 @@ mod tests {
         assert!(dataset.is_empty());
 
         dataset.add(ExpertDemo {
+            level: 1,
+            tick: 0,
             local_obs: vec![vec![0.0; 10]],
             global_obs: vec![0.0; 20],
             action_masks: vec![vec![1.0; MAX_ACTIONS]],
@@ -346,4 +571,25 @@ mod tests {
         assert_eq!(dataset.len(), 1);
         assert!(!dataset.is_empty());
     }
+
+    #[test]
+    fn test_collect_game_records_expert_moves() {
+        use crate::planners::heuristic::HeuristicPlanner;
+
+        let level = Level::parse(0, "#######\n#1   E#\n#######").unwrap();
+        let encoder = StateEncoder::new(EncoderConfig::default());
+        let mut collector = DemoCollector::new();
+        let mut planner = HeuristicPlanner::new();
+
+        let game = collector.collect_game(&mut planner, &level, SimConfig::default(), &encoder);
+
+        assert_eq!(game.status, GameStatus::FinishedSuccess);
+        assert_eq!(game.demos + game.unmatched, game.ticks as usize);
+        assert!(game.demos > 0);
+        assert_eq!(collector.len(), game.demos);
+
+        let demo = collector.dataset().iter().next().unwrap();
+        assert_eq!(demo.actions.len(), 1);
+        assert_eq!(demo.action_masks[0][demo.actions[0] as usize], 1.0);
+    }
 }
@@ -0,0 +1,278 @@
+//! Curriculum learning across the game's levels
+//!
+//! The mechanics arrive level by level: keys at 2, boulders at 6, plates at 7, enemies
+//! at 8, swords at 10, two players at 12 and the boss at 22. Training starts on the
+//! first level and moves on once the rolling success rate on the current level reaches
+//! a threshold. Earlier levels keep being replayed at a small rate so their skills are
+//! not forgotten.
+
+use rand::Rng;
+use rand::rngs::StdRng;
+use serde::{Deserialize, Serialize};
+use std::fs::File;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use crate::sim::Level;
+
+use super::env::LevelSampler;
+use super::metrics::MovingAverage;
+
+/// Curriculum configuration
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct CurriculumConfig {
+    /// Train on the levels one after another instead of all at once
+    pub enabled: bool,
+    /// Rolling success rate at which training moves on to the next level
+    pub success_threshold: f32,
+    /// Episodes played on a level before it can be passed, the success rate is
+    /// taken over this many latest episodes
+    pub min_episodes: usize,
+    /// Share of episodes replaying an earlier level
+    pub replay_rate: f32,
+}
+
+impl Default for CurriculumConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            success_threshold: 0.8,
+            min_episodes: 50,
+            replay_rate: 0.1,
+        }
+    }
+}
+
+/// Levels in order of number, shared by the trainer moving it on and the
+/// environments sampling their episodes from it
+pub struct Curriculum {
+    levels: Vec<Level>,
+    config: CurriculumConfig,
+    /// Index of the level being learned
+    stage: AtomicUsize,
+    /// Completions of the latest episodes played on the current level
+    success: Mutex<MovingAverage>,
+}
+
+/// Curriculum progress saved next to a training checkpoint
+#[derive(Debug, Serialize, Deserialize)]
+struct CurriculumState {
+    stage: usize,
+    level: i32,
+}
+
+impl Curriculum {
+    pub fn new(mut levels: Vec<Level>, config: CurriculumConfig) -> Self {
+        assert!(!levels.is_empty(), "Curriculum needs at least one level");
+        levels.sort_by_key(|level| level.number);
+        let success = MovingAverage::new(config.min_episodes.max(1));
+        Self {
+            levels,
+            config,
+            stage: AtomicUsize::new(0),
+            success: Mutex::new(success),
+        }
+    }
+
+    pub fn stage(&self) -> usize {
+        self.stage.load(Ordering::Relaxed)
+    }
+
+    /// Level being learned
+    pub fn current_level(&self) -> &Level {
+        &self.levels[self.stage()]
+    }
+
+    pub fn is_last_stage(&self) -> bool {
+        self.stage() + 1 == self.levels.len()
+    }
+
+    /// Record a finished episode. Only episodes played on the current level count
+    /// towards passing it, replays and episodes of an earlier stage are skipped.
+    pub fn record_episode(&self, level: i32, completed: bool) {
+        if level == self.current_level().number {
+            let mut success = self.success.lock().unwrap();
+            success.push(if completed { 1.0 } else { 0.0 });
+        }
+    }
+
+    /// Move on to the next level when the success rate over the latest `min_episodes`
+    /// episodes on the current level reaches the threshold. Returns whether the
+    /// curriculum moved on.
+    pub fn update(&self) -> bool {
+        if self.is_last_stage() {
+            return false;
+        }
+        let mut success = self.success.lock().unwrap();
+        if success.len() < success.window_size()
+            || success.average() < self.config.success_threshold
+        {
+            return false;
+        }
+
+        self.stage.fetch_add(1, Ordering::Relaxed);
+        success.clear();
+        true
+    }
+
+    /// File next to a checkpoint that keeps the curriculum stage
+    fn state_path(checkpoint: &str) -> PathBuf {
+        Path::new(checkpoint).with_extension("curriculum.json")
+    }
+
+    /// Save the stage next to the checkpoint at `checkpoint`
+    pub fn save(&self, checkpoint: &str) -> io::Result<()> {
+        let state = CurriculumState {
+            stage: self.stage(),
+            level: self.current_level().number,
+        };
+        let file = File::create(Self::state_path(checkpoint))?;
+        serde_json::to_writer_pretty(file, &state)?;
+        Ok(())
+    }
+
+    /// Continue at the stage saved with the checkpoint at `checkpoint`
+    pub fn restore(&self, checkpoint: &str) -> io::Result<()> {
+        let file = File::open(Self::state_path(checkpoint))?;
+        let state: CurriculumState = serde_json::from_reader(file)?;
+        let stage = self
+            .levels
+            .iter()
+            .position(|level| level.number == state.level)
+            .unwrap_or(state.stage.min(self.levels.len() - 1));
+        self.stage.store(stage, Ordering::Relaxed);
+        self.success.lock().unwrap().clear();
+        Ok(())
+    }
+}
+
+impl LevelSampler for Curriculum {
+    fn sample(&self, rng: &mut StdRng) -> &Level {
+        let stage = self.stage();
+        if stage > 0 && rng.random::<f32>() < self.config.replay_rate {
+            &self.levels[rng.random_range(0..stage)]
+        } else {
+            &self.levels[stage]
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::SeedableRng;
+
+    fn levels() -> Vec<Level> {
+        [3, 1, 2]
+            .into_iter()
+            .map(|number| Level::parse(number, "#####\n#1 E#\n#####").unwrap())
+            .collect()
+    }
+
+    fn record(curriculum: &Curriculum, level: i32, episodes: usize, completed: bool) {
+        for _ in 0..episodes {
+            curriculum.record_episode(level, completed);
+        }
+    }
+
+    #[test]
+    fn test_moves_on_when_the_success_rate_is_reached() {
+        let config = CurriculumConfig {
+            min_episodes: 4,
+            success_threshold: 0.75,
+            ..Default::default()
+        };
+        let curriculum = Curriculum::new(levels(), config);
+        assert_eq!(curriculum.current_level().number, 1);
+
+        // Too few episodes, then too few successes
+        record(&curriculum, 1, 3, true);
+        assert!(!curriculum.update());
+        record(&curriculum, 1, 2, false);
+        assert!(!curriculum.update());
+
+        record(&curriculum, 1, 5, true);
+        assert!(curriculum.update());
+        assert_eq!(curriculum.current_level().number, 2);
+
+        record(&curriculum, 2, 4, true);
+        assert!(curriculum.update());
+        assert!(curriculum.is_last_stage());
+        record(&curriculum, 3, 4, true);
+        assert!(!curriculum.update());
+        assert_eq!(curriculum.current_level().number, 3);
+    }
+
+    #[test]
+    fn test_only_episodes_on_the_current_level_count() {
+        let config = CurriculumConfig {
+            min_episodes: 4,
+            success_threshold: 0.75,
+            ..Default::default()
+        };
+        let curriculum = Curriculum::new(levels(), config);
+        record(&curriculum, 1, 4, true);
+        assert!(curriculum.update());
+
+        // Replays of level 1 and episodes still running from stage 0 are not level 2's
+        record(&curriculum, 1, 10, true);
+        assert!(!curriculum.update());
+        record(&curriculum, 2, 3, false);
+        record(&curriculum, 1, 10, true);
+        record(&curriculum, 2, 1, true);
+        assert!(!curriculum.update());
+        record(&curriculum, 2, 3, true);
+        assert!(curriculum.update());
+        assert_eq!(curriculum.current_level().number, 3);
+    }
+
+    #[test]
+    fn test_stage_is_restored_from_a_checkpoint() {
+        let config = CurriculumConfig {
+            min_episodes: 1,
+            ..Default::default()
+        };
+        let curriculum = Curriculum::new(levels(), config.clone());
+        record(&curriculum, 1, 1, true);
+        assert!(curriculum.update());
+
+        let dir = std::env::temp_dir().join(format!("robbot-curriculum-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let checkpoint = dir.join("checkpoint_7").to_string_lossy().to_string();
+        curriculum.save(&checkpoint).unwrap();
+
+        let resumed = Curriculum::new(levels(), config);
+        assert!(resumed.restore(&format!("{}.mpk", checkpoint)).is_ok());
+        assert_eq!(resumed.current_level().number, 2);
+        let unsaved = dir.join("final").to_string_lossy().to_string();
+        assert!(resumed.restore(&unsaved).is_err());
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_samples_earlier_levels_at_the_replay_rate() {
+        let config = CurriculumConfig {
+            min_episodes: 1,
+            replay_rate: 0.2,
+            ..Default::default()
+        };
+        let curriculum = Curriculum::new(levels(), config);
+        let mut rng = StdRng::seed_from_u64(1);
+        assert!((0..50).all(|_| curriculum.sample(&mut rng).number == 1));
+
+        record(&curriculum, 1, 1, true);
+        curriculum.update();
+        record(&curriculum, 2, 1, true);
+        curriculum.update();
+
+        let samples: Vec<i32> = (0..1000)
+            .map(|_| curriculum.sample(&mut rng).number)
+            .collect();
+        let replays = samples.iter().filter(|&&number| number < 3).count();
+        assert!((120..280).contains(&replays), "{} replays", replays);
+        assert!(samples.contains(&1) && samples.contains(&2));
+    }
+}
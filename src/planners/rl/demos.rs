@@ -0,0 +1,356 @@
+//! Expert demonstrations on disk
+//!
+//! A demo file holds JSON lines: a header with the observation sizes and the games
+//! the demos were taken from, followed by one `ExpertDemo` per line. Demos come from
+//! live games in the simulator (`DemoCollector::collect_game`) or from `.swoq`
+//! replays (`DemoCollector::collect_replay`), and are loaded for behavioral cloning
+//! instead of playing the expert again.
+
+use serde::{Deserialize, Serialize};
+use std::error::Error;
+use std::fmt;
+use std::fs::File;
+use std::io::{self, BufRead, BufReader, BufWriter, Write};
+use std::path::{Path, PathBuf};
+
+use super::action_space::MAX_ACTIONS;
+use super::bc::{DemoDataset, ExpertDemo, ExpertGame};
+use super::encoder::StateEncoder;
+
+/// Written in the header, files of other formats are rejected
+const DEMO_FORMAT: &str = "robbot-demos";
+const DEMO_VERSION: u32 = 1;
+
+#[derive(Debug)]
+pub enum DemoError {
+    Io {
+        path: PathBuf,
+        message: String,
+    },
+    Parse {
+        path: PathBuf,
+        line: usize,
+        message: String,
+    },
+    /// Not a demo file, or one of another version
+    Format {
+        path: PathBuf,
+    },
+    /// The demos were encoded with other observation sizes than the encoder's
+    ObsSize {
+        path: PathBuf,
+        expected: (usize, usize),
+        found: (usize, usize),
+    },
+}
+
+impl fmt::Display for DemoError {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            DemoError::Io { path, message } => {
+                write!(formatter, "Cannot access {}: {}", path.display(), message)
+            }
+            DemoError::Parse {
+                path,
+                line,
+                message,
+            } => write!(formatter, "Invalid demo in {} line {}: {}", path.display(), line, message),
+            DemoError::Format { path } => {
+                write!(formatter, "{} is not a version {} demo file", path.display(), DEMO_VERSION)
+            }
+            DemoError::ObsSize {
+                path,
+                expected,
+                found,
+            } => write!(
+                formatter,
+                "Demos in {} have local/global observation sizes {}/{}, the encoder uses {}/{}",
+                path.display(),
+                found.0,
+                found.1,
+                expected.0,
+                expected.1
+            ),
+        }
+    }
+}
+
+impl Error for DemoError {}
+
+/// First line of a demo file
+#[derive(Debug, Serialize, Deserialize)]
+struct DemoHeader {
+    format: String,
+    version: u32,
+    local_obs_size: usize,
+    global_obs_size: usize,
+    num_actions: usize,
+    games: Vec<ExpertGame>,
+}
+
+/// `GameStatus` by its protobuf name, prost enums have no serde support
+pub(crate) mod game_status {
+    use serde::{Deserialize, Deserializer, Serializer, de};
+
+    use crate::swoq_interface::GameStatus;
+
+    pub fn serialize<S: Serializer>(status: &GameStatus, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(status.as_str_name())
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<GameStatus, D::Error> {
+        let name = String::deserialize(deserializer)?;
+        GameStatus::from_str_name(&name)
+            .ok_or_else(|| de::Error::custom(format!("unknown game status {:?}", name)))
+    }
+}
+
+impl DemoDataset {
+    /// Write the demos to a new file, replacing an existing one
+    pub fn save(&self, path: &Path, encoder: &StateEncoder) -> Result<(), DemoError> {
+        let io_error = |e: io::Error| DemoError::Io {
+            path: path.to_path_buf(),
+            message: e.to_string(),
+        };
+        let header = DemoHeader {
+            format: DEMO_FORMAT.to_string(),
+            version: DEMO_VERSION,
+            local_obs_size: encoder.local_obs_size(),
+            global_obs_size: encoder.global_obs_size(),
+            num_actions: MAX_ACTIONS,
+            games: self.games().to_vec(),
+        };
+
+        let mut writer = BufWriter::new(File::create(path).map_err(io_error)?);
+        serde_json::to_writer(&mut writer, &header)
+            .map_err(io::Error::from)
+            .map_err(io_error)?;
+        writeln!(writer).map_err(io_error)?;
+        for demo in self.iter() {
+            serde_json::to_writer(&mut writer, demo)
+                .map_err(io::Error::from)
+                .map_err(io_error)?;
+            writeln!(writer).map_err(io_error)?;
+        }
+        writer.flush().map_err(io_error)
+    }
+
+    /// Read a demo file written by `save` with the same encoder settings
+    pub fn load(path: &Path, encoder: &StateEncoder) -> Result<Self, DemoError> {
+        let io_error = |e: io::Error| DemoError::Io {
+            path: path.to_path_buf(),
+            message: e.to_string(),
+        };
+        let parse_error = |line: usize, message: String| DemoError::Parse {
+            path: path.to_path_buf(),
+            line,
+            message,
+        };
+
+        let mut lines = BufReader::new(File::open(path).map_err(io_error)?).lines();
+        let header = lines
+            .next()
+            .transpose()
+            .map_err(io_error)?
+            .unwrap_or_default();
+        let header: DemoHeader = serde_json::from_str(&header).map_err(|_| DemoError::Format {
+            path: path.to_path_buf(),
+        })?;
+        if header.format != DEMO_FORMAT || header.version != DEMO_VERSION {
+            return Err(DemoError::Format {
+                path: path.to_path_buf(),
+            });
+        }
+        let expected = (encoder.local_obs_size(), encoder.global_obs_size());
+        let found = (header.local_obs_size, header.global_obs_size);
+        if expected != found || header.num_actions != MAX_ACTIONS {
+            return Err(DemoError::ObsSize {
+                path: path.to_path_buf(),
+                expected,
+                found,
+            });
+        }
+
+        let mut dataset = DemoDataset::new();
+        for game in header.games {
+            dataset.add_game(game);
+        }
+        for (index, line) in lines.enumerate() {
+            let number = index + 2;
+            let line = line.map_err(io_error)?;
+            if line.trim().is_empty() {
+                continue;
+            }
+            let demo: ExpertDemo =
+                serde_json::from_str(&line).map_err(|e| parse_error(number, e.to_string()))?;
+            check_sizes(&demo, found).map_err(|message| parse_error(number, message))?;
+            dataset.add(demo);
+        }
+        Ok(dataset)
+    }
+}
+
+/// Every agent needs an observation, a full mask and an action the mask allows
+fn check_sizes(demo: &ExpertDemo, (local_size, global_size): (usize, usize)) -> Result<(), String> {
+    let agents = demo.actions.len();
+    if agents == 0 || demo.local_obs.len() != agents || demo.action_masks.len() != agents {
+        return Err(format!("{} actions for {} observations", agents, demo.local_obs.len()));
+    }
+    if demo.local_obs.iter().any(|obs| obs.len() != local_size)
+        || demo.global_obs.len() != global_size
+    {
+        return Err("observation size differs from the header".to_string());
+    }
+    for (mask, &action) in demo.action_masks.iter().zip(&demo.actions) {
+        let allowed = usize::try_from(action)
+            .ok()
+            .and_then(|action| mask.get(action));
+        if mask.len() != MAX_ACTIONS || allowed != Some(&1.0) {
+            return Err(format!("action {} is not allowed by its mask", action));
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::infra::ReplayGameConnection;
+    use crate::planners::Planner;
+    use crate::planners::heuristic::HeuristicPlanner;
+    use crate::planners::rl::DemoCollector;
+    use crate::planners::rl::encoder::EncoderConfig;
+    use crate::sim::{Level, SimConfig, Simulator};
+    use crate::state::WorldState;
+    use crate::swoq_interface::{
+        ActRequest, ActResponse, GameStatus, StartRequest, StartResponse, StartResult,
+    };
+
+    const LEVEL: &str = "#######\n#1   E#\n#######";
+
+    fn temp_path(name: &str) -> PathBuf {
+        std::env::temp_dir().join(format!("robbot-{}-{}.jsonl", name, std::process::id()))
+    }
+
+    /// Record the heuristic planner playing `level` like the game connection does
+    fn record_replay(level: &Level) -> ReplayGameConnection {
+        let mut simulator = Simulator::new(level, SimConfig::default());
+        let mut world = WorldState::new(
+            simulator.map_width(),
+            simulator.map_height(),
+            simulator.visibility_range(),
+        );
+        let start_response = StartResponse {
+            result: StartResult::Ok as i32,
+            game_id: Some("game".to_string()),
+            map_width: Some(simulator.map_width()),
+            map_height: Some(simulator.map_height()),
+            visibility_range: Some(simulator.visibility_range()),
+            state: Some(simulator.state()),
+            seed: None,
+        };
+        world.update(&simulator.state());
+        let mut planner = HeuristicPlanner::new();
+        planner.on_level_start(&world);
+
+        let mut steps = Vec::new();
+        while simulator.status() == GameStatus::Active {
+            let (action, _) = planner.decide(&mut world);
+            let result = simulator.act(action, None);
+            steps.push((
+                ActRequest {
+                    game_id: "game".to_string(),
+                    action: Some(action as i32),
+                    action2: None,
+                },
+                ActResponse {
+                    result: result as i32,
+                    state: Some(simulator.state()),
+                },
+            ));
+            world.update(&simulator.state());
+        }
+
+        ReplayGameConnection {
+            start_request: StartRequest {
+                user_id: "id".to_string(),
+                user_name: "bot".to_string(),
+                level: Some(level.number),
+                seed: None,
+            },
+            start_response,
+            steps,
+        }
+    }
+
+    #[test]
+    fn test_replay_gives_the_demos_of_the_live_game() {
+        let level = Level::parse(0, LEVEL).unwrap();
+        let encoder = StateEncoder::new(EncoderConfig::default());
+
+        let mut live = DemoCollector::new();
+        let live_game =
+            live.collect_game(&mut HeuristicPlanner::new(), &level, SimConfig::default(), &encoder);
+        let mut replayed = DemoCollector::new();
+        let replay_game = replayed.collect_replay(&record_replay(&level), "game.swoq", &encoder);
+
+        assert_eq!(replay_game.source, "game.swoq");
+        assert_eq!(replay_game.status, GameStatus::FinishedSuccess);
+        assert_eq!(replay_game.ticks, live_game.ticks);
+        assert_eq!(replay_game.demos, live_game.demos);
+
+        let live = live.into_dataset();
+        let replayed = replayed.into_dataset();
+        for (a, b) in live.iter().zip(replayed.iter()) {
+            assert_eq!(a.tick, b.tick);
+            assert_eq!(a.actions, b.actions);
+            assert_eq!(a.local_obs, b.local_obs);
+        }
+    }
+
+    #[test]
+    fn test_save_and_load_round_trip() {
+        let level = Level::parse(0, LEVEL).unwrap();
+        let encoder = StateEncoder::new(EncoderConfig::default());
+        let mut collector = DemoCollector::new();
+        collector.collect_game(
+            &mut HeuristicPlanner::new(),
+            &level,
+            SimConfig::default(),
+            &encoder,
+        );
+        let dataset = collector.into_dataset();
+
+        let path = temp_path("demos");
+        dataset.save(&path, &encoder).unwrap();
+        let loaded = DemoDataset::load(&path, &encoder);
+
+        let other_encoder = StateEncoder::new(EncoderConfig {
+            max_enemies: 2,
+            ..Default::default()
+        });
+        let mismatch = DemoDataset::load(&path, &other_encoder);
+        std::fs::remove_file(&path).ok();
+
+        let loaded = loaded.unwrap();
+        assert_eq!(loaded.len(), dataset.len());
+        assert_eq!(loaded.games().len(), 1);
+        assert_eq!(loaded.games()[0].status, GameStatus::FinishedSuccess);
+        assert_eq!(loaded.games()[0].source, "simulator");
+        let (a, b) = (dataset.iter().last().unwrap(), loaded.iter().last().unwrap());
+        assert_eq!((a.level, a.tick, &a.actions), (b.level, b.tick, &b.actions));
+        assert_eq!(a.global_obs, b.global_obs);
+        assert!(matches!(mismatch, Err(DemoError::ObsSize { .. })));
+    }
+
+    #[test]
+    fn test_load_rejects_other_files() {
+        let encoder = StateEncoder::new(EncoderConfig::default());
+        let path = temp_path("not-demos");
+        std::fs::write(&path, "{\"event\":\"tick\"}\n").unwrap();
+        let result = DemoDataset::load(&path, &encoder);
+        std::fs::remove_file(&path).ok();
+
+        assert!(matches!(result, Err(DemoError::Format { .. })));
+    }
+}
@@ -1,5 +1,7 @@
 //! State encoder for RL - converts WorldState to tensor observations
 
+use serde::Deserialize;
+
 use crate::infra::{Color, Position};
 use crate::state::WorldState;
 use crate::swoq_interface::Inventory;
@@ -8,7 +10,8 @@ use crate::swoq_interface::Inventory;
 pub const MAX_MAP_SIZE: usize = 64;
 
 /// Configuration for the state encoder
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
 pub struct EncoderConfig {
     /// Maximum number of players to encode
     pub max_players: usize,
@@ -73,6 +76,9 @@ impl StateEncoder {
         // Exit features
         let exit_size = 3; // position (2), visible (1)
 
+        // Boss and treasure features: position (2), visible (1) each, carrying treasure (1)
+        let boss_treasure_size = 3 + 3 + 1;
+
         // Level info
         let level_size = 1;
 
@@ -85,6 +91,7 @@ impl StateEncoder {
             + plate_size
             + boulder_size
             + exit_size
+            + boss_treasure_size
             + level_size
     }
 
@@ -288,6 +295,22 @@ impl StateEncoder {
             obs.extend_from_slice(&[0.0, 0.0, 0.0]);
         }
 
+        // Boss and treasure
+        for pos in [world.boss_position, world.treasure_position] {
+            if let Some(pos) = pos {
+                obs.push(pos.x as f32 / map_width);
+                obs.push(pos.y as f32 / map_height);
+                obs.push(1.0); // visible
+            } else {
+                obs.extend_from_slice(&[0.0, 0.0, 0.0]);
+            }
+        }
+        obs.push(if player.inventory == Inventory::Treasure {
+            1.0
+        } else {
+            0.0
+        });
+
         // Level (normalized, assuming max level ~10)
         obs.push(world.level as f32 / 10.0);
 
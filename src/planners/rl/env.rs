@@ -1,15 +1,23 @@
 //! RL Environment - gym-like interface for training
 
-use crate::infra::Position;
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use serde::Deserialize;
+use std::sync::{Arc, mpsc};
+use std::thread::{self, JoinHandle};
+
+use crate::infra::{Position, path_length};
+use crate::sim::{Level, SimConfig, Simulator};
 use crate::state::WorldState;
-use crate::swoq_interface::DirectedAction;
+use crate::swoq_interface::{ActResult, DirectedAction, GameStatus};
 
 use super::action_space::{ActionSpace, MAX_ACTIONS, MultiAgentActionSpace};
 use super::actions::{ActionExecutionState, ExecutionStatus, RLActionTrait};
 use super::encoder::{EncoderConfig, StateEncoder};
 
 /// Environment configuration
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
 pub struct EnvConfig {
     /// Maximum steps per episode
     pub max_steps: usize,
@@ -21,7 +29,8 @@ pub struct EnvConfig {
     pub completion_bonus: f32,
     /// Penalty for player death
     pub death_penalty: f32,
-    /// Encoder configuration
+    /// Encoder configuration, shared with the policy instead of read from a file
+    #[serde(skip)]
     pub encoder_config: EncoderConfig,
 }
 
@@ -99,6 +108,10 @@ pub struct RLEnv {
     current_actions: Vec<Option<Box<dyn RLActionTrait>>>,
     /// Previous evaluation score for reward computation
     prev_score: f32,
+    /// Simulator advancing the world (None when stepping a bare WorldState)
+    simulator: Option<Simulator>,
+    /// Simulator at the start of the episode, for reset
+    initial_simulator: Option<Simulator>,
 }
 
 impl RLEnv {
@@ -117,12 +130,35 @@ impl RLEnv {
             execution_states: vec![ActionExecutionState::default(); num_players],
             current_actions: vec![None; num_players],
             prev_score,
+            simulator: None,
+            initial_simulator: None,
         }
     }
 
+    /// Create an environment that plays a full level through the offline simulator
+    pub fn from_level(level: &Level, sim_config: SimConfig, config: EnvConfig) -> Self {
+        let simulator = Simulator::new(level, sim_config);
+        let mut env = Self::new(Self::initial_world_for(&simulator), config);
+        env.initial_simulator = Some(simulator.clone());
+        env.simulator = Some(simulator);
+        env
+    }
+
+    /// Observed world at the start of a simulated game
+    fn initial_world_for(simulator: &Simulator) -> WorldState {
+        let mut world = WorldState::new(
+            simulator.map_width(),
+            simulator.map_height(),
+            simulator.visibility_range(),
+        );
+        world.update(&simulator.state());
+        world
+    }
+
     /// Reset the environment to initial state
     pub fn reset(&mut self) -> Observation {
         self.world = self.initial_world.clone();
+        self.simulator = self.initial_simulator.clone();
         self.steps = 0;
         self.execution_states = vec![ActionExecutionState::default(); self.world.players.len()];
         self.current_actions = vec![None; self.world.players.len()];
@@ -131,10 +167,20 @@ impl RLEnv {
         self.get_observation()
     }
 
+    /// Reset to the start of another level of the offline simulator
+    pub fn reset_with_level(&mut self, level: &Level, sim_config: SimConfig) -> Observation {
+        let simulator = Simulator::new(level, sim_config);
+        self.initial_world = Self::initial_world_for(&simulator);
+        self.initial_simulator = Some(simulator);
+        self.reset()
+    }
+
     /// Reset with a new initial world
     pub fn reset_with_world(&mut self, world: WorldState) -> Observation {
         self.initial_world = world.clone();
         self.world = world;
+        self.simulator = None;
+        self.initial_simulator = None;
         self.steps = 0;
         self.execution_states = vec![ActionExecutionState::default(); self.world.players.len()];
         self.current_actions = vec![None; self.world.players.len()];
@@ -209,7 +255,29 @@ impl RLEnv {
 
         // Apply actions to world (this would normally be done by the game server)
         // For training, we simulate the transitions
-        // self.world.apply_actions(&low_level_actions);
+        let mut sim_status = None;
+        if let Some(simulator) = self.simulator.as_mut() {
+            let mut player_actions =
+                low_level_actions
+                    .iter()
+                    .zip(self.world.players.iter())
+                    .map(|(&action, player)| {
+                        if player.is_active {
+                            action
+                        } else {
+                            DirectedAction::None
+                        }
+                    });
+            let action1 = player_actions.next().unwrap_or(DirectedAction::None);
+            let action2 = player_actions.next();
+
+            let result = simulator.act(action1, action2);
+            if result != ActResult::Ok {
+                tracing::debug!("Simulator rejected {:?}/{:?}: {:?}", action1, action2, result);
+            }
+            self.world.update(&simulator.state());
+            sim_status = Some(simulator.status());
+        }
 
         // Calculate reward
         let current_score = Self::evaluate_state(&self.world);
@@ -227,7 +295,9 @@ impl RLEnv {
         info.enemies_remaining = self.world.enemies.get_positions().len();
 
         // Check if level completed (all live players at exit)
-        let level_complete = if let Some(exit_pos) = self.world.exit_position {
+        let level_complete = if let Some(status) = sim_status {
+            status == GameStatus::FinishedSuccess
+        } else if let Some(exit_pos) = self.world.exit_position {
             self.world
                 .players
                 .iter()
@@ -243,12 +313,23 @@ impl RLEnv {
         }
 
         // Check if any player died
-        if self.world.players.iter().any(|p| p.health <= 0) {
+        let player_died = match sim_status {
+            Some(status) => {
+                matches!(status, GameStatus::FinishedPlayerDied | GameStatus::FinishedPlayer2Died)
+            }
+            None => self.world.players.iter().any(|p| p.health <= 0),
+        };
+        if player_died {
             done = true;
             info.player_died = true;
             reward += self.config.death_penalty;
         }
 
+        // Simulated game ended for another reason (timeout, no progress)
+        if sim_status.is_some_and(|status| status != GameStatus::Active) {
+            done = true;
+        }
+
         // Check for truncation
         let truncated = self.steps >= self.config.max_steps;
         if truncated {
@@ -276,7 +357,7 @@ impl RLEnv {
                 if player.health > 0 {
                     if let Some(path) = world.find_path(player.position, exit) {
                         // Closer to exit = higher score
-                        score -= path.len() as f32 * 0.1;
+                        score -= path_length(&path) as f32 * 0.1;
                     }
                 }
             }
@@ -329,39 +410,234 @@ impl RLEnv {
     }
 }
 
-/// Batch environment for parallel rollout collection
+/// Picks the level of each new episode of the simulated environments in a `BatchEnv`
+pub trait LevelSampler: Send + Sync {
+    fn sample(&self, rng: &mut StdRng) -> &Level;
+
+    /// Level of the first episode of env `index`, a sampled one by default
+    fn first(&self, _index: usize, rng: &mut StdRng) -> &Level {
+        self.sample(rng)
+    }
+}
+
+/// Every level equally often, the envs start on the levels in turn
+impl LevelSampler for Vec<Level> {
+    fn sample(&self, rng: &mut StdRng) -> &Level {
+        &self[rng.random_range(0..self.len())]
+    }
+
+    fn first(&self, index: usize, _rng: &mut StdRng) -> &Level {
+        &self[index % self.len()]
+    }
+}
+
+/// Levels a worker picks its episodes from
+struct LevelPool {
+    sampler: Arc<dyn LevelSampler>,
+    sim_config: SimConfig,
+    rng: StdRng,
+}
+
+impl LevelPool {
+    fn next_level(&mut self) -> &Level {
+        self.sampler.sample(&mut self.rng)
+    }
+
+    fn first_level(&mut self, index: usize) -> &Level {
+        self.sampler.first(index, &mut self.rng)
+    }
+}
+
+enum WorkerCommand {
+    Observe,
+    Reset,
+    Step(Vec<usize>),
+}
+
+enum WorkerReply {
+    Observation(Observation),
+    Step(StepResult),
+}
+
+/// Thread owning one environment, stepping it on command
+struct EnvWorker {
+    commands: mpsc::Sender<WorkerCommand>,
+    replies: mpsc::Receiver<WorkerReply>,
+    handle: Option<JoinHandle<()>>,
+}
+
+impl EnvWorker {
+    fn spawn(index: usize, mut env: RLEnv, mut pool: Option<LevelPool>) -> Self {
+        let (command_tx, command_rx) = mpsc::channel();
+        let (reply_tx, reply_rx) = mpsc::channel();
+
+        let handle = thread::Builder::new()
+            .name(format!("rl-env-{}", index))
+            .spawn(move || {
+                let mut reset = |env: &mut RLEnv| match pool.as_mut() {
+                    Some(pool) => {
+                        let sim_config = pool.sim_config.clone();
+                        env.reset_with_level(pool.next_level(), sim_config)
+                    }
+                    None => env.reset(),
+                };
+
+                for command in command_rx {
+                    let reply = match command {
+                        WorkerCommand::Observe => WorkerReply::Observation(env.get_observation()),
+                        WorkerCommand::Reset => WorkerReply::Observation(reset(&mut env)),
+                        WorkerCommand::Step(actions) => {
+                            let mut result = env.step(&actions);
+                            if result.done {
+                                result.observation = reset(&mut env);
+                            }
+                            WorkerReply::Step(result)
+                        }
+                    };
+                    if reply_tx.send(reply).is_err() {
+                        break;
+                    }
+                }
+            })
+            .expect("failed to spawn env worker");
+
+        Self {
+            commands: command_tx,
+            replies: reply_rx,
+            handle: Some(handle),
+        }
+    }
+
+    fn send(&self, command: WorkerCommand) {
+        self.commands.send(command).expect("env worker stopped");
+    }
+
+    fn receive(&self) -> WorkerReply {
+        self.replies.recv().expect("env worker stopped")
+    }
+}
+
+impl Drop for EnvWorker {
+    fn drop(&mut self) {
+        // Closing the command channel ends the worker loop
+        let (closed, _) = mpsc::channel();
+        self.commands = closed;
+        if let Some(handle) = self.handle.take() {
+            handle.join().ok();
+        }
+    }
+}
+
+/// Batch environment for parallel rollout collection.
+///
+/// Every environment lives on its own worker thread and all of them step at once.
+/// An environment whose episode is done starts the next one right away: its
+/// `StepResult` carries the first observation of the new episode.
 pub struct BatchEnv {
-    envs: Vec<RLEnv>,
+    workers: Vec<EnvWorker>,
 }
 
 impl BatchEnv {
+    /// Workers for `envs`, which restart their own episode when done
     pub fn new(envs: Vec<RLEnv>) -> Self {
-        Self { envs }
+        let workers = envs
+            .into_iter()
+            .enumerate()
+            .map(|(index, env)| EnvWorker::spawn(index, env, None))
+            .collect();
+        Self { workers }
+    }
+
+    /// `num_envs` simulated environments playing `levels`. Env `i` starts on level
+    /// `i % levels.len()` and picks each next episode's level at random.
+    pub fn from_levels(
+        levels: &[Level],
+        num_envs: usize,
+        sim_config: SimConfig,
+        config: EnvConfig,
+        seed: u64,
+    ) -> Self {
+        assert!(!levels.is_empty(), "BatchEnv needs at least one level");
+        Self::with_sampler(Arc::new(levels.to_vec()), num_envs, sim_config, config, seed)
+    }
+
+    /// `num_envs` simulated environments playing the levels `sampler` picks. Env `i`
+    /// picks the level of every episode with its own RNG, seeded with `seed + i`.
+    pub fn with_sampler(
+        sampler: Arc<dyn LevelSampler>,
+        num_envs: usize,
+        sim_config: SimConfig,
+        config: EnvConfig,
+        seed: u64,
+    ) -> Self {
+        let workers = (0..num_envs)
+            .map(|index| {
+                let mut pool = LevelPool {
+                    sampler: sampler.clone(),
+                    sim_config: sim_config.clone(),
+                    rng: StdRng::seed_from_u64(seed.wrapping_add(index as u64)),
+                };
+                let level = pool.first_level(index);
+                let env = RLEnv::from_level(level, sim_config.clone(), config.clone());
+                EnvWorker::spawn(index, env, Some(pool))
+            })
+            .collect();
+        Self { workers }
+    }
+
+    /// Send one command to every worker and wait for all replies, in env order
+    fn broadcast(&self, commands: impl Iterator<Item = WorkerCommand>) -> Vec<WorkerReply> {
+        for (worker, command) in self.workers.iter().zip(commands) {
+            worker.send(command);
+        }
+        self.workers.iter().map(EnvWorker::receive).collect()
+    }
+
+    fn observations(replies: Vec<WorkerReply>) -> Vec<Observation> {
+        replies
+            .into_iter()
+            .map(|reply| match reply {
+                WorkerReply::Observation(observation) => observation,
+                WorkerReply::Step(_) => unreachable!("env worker answered out of turn"),
+            })
+            .collect()
+    }
+
+    /// Current observation of every environment
+    pub fn observe_all(&mut self) -> Vec<Observation> {
+        let commands = std::iter::repeat_with(|| WorkerCommand::Observe);
+        Self::observations(self.broadcast(commands))
     }
 
     /// Reset all environments
     pub fn reset_all(&mut self) -> Vec<Observation> {
-        self.envs.iter_mut().map(|e| e.reset()).collect()
+        let commands = std::iter::repeat_with(|| WorkerCommand::Reset);
+        Self::observations(self.broadcast(commands))
     }
 
-    /// Step all environments
+    /// Step all environments, `actions` holds the action indices of each env's players
     pub fn step_all(&mut self, actions: &[Vec<usize>]) -> Vec<StepResult> {
-        self.envs
-            .iter_mut()
-            .zip(actions.iter())
-            .map(|(env, acts)| env.step(acts))
+        assert_eq!(actions.len(), self.workers.len(), "one action list per env");
+        let commands = actions.iter().map(|acts| WorkerCommand::Step(acts.clone()));
+        self.broadcast(commands)
+            .into_iter()
+            .map(|reply| match reply {
+                WorkerReply::Step(result) => result,
+                WorkerReply::Observation(_) => unreachable!("env worker answered out of turn"),
+            })
             .collect()
     }
 
     /// Get batch size
     pub fn batch_size(&self) -> usize {
-        self.envs.len()
+        self.workers.len()
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::planners::rl::curriculum::{Curriculum, CurriculumConfig};
 
     #[test]
     fn test_env_config_default() {
@@ -369,4 +645,77 @@ mod tests {
         assert_eq!(config.max_steps, 500);
         assert!((config.completion_bonus - 10.0).abs() < 1e-6);
     }
+
+    #[test]
+    fn test_env_from_level_advances_world() {
+        let level = Level::parse(1, "#########\n#1     E#\n#########").unwrap();
+        let mut env = RLEnv::from_level(&level, SimConfig::default(), EnvConfig::default());
+        env.reset();
+        assert_eq!(env.world().tick, 0);
+
+        // Masked action index falls back to waiting, which the simulator always accepts
+        let result = env.step(&[MAX_ACTIONS - 1]);
+        assert_eq!(env.world().tick, 1);
+        assert!(!result.done);
+    }
+
+    #[test]
+    fn test_batch_env_steps_every_level() {
+        let levels = [
+            Level::parse(1, "#########\n#1     E#\n#########").unwrap(),
+            Level::parse(2, "#######\n#1   E#\n#######").unwrap(),
+        ];
+        let config = EnvConfig {
+            max_steps: 2,
+            ..Default::default()
+        };
+        let mut envs = BatchEnv::from_levels(&levels, 3, SimConfig::default(), config, 7);
+        assert_eq!(envs.batch_size(), 3);
+        assert_eq!(envs.observe_all().len(), 3);
+
+        let wait = vec![vec![MAX_ACTIONS - 1]; 3];
+        let results = envs.step_all(&wait);
+        let played: Vec<usize> = results.iter().map(|result| result.info.level).collect();
+        assert_eq!(played, vec![1, 2, 1]);
+        assert!(results.iter().all(|result| !result.done));
+
+        // Truncated episodes restart on a level of the pool
+        let results = envs.step_all(&wait);
+        assert!(results.iter().all(|result| result.truncated));
+        let results = envs.step_all(&wait);
+        assert!(results.iter().all(|result| result.info.steps == 1));
+        assert_eq!(envs.reset_all().len(), 3);
+    }
+
+    #[test]
+    fn test_batch_env_plays_the_curriculum_level() {
+        let levels = vec![
+            Level::parse(1, "#########\n#1     E#\n#########").unwrap(),
+            Level::parse(2, "#######\n#1   E#\n#######").unwrap(),
+        ];
+        let config = CurriculumConfig {
+            min_episodes: 1,
+            replay_rate: 0.0,
+            ..Default::default()
+        };
+        let curriculum = Arc::new(Curriculum::new(levels, config));
+        let env_config = EnvConfig {
+            max_steps: 1,
+            ..Default::default()
+        };
+        let mut envs =
+            BatchEnv::with_sampler(curriculum.clone(), 3, SimConfig::default(), env_config, 7);
+
+        let wait = vec![vec![MAX_ACTIONS - 1]; 3];
+        let results = envs.step_all(&wait);
+        assert!(results.iter().all(|result| result.info.level == 1));
+
+        // Episodes that already started stay on level 1, the next ones play level 2
+        curriculum.record_episode(1, true);
+        assert!(curriculum.update());
+        let results = envs.step_all(&wait);
+        assert!(results.iter().all(|result| result.info.level == 1));
+        let results = envs.step_all(&wait);
+        assert!(results.iter().all(|result| result.info.level == 2));
+    }
 }
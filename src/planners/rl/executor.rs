@@ -1,7 +1,7 @@
 //! RL Executor - runs the trained RL agent for inference
 
 use burn::prelude::*;
-use burn::record::{FullPrecisionSettings, NamedMpkFileRecorder};
+use burn::record::{FullPrecisionSettings, NamedMpkFileRecorder, RecorderError};
 use burn::tensor::backend::Backend;
 
 use crate::infra::Position;
@@ -84,14 +84,14 @@ impl<B: Backend> RLExecutor<B> {
     }
 
     /// Load model from checkpoint
-    pub fn load_model(&mut self, path: &str) {
+    pub fn load_model(&mut self, path: &str) -> Result<(), RecorderError> {
         let recorder = NamedMpkFileRecorder::<FullPrecisionSettings>::new();
         self.model = self
             .model
             .clone()
-            .load_file(path, &recorder, &self.device)
-            .expect("Failed to load model");
+            .load_file(path, &recorder, &self.device)?;
         tracing::info!("Loaded model from {}", path);
+        Ok(())
     }
 
     /// Initialize execution state for players
@@ -3,13 +3,16 @@
 use std::time::Instant;
 
 use burn::prelude::*;
-use burn::tensor::backend::Backend;
+use burn::record::RecorderError;
+use burn::tensor::backend::{AutodiffBackend, Backend};
 
-use crate::infra::Position;
+use crate::infra::{GameSummary, Position};
+use crate::planners::Planner;
 use crate::state::WorldState;
 use crate::swoq_interface::DirectedAction;
 
 use super::action_space::ActionSpace;
+use super::backend::{BackendKind, BackendTask};
 use super::executor::{InferenceConfig, RLExecutor};
 use super::metrics::EvaluationMetrics;
 
@@ -41,8 +44,8 @@ impl<B: Backend> RLGameRunner<B> {
     }
 
     /// Load the trained model
-    pub fn load_model(&mut self, path: &str) {
-        self.executor.load_model(path);
+    pub fn load_model(&mut self, path: &str) -> Result<(), RecorderError> {
+        self.executor.load_model(path)
     }
 
     /// Initialize for a new game
@@ -101,6 +104,60 @@ impl<B: Backend> RLGameRunner<B> {
     }
 }
 
+impl<B: Backend> Planner for RLGameRunner<B> {
+    fn on_level_start(&mut self, world: &WorldState) {
+        self.init_game(world);
+    }
+
+    fn decide(&mut self, world: &mut WorldState) -> (DirectedAction, Option<DirectedAction>) {
+        let actions = self.get_actions(world);
+        let action1 = actions.first().copied().unwrap_or(DirectedAction::None);
+        let action2 = if world.players.len() > 1 {
+            Some(actions.get(1).copied().unwrap_or(DirectedAction::None))
+        } else {
+            None
+        };
+        (action1, action2)
+    }
+
+    fn goal_names(&self) -> Vec<String> {
+        self.get_action_names()
+    }
+
+    fn on_game_finished(&mut self, summary: &GameSummary) {
+        // Quests can end on a later level than they started
+        self.current_level = summary.level as usize;
+        self.end_episode(summary.is_success(), summary.deaths > 0);
+        self.print_summary();
+    }
+}
+
+/// Builds an `RLGameRunner` on the backend chosen at run time
+struct LoadPlanner {
+    config: InferenceConfig,
+}
+
+impl BackendTask for LoadPlanner {
+    type Output = Result<Box<dyn Planner>, RecorderError>;
+
+    fn run<B: AutodiffBackend>(self, device: B::Device) -> Self::Output {
+        let model_path = self.config.model_path.clone();
+        // Playing needs no gradients
+        let mut runner = RLGameRunner::<B::InnerBackend>::new(device, self.config);
+        runner.load_model(&model_path)?;
+        Ok(Box::new(runner))
+    }
+}
+
+/// Planner playing the checkpoint at `config.model_path`, for the main binary and evaluations
+pub fn load_planner(
+    backend: BackendKind,
+    config: InferenceConfig,
+) -> Result<Box<dyn Planner>, RecorderError> {
+    tracing::info!("Loading RL planner on the {} backend", backend.name());
+    backend.run(LoadPlanner { config })
+}
+
 /// Comparison runner for comparing RL vs GOAP performance
 pub struct ComparisonRunner {
     /// RL metrics
@@ -45,6 +45,16 @@ impl MovingAverage {
         self.values.len()
     }
 
+    /// Number of most recent values averaged
+    pub fn window_size(&self) -> usize {
+        self.window_size
+    }
+
+    pub fn clear(&mut self) {
+        self.values.clear();
+        self.sum = 0.0;
+    }
+
     pub fn is_empty(&self) -> bool {
         self.values.is_empty()
     }
@@ -3,8 +3,10 @@
 //! This module provides an RL-based alternative to the GOAP planner, using:
 //! - Fine-grained action space: dynamically generated action instances with masking
 //! - MAPPO: decentralized actors with centralized critic for multi-player coordination
-//! - Burn framework with Metal backend for M1 Ultra acceleration
-//! - Optional behavioral cloning warmup from GOAP expert trajectories
+//! - Burn framework on the CPU (ndarray) backend, or on Apple GPUs with the `rl-metal` feature
+//! - Curriculum over the levels, moving on as the agent masters each one
+//! - Optional behavioral cloning warmup from GOAP expert trajectories, collected live or
+//!   converted from replays and saved as demo files
 //!
 //! # Architecture
 //!
@@ -46,22 +48,32 @@ pub mod actions;
 pub mod encoder;
 
 // Burn-dependent modules
+pub mod backend;
 pub mod bc;
+pub mod curriculum;
+pub mod demos;
 pub mod env;
 pub mod executor;
 pub mod game;
 pub mod metrics;
 pub mod policy;
+pub mod settings;
 pub mod train;
 
 // Re-export commonly used types
 pub use action_space::{ActionSpace, MAX_ACTIONS, MultiAgentActionSpace};
 pub use actions::{ActionExecutionState, ActionType, ExecutionStatus, RLActionTrait};
-pub use bc::{BCConfig, BCTrainer, DemoCollector, DemoDataset, ExpertDemo};
+pub use backend::{BackendKind, BackendTask, CpuBackend, DefaultAutodiffBackend, DefaultBackend};
+pub use bc::{
+    BCConfig, BCTrainer, DemoCollector, DemoDataset, ExpertDemo, ExpertGame, match_action,
+};
+pub use curriculum::{Curriculum, CurriculumConfig};
+pub use demos::DemoError;
 pub use encoder::{EncoderConfig, ObservationBatch, StateEncoder};
-pub use env::{BatchEnv, EnvConfig, Observation, RLEnv, StepInfo, StepResult};
+pub use env::{BatchEnv, EnvConfig, LevelSampler, Observation, RLEnv, StepInfo, StepResult};
 pub use executor::{InferenceConfig, RLExecutor};
-pub use game::{ComparisonRunner, RLGameRunner};
+pub use game::{ComparisonRunner, RLGameRunner, load_planner};
 pub use metrics::{EvaluationMetrics, TrainingMetrics};
 pub use policy::{Actor, Critic, MAPPOConfig, MAPPOModel, RolloutBuffer};
+pub use settings::{DEFAULT_TRAIN_CONFIG_FILE, TrainSettings};
 pub use train::{PPOTrainer, TrainConfig};
@@ -4,12 +4,17 @@ use burn::module::Module;
 use burn::nn::{Linear, LinearConfig, Relu};
 use burn::prelude::*;
 use burn::tensor::activation::softmax;
+use serde::Deserialize;
 
 use super::action_space::MAX_ACTIONS;
 use super::encoder::{EncoderConfig, StateEncoder};
 
+/// Floor for probabilities before taking their log, masked actions have probability 0
+const MIN_PROB: f32 = 1e-8;
+
 /// Configuration for MAPPO networks
-#[derive(Debug, Config)]
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
 pub struct MAPPOConfig {
     /// Hidden layer size for actor network
     pub actor_hidden_size: usize,
@@ -98,9 +103,10 @@ impl<B: Backend> Actor<B> {
     pub fn get_probs(&self, obs: Tensor<B, 2>, mask: Tensor<B, 2>) -> Tensor<B, 2> {
         let logits = self.forward(obs);
 
-        // Apply mask: set invalid actions to -inf before softmax
-        let neg_inf = Tensor::full(logits.shape(), f32::NEG_INFINITY, &logits.device());
-        let masked_logits = mask.clone() * logits + (Tensor::ones_like(&mask) - mask) * neg_inf;
+        // Apply mask: set invalid actions to -inf before softmax.
+        // Filling instead of multiplying, 0 * -inf is NaN on IEEE backends such as the CPU
+        let invalid = mask.lower_elem(0.5);
+        let masked_logits = logits.mask_fill(invalid, f32::NEG_INFINITY);
 
         softmax(masked_logits, 1)
     }
@@ -144,7 +150,8 @@ impl<B: Backend> Actor<B> {
         actions: Tensor<B, 1, Int>,
     ) -> (Tensor<B, 1>, Tensor<B, 1>) {
         let probs = self.get_probs(obs, mask);
-        let log_probs = probs.clone().log();
+        // Clamped, so masked actions add 0 * log(MIN_PROB) to the entropy instead of NaN
+        let log_probs = probs.clone().clamp_min(MIN_PROB).log();
 
         // Reshape actions to 2D for gather
         let batch_size = actions.dims()[0];
@@ -360,6 +367,18 @@ impl RolloutBuffer {
         self.rewards.is_empty()
     }
 
+    /// Add the transitions of `other` after the ones in this buffer
+    pub fn append(&mut self, other: &RolloutBuffer) {
+        self.local_obs.extend_from_slice(&other.local_obs);
+        self.global_obs.extend_from_slice(&other.global_obs);
+        self.masks.extend_from_slice(&other.masks);
+        self.actions.extend_from_slice(&other.actions);
+        self.log_probs.extend_from_slice(&other.log_probs);
+        self.rewards.extend_from_slice(&other.rewards);
+        self.dones.extend_from_slice(&other.dones);
+        self.values.extend_from_slice(&other.values);
+    }
+
     pub fn clear(&mut self) {
         self.local_obs.clear();
         self.global_obs.clear();
@@ -407,6 +426,7 @@ impl Default for RolloutBuffer {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::planners::rl::backend::TestBackend;
 
     #[test]
     fn test_mappo_config_default() {
@@ -434,4 +454,62 @@ mod tests {
 
         assert_eq!(buffer.len(), 1);
     }
+
+    fn small_config() -> MAPPOConfig {
+        MAPPOConfig {
+            actor_hidden_size: 16,
+            actor_num_layers: 2,
+            critic_hidden_size: 16,
+            critic_num_layers: 2,
+            ..Default::default()
+        }
+    }
+
+    fn mask_with(
+        valid: &[usize],
+        device: &<TestBackend as Backend>::Device,
+    ) -> Tensor<TestBackend, 2> {
+        let mut mask = vec![0.0; MAX_ACTIONS];
+        for &index in valid {
+            mask[index] = 1.0;
+        }
+        Tensor::<TestBackend, 1>::from_floats(mask.as_slice(), device).reshape([1, MAX_ACTIONS])
+    }
+
+    #[test]
+    fn test_masked_actions_get_zero_probability() {
+        let device = Default::default();
+        let actor = Actor::<TestBackend>::new(&device, 8, &small_config());
+        let obs = Tensor::<TestBackend, 2>::ones([1, 8], &device);
+
+        let probs: Vec<f32> = actor
+            .get_probs(obs.clone(), mask_with(&[2, 5], &device))
+            .into_data()
+            .to_vec()
+            .unwrap();
+        assert!(probs.iter().all(|p| p.is_finite()));
+        assert!((probs[2] + probs[5] - 1.0).abs() < 1e-5);
+        assert_eq!(probs[0], 0.0);
+
+        let (action, log_prob) = actor.sample_action(obs, mask_with(&[2, 5], &device));
+        let action: Vec<i64> = action.into_data().to_vec().unwrap();
+        let log_prob: Vec<f32> = log_prob.into_data().to_vec().unwrap();
+        assert!(action[0] == 2 || action[0] == 5);
+        assert!(log_prob[0].is_finite());
+    }
+
+    #[test]
+    fn test_evaluate_actions_entropy_is_finite() {
+        let device = Default::default();
+        let actor = Actor::<TestBackend>::new(&device, 8, &small_config());
+        let obs = Tensor::<TestBackend, 2>::ones([1, 8], &device);
+        let actions = Tensor::<TestBackend, 1, Int>::from_ints([5], &device);
+
+        let (log_prob, entropy) = actor.evaluate_actions(obs, mask_with(&[2, 5], &device), actions);
+        let log_prob: Vec<f32> = log_prob.into_data().to_vec().unwrap();
+        let entropy: Vec<f32> = entropy.into_data().to_vec().unwrap();
+        assert!(log_prob[0].is_finite() && log_prob[0] <= 0.0);
+        // At most ln(2) with two valid actions
+        assert!(entropy[0].is_finite() && entropy[0] >= 0.0 && entropy[0] <= 0.7);
+    }
 }
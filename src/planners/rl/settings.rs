@@ -0,0 +1,206 @@
+//! Settings file of the `robbot-train` binary
+//!
+//! Every section is optional and falls back to the library defaults, see
+//! `robbot-train.example.toml`. The `[encoder]` and `[mappo]` sections are shared
+//! by demo collection, behavioral cloning and PPO, so their models stay compatible.
+
+use std::fs;
+use std::path::Path;
+
+use serde::Deserialize;
+
+use crate::config::{ConfigError, GoapConfig};
+use crate::eval::PlannerKind;
+
+use super::bc::BCConfig;
+use super::curriculum::CurriculumConfig;
+use super::encoder::EncoderConfig;
+use super::env::EnvConfig;
+use super::policy::MAPPOConfig;
+use super::train::TrainConfig;
+
+/// Settings file read when `--config` names none
+pub const DEFAULT_TRAIN_CONFIG_FILE: &str = "robbot-train.toml";
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct TrainSettings {
+    /// Level file or directory of `level-<n>.txt` files
+    pub levels_path: String,
+    /// Levels to play, empty uses every level found. PPO envs pick their episodes from them
+    pub levels: Vec<i32>,
+    /// Planner playing the expert games for demos, heuristic or goap
+    pub expert: PlannerKind,
+    pub goap: GoapConfig,
+    pub ppo: TrainConfig,
+    pub curriculum: CurriculumConfig,
+    pub env: EnvConfig,
+    pub bc: BCConfig,
+    pub encoder: EncoderConfig,
+    pub mappo: MAPPOConfig,
+}
+
+impl Default for TrainSettings {
+    fn default() -> Self {
+        Self {
+            levels_path: "./levels/".to_string(),
+            levels: Vec::new(),
+            expert: PlannerKind::Goap,
+            goap: GoapConfig::default(),
+            ppo: TrainConfig::default(),
+            curriculum: CurriculumConfig::default(),
+            env: EnvConfig::default(),
+            bc: BCConfig::default(),
+            encoder: EncoderConfig::default(),
+            mappo: MAPPOConfig::default(),
+        }
+    }
+}
+
+impl TrainSettings {
+    pub fn from_toml(text: &str, path: &Path) -> Result<Self, ConfigError> {
+        let settings: Self = toml::from_str(text).map_err(|e| ConfigError::Parse {
+            path: path.to_path_buf(),
+            message: e.message().to_string(),
+        })?;
+        settings.validate()?;
+        Ok(settings)
+    }
+
+    pub fn load(path: &Path) -> Result<Self, ConfigError> {
+        let text = fs::read_to_string(path).map_err(|e| ConfigError::Read {
+            path: path.to_path_buf(),
+            message: e.to_string(),
+        })?;
+        Self::from_toml(&text, path)
+    }
+
+    pub fn validate(&self) -> Result<(), ConfigError> {
+        if self.expert == PlannerKind::Rl {
+            return Err(ConfigError::Invalid {
+                setting: "expert",
+                reason: "demos need a heuristic or goap expert".to_string(),
+            });
+        }
+        let positive = [
+            ("ppo.num_iterations", self.ppo.num_iterations),
+            ("ppo.rollout_steps", self.ppo.rollout_steps),
+            ("ppo.num_envs", self.ppo.num_envs),
+            ("ppo.mini_batch_size", self.ppo.mini_batch_size),
+            ("ppo.checkpoint_freq", self.ppo.checkpoint_freq),
+            ("bc.batch_size", self.bc.batch_size),
+            ("bc.log_freq", self.bc.log_freq),
+            ("curriculum.min_episodes", self.curriculum.min_episodes),
+        ];
+        for (setting, value) in positive {
+            if value == 0 {
+                return Err(ConfigError::Invalid {
+                    setting,
+                    reason: "must be greater than 0".to_string(),
+                });
+            }
+        }
+
+        let rates = [
+            ("curriculum.success_threshold", self.curriculum.success_threshold, 0.0..=1.0),
+            ("curriculum.replay_rate", self.curriculum.replay_rate, 0.0..=1.0),
+        ];
+        for (setting, value, range) in rates {
+            if !range.contains(&value) {
+                return Err(ConfigError::Invalid {
+                    setting,
+                    reason: "must be between 0 and 1".to_string(),
+                });
+            }
+        }
+        Ok(())
+    }
+
+    /// PPO settings with the shared environment, encoder and network sections
+    pub fn train_config(&self) -> TrainConfig {
+        TrainConfig {
+            env_config: self.env_config(),
+            encoder_config: self.encoder.clone(),
+            mappo_config: self.mappo.clone(),
+            ..self.ppo.clone()
+        }
+    }
+
+    pub fn env_config(&self) -> EnvConfig {
+        EnvConfig {
+            encoder_config: self.encoder.clone(),
+            ..self.env.clone()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(text: &str) -> Result<TrainSettings, ConfigError> {
+        TrainSettings::from_toml(text, Path::new("train.toml"))
+    }
+
+    #[test]
+    fn test_sections_fill_the_train_config() {
+        let settings = parse(
+            r#"
+            levels = [2]
+            expert = "heuristic"
+
+            [ppo]
+            num_iterations = 20
+            checkpoint_dir = "out"
+
+            [curriculum]
+            enabled = true
+
+            [env]
+            max_steps = 100
+
+            [encoder]
+            max_enemies = 4
+
+            [mappo]
+            actor_hidden_size = 32
+            "#,
+        )
+        .unwrap();
+
+        assert_eq!(settings.expert, PlannerKind::Heuristic);
+        assert!(settings.curriculum.enabled);
+        assert_eq!(settings.curriculum.min_episodes, 50);
+        let config = settings.train_config();
+        assert_eq!(config.num_iterations, 20);
+        assert_eq!(config.rollout_steps, 128);
+        assert_eq!(config.checkpoint_dir, "out");
+        assert_eq!(config.env_config.max_steps, 100);
+        assert_eq!(config.env_config.encoder_config.max_enemies, 4);
+        assert_eq!(config.encoder_config.max_enemies, 4);
+        assert_eq!(config.mappo_config.actor_hidden_size, 32);
+        assert_eq!(config.mappo_config.critic_hidden_size, 512);
+    }
+
+    #[test]
+    fn test_example_settings_parse() {
+        let text = include_str!("../../../robbot-train.example.toml");
+        let settings = parse(text).unwrap();
+        assert_eq!(settings.expert, PlannerKind::Goap);
+    }
+
+    #[test]
+    fn test_invalid_settings() {
+        let error = parse("[ppo]\nnum_iteration = 5\n").unwrap_err();
+        assert!(matches!(error, ConfigError::Parse { .. }));
+
+        let error = parse("expert = \"rl\"\n").unwrap_err();
+        assert_eq!(error.to_string(), "Invalid expert: demos need a heuristic or goap expert");
+
+        let error = parse("[bc]\nbatch_size = 0\n").unwrap_err();
+        assert_eq!(error.to_string(), "Invalid bc.batch_size: must be greater than 0");
+
+        let error = parse("[curriculum]\nreplay_rate = 1.5\n").unwrap_err();
+        assert_eq!(error.to_string(), "Invalid curriculum.replay_rate: must be between 0 and 1");
+    }
+}
@@ -2,23 +2,31 @@
 
 use burn::optim::{AdamConfig, GradientsParams, Optimizer};
 use burn::prelude::*;
-use burn::record::{FullPrecisionSettings, NamedMpkFileRecorder};
+use burn::record::{FullPrecisionSettings, NamedMpkFileRecorder, RecorderError};
 use burn::tensor::backend::AutodiffBackend;
+use serde::Deserialize;
+use std::sync::Arc;
 
 use super::action_space::MAX_ACTIONS;
+use super::bc::{BCConfig, BCTrainer, DemoDataset};
+use super::curriculum::Curriculum;
 use super::encoder::EncoderConfig;
-use super::env::{EnvConfig, RLEnv};
+use super::env::{BatchEnv, EnvConfig, Observation};
+use super::metrics::{TensorBoardLogger, TrainingMetrics};
 use super::policy::{MAPPOConfig, MAPPOModel, RolloutBuffer};
 
 /// Training configuration
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
 pub struct TrainConfig {
     /// Number of training iterations
     pub num_iterations: usize,
     /// Steps per rollout
     pub rollout_steps: usize,
-    /// Number of parallel environments
+    /// Number of parallel environments, each stepped on its own thread
     pub num_envs: usize,
+    /// Seed of the environments' level choice, random when unset
+    pub seed: Option<u64>,
     /// Number of PPO epochs per rollout
     pub ppo_epochs: usize,
     /// Mini-batch size for PPO updates
@@ -35,13 +43,16 @@ pub struct TrainConfig {
     pub log_dir: String,
     /// Whether to use behavioral cloning warmup
     pub use_bc_warmup: bool,
-    /// Number of BC warmup iterations
+    /// Number of BC warmup epochs, see `PPOTrainer::warm_start`
     pub bc_warmup_iterations: usize,
     /// Environment config
+    #[serde(skip)]
     pub env_config: EnvConfig,
     /// Encoder config
+    #[serde(skip)]
     pub encoder_config: EncoderConfig,
     /// MAPPO config
+    #[serde(skip)]
     pub mappo_config: MAPPOConfig,
 }
 
@@ -51,6 +62,7 @@ impl Default for TrainConfig {
             num_iterations: 10000,
             rollout_steps: 128,
             num_envs: 8,
+            seed: None,
             ppo_epochs: 4,
             mini_batch_size: 64,
             learning_rate: 3e-4,
@@ -73,6 +85,12 @@ pub struct PPOTrainer<B: AutodiffBackend> {
     config: TrainConfig,
     device: B::Device,
     iteration: usize,
+    /// Reward and length per env of the episodes still running at the end of the last rollout
+    episode_rewards: Vec<f32>,
+    episode_lengths: Vec<usize>,
+    /// Curriculum moved on by the episodes played on its level, shared with the envs.
+    /// Its stage is saved with every checkpoint.
+    curriculum: Option<Arc<Curriculum>>,
 }
 
 impl<B: AutodiffBackend> PPOTrainer<B> {
@@ -84,80 +102,183 @@ impl<B: AutodiffBackend> PPOTrainer<B> {
             config,
             device,
             iteration: 0,
+            episode_rewards: Vec::new(),
+            episode_lengths: Vec::new(),
+            curriculum: None,
         }
     }
 
-    /// Collect a rollout from the environment
-    pub fn collect_rollout(&self, env: &mut RLEnv, buffer: &mut RolloutBuffer) {
-        buffer.clear();
+    /// Continue the iteration count of a resumed run, so its checkpoints are not overwritten
+    pub fn set_iteration(&mut self, iteration: usize) {
+        self.iteration = iteration;
+    }
 
-        let mut obs = env.get_observation();
+    /// Move `curriculum` on during training, the envs should sample their levels from it
+    pub fn set_curriculum(&mut self, curriculum: Arc<Curriculum>) {
+        self.curriculum = Some(curriculum);
+    }
 
-        for _ in 0..self.config.rollout_steps {
-            // Convert observations to tensors
-            let local_obs_tensor = self.obs_to_tensor(&obs.local_obs);
-            let global_obs_tensor = self.global_obs_to_tensor(&obs.global_obs);
-            let mask_tensor = self.masks_to_tensor(&obs.action_masks);
-
-            // Get actions from policy
-            let (actions_tensor, log_probs_tensor) = self
-                .model
-                .get_actions(local_obs_tensor.unsqueeze(), mask_tensor.unsqueeze());
-
-            // Get value estimate
-            let value = self.model.get_value(global_obs_tensor.clone().unsqueeze());
-
-            // Convert tensors to vectors
-            let actions: Vec<i64> = actions_tensor.squeeze::<1>(0).into_data().to_vec().unwrap();
-            let log_probs: Vec<f32> = log_probs_tensor
-                .squeeze::<1>(0)
-                .into_data()
-                .to_vec()
-                .unwrap();
-            let value_scalar: f32 = value.into_data().to_vec::<f32>().unwrap()[0];
-
-            // Step environment
-            let action_indices: Vec<usize> = actions.iter().map(|&a| a as usize).collect();
-            let result = env.step(&action_indices);
-
-            // Store transition
-            buffer.push(
-                obs.local_obs.clone(),
-                obs.global_obs.clone(),
-                obs.action_masks.clone(),
-                actions,
-                log_probs,
-                result.reward,
-                result.done,
-                value_scalar,
-            );
+    /// Start from a behavioral cloning model trained on `dataset` for
+    /// `bc_warmup_iterations` epochs
+    pub fn warm_start(&mut self, dataset: &DemoDataset, bc_config: BCConfig) {
+        tracing::info!("Running BC warmup for {} epochs", self.config.bc_warmup_iterations);
+        let bc_config = BCConfig {
+            epochs: self.config.bc_warmup_iterations,
+            ..bc_config
+        };
+        let mut bc = BCTrainer::<B>::new(
+            self.device.clone(),
+            &self.config.encoder_config,
+            &self.config.mappo_config,
+            bc_config,
+        );
+        bc.train(dataset);
+        self.model = bc.into_model();
+    }
 
-            obs = result.observation;
+    /// Collect a rollout from every environment into its own buffer, recording
+    /// finished episodes in `metrics`
+    pub fn collect_rollout(
+        &mut self,
+        envs: &mut BatchEnv,
+        buffers: &mut [RolloutBuffer],
+        metrics: &mut TrainingMetrics,
+    ) {
+        let num_envs = envs.batch_size();
+        assert_eq!(buffers.len(), num_envs, "one rollout buffer per env");
+        buffers.iter_mut().for_each(RolloutBuffer::clear);
+        self.episode_rewards.resize(num_envs, 0.0);
+        self.episode_lengths.resize(num_envs, 0);
+
+        let mut observations = envs.observe_all();
 
-            if result.done {
-                obs = env.reset();
+        for _ in 0..self.config.rollout_steps {
+            let (actions, log_probs, values) = self.act(&observations);
+
+            // Step environments
+            let action_indices: Vec<Vec<usize>> = actions
+                .iter()
+                .map(|env_actions| env_actions.iter().map(|&a| a as usize).collect())
+                .collect();
+            let results = envs.step_all(&action_indices);
+
+            // Store transitions
+            let transitions = results.into_iter().zip(actions).zip(log_probs).zip(values);
+            for (index, (((result, actions), log_probs), value)) in transitions.enumerate() {
+                let obs = std::mem::replace(&mut observations[index], result.observation);
+                buffers[index].push(
+                    obs.local_obs,
+                    obs.global_obs,
+                    obs.action_masks,
+                    actions,
+                    log_probs,
+                    result.reward,
+                    result.done,
+                    value,
+                );
+
+                self.episode_rewards[index] += result.reward;
+                self.episode_lengths[index] += 1;
+                if result.done {
+                    metrics.record_episode(
+                        self.episode_rewards[index],
+                        self.episode_lengths[index],
+                        result.info.level_complete,
+                        result.info.player_died,
+                    );
+                    if let Some(curriculum) = &self.curriculum {
+                        let level = result.info.level as i32;
+                        curriculum.record_episode(level, result.info.level_complete);
+                    }
+                    self.episode_rewards[index] = 0.0;
+                    self.episode_lengths[index] = 0;
+                }
             }
         }
     }
 
-    /// Perform PPO update on collected rollout
-    pub fn ppo_update(&mut self, buffer: &RolloutBuffer) -> (f32, f32, f32) {
-        // Get last value for GAE computation
-        let last_global_obs = buffer.global_obs.last().unwrap();
-        let last_value = if *buffer.dones.last().unwrap_or(&true) {
-            0.0
-        } else {
-            let global_tensor = self.global_obs_to_tensor(last_global_obs).unsqueeze();
-            let val = self.model.get_value(global_tensor);
-            val.into_data().to_vec::<f32>().unwrap()[0]
-        };
+    /// Sample actions and value estimates for every env in one actor and one critic
+    /// forward pass. Agents of all envs are stacked, so their number of players may differ.
+    /// Returns per env: actions, log probs and value
+    fn act(&self, observations: &[Observation]) -> (Vec<Vec<i64>>, Vec<Vec<f32>>, Vec<f32>) {
+        let local_obs: Vec<Vec<f32>> = observations
+            .iter()
+            .flat_map(|obs| obs.local_obs.iter().cloned())
+            .collect();
+        let masks: Vec<Vec<f32>> = observations
+            .iter()
+            .flat_map(|obs| obs.action_masks.iter().cloned())
+            .collect();
+        let global_obs: Vec<Vec<f32>> = observations
+            .iter()
+            .map(|obs| obs.global_obs.clone())
+            .collect();
 
-        // Compute returns and advantages
-        let (returns, advantages) = buffer.compute_returns_and_advantages(
-            last_value,
-            self.config.mappo_config.gamma,
-            self.config.mappo_config.gae_lambda,
-        );
+        let (actions_tensor, log_probs_tensor) = self
+            .model
+            .actor
+            .sample_action(self.obs_to_tensor(&local_obs), self.masks_to_tensor(&masks));
+        let values_tensor = self
+            .model
+            .get_value(self.batch_global_obs_to_tensor(&global_obs));
+
+        let actions: Vec<i64> = actions_tensor.into_data().to_vec().unwrap();
+        let log_probs: Vec<f32> = log_probs_tensor.into_data().to_vec().unwrap();
+        let values: Vec<f32> = values_tensor.into_data().to_vec().unwrap();
+
+        // Split the stacked agents back into their envs
+        let mut env_actions = Vec::with_capacity(observations.len());
+        let mut env_log_probs = Vec::with_capacity(observations.len());
+        let mut start = 0;
+        for obs in observations {
+            let end = start + obs.local_obs.len();
+            env_actions.push(actions[start..end].to_vec());
+            env_log_probs.push(log_probs[start..end].to_vec());
+            start = end;
+        }
+
+        (env_actions, env_log_probs, values)
+    }
+
+    /// Perform PPO update on the rollouts collected from each env
+    pub fn ppo_update(&mut self, buffers: &[RolloutBuffer]) -> (f32, f32, f32) {
+        // Bootstrap values for rollouts that stopped mid-episode, in one critic pass
+        let last_global_obs: Vec<Vec<f32>> = buffers
+            .iter()
+            .filter_map(|buffer| buffer.global_obs.last().cloned())
+            .collect();
+        if last_global_obs.is_empty() {
+            return (0.0, 0.0, 0.0);
+        }
+        let last_values: Vec<f32> = self
+            .model
+            .get_value(self.batch_global_obs_to_tensor(&last_global_obs))
+            .into_data()
+            .to_vec()
+            .unwrap();
+
+        // Compute returns and advantages per env, then pool the transitions
+        let mut buffer = RolloutBuffer::new();
+        let mut returns = Vec::new();
+        let mut advantages = Vec::new();
+        for (env_buffer, last_value) in buffers
+            .iter()
+            .filter(|buffer| !buffer.is_empty())
+            .zip(last_values)
+        {
+            let last_value = match env_buffer.dones.last() {
+                Some(true) => 0.0,
+                _ => last_value,
+            };
+            let (env_returns, env_advantages) = env_buffer.compute_returns_and_advantages(
+                last_value,
+                self.config.mappo_config.gamma,
+                self.config.mappo_config.gae_lambda,
+            );
+            returns.extend(env_returns);
+            advantages.extend(env_advantages);
+            buffer.append(env_buffer);
+        }
 
         // Normalize advantages
         let adv_mean: f32 = advantages.iter().sum::<f32>() / advantages.len() as f32;
@@ -181,18 +302,24 @@ impl<B: AutodiffBackend> PPOTrainer<B> {
         let mut total_entropy = 0.0f32;
         let mut num_updates = 0usize;
 
+        // Mini-batches stack the agents, so levels with one and two players go apart
+        let mut groups: Vec<Vec<usize>> = Vec::new();
+        for num_agents in [1, 2] {
+            let group: Vec<usize> = (0..buffer.len())
+                .filter(|&i| buffer.actions[i].len() == num_agents)
+                .collect();
+            if !group.is_empty() {
+                groups.push(group);
+            }
+        }
+
         for _ in 0..self.config.ppo_epochs {
             // Create mini-batches
-            let indices: Vec<usize> = (0..buffer.len()).collect();
-
-            for batch_start in (0..buffer.len()).step_by(self.config.mini_batch_size) {
-                let batch_end = (batch_start + self.config.mini_batch_size).min(buffer.len());
-                let batch_indices: Vec<usize> = indices[batch_start..batch_end].to_vec();
-
-                if batch_indices.is_empty() {
-                    continue;
-                }
+            let mini_batches = groups
+                .iter()
+                .flat_map(|group| group.chunks(self.config.mini_batch_size));
 
+            for batch_indices in mini_batches {
                 // Gather batch data
                 let batch_local_obs: Vec<Vec<Vec<f32>>> = batch_indices
                     .iter()
@@ -297,42 +424,47 @@ impl<B: AutodiffBackend> PPOTrainer<B> {
         (avg_policy_loss, avg_value_loss, avg_entropy)
     }
 
-    /// Run the training loop
-    pub fn train(&mut self, mut env: RLEnv) {
-        let mut buffer = RolloutBuffer::new();
+    /// Run the training loop, writing checkpoints to `checkpoint_dir` and metrics to `log_dir`
+    pub fn train(&mut self, mut envs: BatchEnv) {
+        let mut buffers = vec![RolloutBuffer::new(); envs.batch_size()];
+        let mut metrics = TrainingMetrics::default();
+        let mut logger = TensorBoardLogger::new(&self.config.log_dir);
+        std::fs::create_dir_all(&self.config.checkpoint_dir).ok();
 
         tracing::info!(
             "Starting training for {} iterations",
             self.config.num_iterations
         );
 
-        // BC warmup if enabled
-        if self.config.use_bc_warmup {
-            tracing::info!(
-                "Running BC warmup for {} iterations",
-                self.config.bc_warmup_iterations
-            );
-            // BC warmup would be implemented here using the bc module
-        }
-
-        for iteration in 0..self.config.num_iterations {
+        for iteration in self.iteration..self.config.num_iterations {
             self.iteration = iteration;
 
             // Collect rollout
-            self.collect_rollout(&mut env, &mut buffer);
+            self.collect_rollout(&mut envs, &mut buffers, &mut metrics);
 
             // PPO update
-            let (policy_loss, value_loss, entropy) = self.ppo_update(&buffer);
+            let (policy_loss, value_loss, entropy) = self.ppo_update(&buffers);
+            metrics.record_losses(policy_loss, value_loss, entropy);
+            let timesteps = buffers.iter().map(RolloutBuffer::len).sum();
+            metrics.update_iteration(iteration, timesteps);
+
+            if let Some(curriculum) = &self.curriculum
+                && curriculum.update()
+            {
+                tracing::info!(
+                    "Curriculum moves on to level {}",
+                    curriculum.current_level().number
+                );
+            }
 
             // Logging
             if iteration % 10 == 0 {
-                tracing::info!(
-                    "Iteration {}: policy_loss={:.4}, value_loss={:.4}, entropy={:.4}",
-                    iteration,
-                    policy_loss,
-                    value_loss,
-                    entropy
-                );
+                metrics.log_to_console();
+                logger.log_metrics(&metrics);
+                if let Some(curriculum) = &self.curriculum {
+                    let level = curriculum.current_level().number;
+                    logger.log_scalar("curriculum/level", level as f32, iteration);
+                }
             }
 
             // Save checkpoint
@@ -346,6 +478,8 @@ impl<B: AutodiffBackend> PPOTrainer<B> {
 
         // Final checkpoint
         self.save_checkpoint(&format!("{}/final", self.config.checkpoint_dir));
+        logger.log_metrics(&metrics);
+        logger.close();
         tracing::info!("Training complete!");
     }
 
@@ -356,18 +490,23 @@ impl<B: AutodiffBackend> PPOTrainer<B> {
             .clone()
             .save_file(path, &recorder)
             .expect("Failed to save checkpoint");
+        if let Some(curriculum) = &self.curriculum {
+            curriculum
+                .save(path)
+                .expect("Failed to save the curriculum stage");
+        }
         tracing::info!("Saved checkpoint to {}", path);
     }
 
     /// Load model checkpoint
-    pub fn load_checkpoint(&mut self, path: &str) {
+    pub fn load_checkpoint(&mut self, path: &str) -> Result<(), RecorderError> {
         let recorder = NamedMpkFileRecorder::<FullPrecisionSettings>::new();
         self.model = self
             .model
             .clone()
-            .load_file(path, &recorder, &self.device)
-            .expect("Failed to load checkpoint");
+            .load_file(path, &recorder, &self.device)?;
         tracing::info!("Loaded checkpoint from {}", path);
+        Ok(())
     }
 
     // Helper functions for tensor conversion
@@ -379,10 +518,6 @@ impl<B: AutodiffBackend> PPOTrainer<B> {
         Tensor::<B, 1>::from_floats(flat.as_slice(), &self.device).reshape([num_agents, obs_size])
     }
 
-    fn global_obs_to_tensor(&self, global_obs: &[f32]) -> Tensor<B, 1> {
-        Tensor::<B, 1>::from_floats(global_obs, &self.device)
-    }
-
     fn masks_to_tensor(&self, masks: &[Vec<f32>]) -> Tensor<B, 2> {
         let num_agents = masks.len();
         let flat: Vec<f32> = masks.iter().flatten().copied().collect();
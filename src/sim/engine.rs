@@ -0,0 +1,922 @@
+use std::collections::{HashMap, HashSet, VecDeque};
+
+use tracing::debug;
+
+use crate::infra::{Color, Position, tile_to_glyph};
+use crate::sim::Level;
+use crate::swoq_interface::{self, ActResult, DirectedAction, GameStatus, Inventory, State, Tile};
+
+/// Tunable rule constants for the simulator
+#[derive(Debug, Clone)]
+pub struct SimConfig {
+    pub visibility_range: i32,
+    /// Game ends with `FinishedTimeout` after this many ticks
+    pub max_ticks: i32,
+    /// Game ends with `FinishedNoProgress` when nothing new happened for this many ticks
+    pub no_progress_ticks: i32,
+    pub player_health: i32,
+    pub health_pickup: i32,
+    pub sword_damage: i32,
+    pub enemy_health: i32,
+    pub enemy_damage: i32,
+    pub boss_health: i32,
+    pub boss_damage: i32,
+}
+
+impl Default for SimConfig {
+    fn default() -> Self {
+        Self {
+            visibility_range: 4,
+            max_ticks: 2000,
+            no_progress_ticks: 500,
+            player_health: 5,
+            health_pickup: 3,
+            sword_damage: 1,
+            enemy_health: 3,
+            enemy_damage: 1,
+            boss_health: 10,
+            boss_damage: 2,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+struct SimPlayer {
+    position: Position,
+    health: i32,
+    inventory: Inventory,
+    has_sword: bool,
+    exited: bool,
+}
+
+#[derive(Debug, Clone)]
+struct SimEnemy {
+    position: Position,
+    health: i32,
+    damage: i32,
+    is_boss: bool,
+}
+
+/// Offline game engine implementing the server rules for a single level.
+///
+/// Rules:
+/// - Moving onto a key or the treasure picks it up (inventory must be empty),
+///   moving onto a sword or health picks it up as well.
+/// - Using a door with the matching key opens it permanently and consumes the key.
+///   A door is also open while a player or boulder is on a plate of its color;
+///   a player standing in a door that closes dies.
+/// - Using a boulder picks it up, using a free floor tile while holding one drops it.
+/// - Using an enemy requires a sword. Killing the boss drops the treasure.
+/// - Enemies that can see a player chase it and attack when adjacent.
+/// - Exiting requires an empty inventory (or the treasure). On levels with a
+///   boss or treasure, the treasure must be carried out before anyone can exit.
+/// - The game is won when all players have exited.
+///
+/// Actions are applied for player 1 then player 2, then enemies move and the tick advances.
+/// An invalid action leaves the state untouched and is reported through the `ActResult`.
+#[derive(Debug, Clone)]
+pub struct Simulator {
+    config: SimConfig,
+    level: i32,
+    width: i32,
+    height: i32,
+    terrain: Vec<Tile>,
+    items: HashMap<Position, Tile>,
+    enemies: Vec<SimEnemy>,
+    players: Vec<SimPlayer>,
+    tick: i32,
+    status: GameStatus,
+    seen: HashSet<Position>,
+    last_progress_tick: i32,
+    treasure_required: bool,
+    treasure_delivered: bool,
+}
+
+impl Simulator {
+    pub fn new(level: &Level, config: SimConfig) -> Self {
+        let mut terrain = vec![Tile::Empty; (level.width * level.height) as usize];
+        let mut items = HashMap::new();
+        let mut enemies = Vec::new();
+        let mut treasure_required = false;
+
+        for y in 0..level.height {
+            for x in 0..level.width {
+                let pos = Position::new(x, y);
+                let idx = (y * level.width + x) as usize;
+                match level.tile(pos) {
+                    tile @ (Tile::Wall
+                    | Tile::Exit
+                    | Tile::DoorRed
+                    | Tile::DoorGreen
+                    | Tile::DoorBlue
+                    | Tile::PressurePlateRed
+                    | Tile::PressurePlateGreen
+                    | Tile::PressurePlateBlue) => terrain[idx] = tile,
+                    tile @ (Tile::KeyRed
+                    | Tile::KeyGreen
+                    | Tile::KeyBlue
+                    | Tile::Boulder
+                    | Tile::Sword
+                    | Tile::Health
+                    | Tile::Treasure) => {
+                        if tile == Tile::Treasure {
+                            treasure_required = true;
+                        }
+                        items.insert(pos, tile);
+                    }
+                    Tile::Enemy => enemies.push(SimEnemy {
+                        position: pos,
+                        health: config.enemy_health,
+                        damage: config.enemy_damage,
+                        is_boss: false,
+                    }),
+                    Tile::Boss => {
+                        treasure_required = true;
+                        enemies.push(SimEnemy {
+                            position: pos,
+                            health: config.boss_health,
+                            damage: config.boss_damage,
+                            is_boss: true,
+                        });
+                    }
+                    Tile::Empty | Tile::Unknown | Tile::Player => {}
+                }
+            }
+        }
+
+        let players = level
+            .player_starts()
+            .iter()
+            .map(|&position| SimPlayer {
+                position,
+                health: config.player_health,
+                inventory: Inventory::None,
+                has_sword: false,
+                exited: false,
+            })
+            .collect();
+
+        let mut simulator = Self {
+            config,
+            level: level.number,
+            width: level.width,
+            height: level.height,
+            terrain,
+            items,
+            enemies,
+            players,
+            tick: 0,
+            status: GameStatus::Active,
+            seen: HashSet::new(),
+            last_progress_tick: 0,
+            treasure_required,
+            treasure_delivered: false,
+        };
+        simulator.update_seen();
+        simulator
+    }
+
+    pub fn map_width(&self) -> i32 {
+        self.width
+    }
+
+    pub fn map_height(&self) -> i32 {
+        self.height
+    }
+
+    pub fn visibility_range(&self) -> i32 {
+        self.config.visibility_range
+    }
+
+    pub fn level(&self) -> i32 {
+        self.level
+    }
+
+    pub fn tick(&self) -> i32 {
+        self.tick
+    }
+
+    pub fn status(&self) -> GameStatus {
+        self.status
+    }
+
+    pub fn num_players(&self) -> usize {
+        self.players.len()
+    }
+
+    /// Continue counting from the tick a previous level ended on (quest games)
+    pub fn continue_from_tick(&mut self, tick: i32) {
+        self.tick = tick;
+        self.last_progress_tick = tick;
+    }
+
+    /// End an active game as canceled, e.g. when its client stopped acting
+    pub fn cancel(&mut self) {
+        if self.status == GameStatus::Active {
+            self.status = GameStatus::FinishedCanceled;
+        }
+    }
+
+    /// Apply one tick of actions. `action2` is only valid when the level has a second player.
+    pub fn act(&mut self, action: DirectedAction, action2: Option<DirectedAction>) -> ActResult {
+        if self.status != GameStatus::Active {
+            return ActResult::GameFinished;
+        }
+
+        let mut next = self.clone();
+        let mut progress = false;
+
+        let actions = [Some(action), action2];
+        for (player_idx, player_action) in actions.into_iter().enumerate() {
+            let Some(player_action) = player_action else {
+                continue;
+            };
+            if player_action == DirectedAction::None {
+                continue;
+            }
+            let present = next.players.get(player_idx).is_some_and(|p| !p.exited);
+            if !present {
+                return if player_idx == 0 {
+                    ActResult::PlayerNotPresent
+                } else {
+                    ActResult::Player2NotPresent
+                };
+            }
+
+            let result = next.apply_player_action(player_idx, player_action, &mut progress);
+            if result != ActResult::Ok {
+                debug!(
+                    "Player {} action {:?} rejected: {:?}",
+                    player_idx + 1,
+                    player_action,
+                    result
+                );
+                return result;
+            }
+        }
+
+        next.crush_players_in_closed_doors();
+        next.move_enemies();
+        next.tick += 1;
+
+        if next.update_seen() || progress {
+            next.last_progress_tick = next.tick;
+        }
+        next.update_status();
+
+        *self = next;
+        ActResult::Ok
+    }
+
+    /// Build the state message the server would send for the current tick
+    pub fn state(&self) -> State {
+        State {
+            tick: self.tick,
+            level: self.level,
+            status: self.status as i32,
+            player_state: self.player_state(0),
+            player2_state: self.player_state(1),
+        }
+    }
+
+    fn player_state(&self, player_idx: usize) -> Option<swoq_interface::PlayerState> {
+        let player = self.players.get(player_idx)?;
+        if player.exited {
+            return None;
+        }
+        Some(swoq_interface::PlayerState {
+            position: Some(swoq_interface::Position {
+                x: player.position.x,
+                y: player.position.y,
+            }),
+            surroundings: self.surroundings(player.position),
+            inventory: Some(player.inventory as i32),
+            health: Some(player.health),
+            has_sword: Some(player.has_sword),
+        })
+    }
+
+    /// Tiles around `center` in row-major order, `Unknown` where out of map or out of sight
+    fn surroundings(&self, center: Position) -> Vec<i32> {
+        let range = self.config.visibility_range;
+        let mut tiles = Vec::with_capacity(((range * 2 + 1) * (range * 2 + 1)) as usize);
+        for dy in -range..=range {
+            for dx in -range..=range {
+                let pos = Position::new(center.x + dx, center.y + dy);
+                let tile = if self.is_visible_from(center, pos) {
+                    self.visible_tile(pos)
+                } else {
+                    Tile::Unknown
+                };
+                tiles.push(tile as i32);
+            }
+        }
+        tiles
+    }
+
+    /// Full map with players, for debugging and tests
+    pub fn render(&self) -> String {
+        let mut output = String::new();
+        for y in 0..self.height {
+            for x in 0..self.width {
+                let pos = Position::new(x, y);
+                match self.player_at(pos) {
+                    Some(0) => output.push('1'),
+                    Some(_) => output.push('2'),
+                    None => output.push(tile_to_glyph(Some(self.visible_tile(pos)))),
+                }
+            }
+            output.push('\n');
+        }
+        output
+    }
+
+    // ========================================================================
+    // Map queries
+    // ========================================================================
+
+    fn contains(&self, pos: Position) -> bool {
+        pos.x >= 0 && pos.x < self.width && pos.y >= 0 && pos.y < self.height
+    }
+
+    fn terrain(&self, pos: Position) -> Tile {
+        if self.contains(pos) {
+            self.terrain[(pos.y * self.width + pos.x) as usize]
+        } else {
+            Tile::Wall
+        }
+    }
+
+    fn set_terrain(&mut self, pos: Position, tile: Tile) {
+        let idx = (pos.y * self.width + pos.x) as usize;
+        self.terrain[idx] = tile;
+    }
+
+    fn player_at(&self, pos: Position) -> Option<usize> {
+        self.players
+            .iter()
+            .position(|p| !p.exited && p.position == pos)
+    }
+
+    fn enemy_at(&self, pos: Position) -> Option<usize> {
+        self.enemies.iter().position(|e| e.position == pos)
+    }
+
+    fn door_color(tile: Tile) -> Option<Color> {
+        match tile {
+            Tile::DoorRed => Some(Color::Red),
+            Tile::DoorGreen => Some(Color::Green),
+            Tile::DoorBlue => Some(Color::Blue),
+            _ => None,
+        }
+    }
+
+    fn plate_color(tile: Tile) -> Option<Color> {
+        match tile {
+            Tile::PressurePlateRed => Some(Color::Red),
+            Tile::PressurePlateGreen => Some(Color::Green),
+            Tile::PressurePlateBlue => Some(Color::Blue),
+            _ => None,
+        }
+    }
+
+    fn key_color(inventory: Inventory) -> Option<Color> {
+        match inventory {
+            Inventory::KeyRed => Some(Color::Red),
+            Inventory::KeyGreen => Some(Color::Green),
+            Inventory::KeyBlue => Some(Color::Blue),
+            _ => None,
+        }
+    }
+
+    /// A plate is pressed by a player or a boulder standing on it
+    fn is_plate_pressed(&self, color: Color) -> bool {
+        let pressed_by_player = self
+            .players
+            .iter()
+            .filter(|p| !p.exited)
+            .any(|p| Self::plate_color(self.terrain(p.position)) == Some(color));
+        let pressed_by_boulder = self.items.iter().any(|(pos, tile)| {
+            *tile == Tile::Boulder && Self::plate_color(self.terrain(*pos)) == Some(color)
+        });
+        pressed_by_player || pressed_by_boulder
+    }
+
+    fn is_closed_door(&self, pos: Position) -> bool {
+        Self::door_color(self.terrain(pos)).is_some_and(|color| !self.is_plate_pressed(color))
+    }
+
+    /// Tile as a player would see it
+    fn visible_tile(&self, pos: Position) -> Tile {
+        if !self.contains(pos) {
+            return Tile::Unknown;
+        }
+        if self.player_at(pos).is_some() {
+            return Tile::Player;
+        }
+        if let Some(idx) = self.enemy_at(pos) {
+            return if self.enemies[idx].is_boss {
+                Tile::Boss
+            } else {
+                Tile::Enemy
+            };
+        }
+        if let Some(&item) = self.items.get(&pos) {
+            return item;
+        }
+        let terrain = self.terrain(pos);
+        if Self::door_color(terrain).is_some() && !self.is_closed_door(pos) {
+            Tile::Empty
+        } else {
+            terrain
+        }
+    }
+
+    fn is_opaque(&self, pos: Position) -> bool {
+        self.terrain(pos) == Tile::Wall || self.is_closed_door(pos)
+    }
+
+    /// Line of sight within the visibility window: no wall or closed door strictly between
+    fn is_visible_from(&self, from: Position, to: Position) -> bool {
+        if !self.contains(to) {
+            return false;
+        }
+        let range = self.config.visibility_range;
+        if (to.x - from.x).abs() > range || (to.y - from.y).abs() > range {
+            return false;
+        }
+
+        // Bresenham line walk
+        let dx = (to.x - from.x).abs();
+        let dy = -(to.y - from.y).abs();
+        let sx = if from.x < to.x { 1 } else { -1 };
+        let sy = if from.y < to.y { 1 } else { -1 };
+        let mut err = dx + dy;
+        let mut current = from;
+        while current != to {
+            if current != from && self.is_opaque(current) {
+                return false;
+            }
+            let e2 = 2 * err;
+            if e2 >= dy {
+                err += dy;
+                current.x += sx;
+            }
+            if e2 <= dx {
+                err += dx;
+                current.y += sy;
+            }
+        }
+        true
+    }
+
+    /// Add every currently visible tile to the seen set, returns true if anything was new
+    fn update_seen(&mut self) -> bool {
+        let range = self.config.visibility_range;
+        let mut newly_seen = Vec::new();
+        for player in self.players.iter().filter(|p| !p.exited) {
+            for dy in -range..=range {
+                for dx in -range..=range {
+                    let pos = Position::new(player.position.x + dx, player.position.y + dy);
+                    if !self.seen.contains(&pos) && self.is_visible_from(player.position, pos) {
+                        newly_seen.push(pos);
+                    }
+                }
+            }
+        }
+        let mut any_new = false;
+        for pos in newly_seen {
+            any_new |= self.seen.insert(pos);
+        }
+        any_new
+    }
+
+    // ========================================================================
+    // Player actions
+    // ========================================================================
+
+    fn apply_player_action(
+        &mut self,
+        player_idx: usize,
+        action: DirectedAction,
+        progress: &mut bool,
+    ) -> ActResult {
+        let position = self.players[player_idx].position;
+        let (target, is_use) = match action {
+            DirectedAction::None => return ActResult::Ok,
+            DirectedAction::MoveNorth => (Position::new(position.x, position.y - 1), false),
+            DirectedAction::MoveEast => (Position::new(position.x + 1, position.y), false),
+            DirectedAction::MoveSouth => (Position::new(position.x, position.y + 1), false),
+            DirectedAction::MoveWest => (Position::new(position.x - 1, position.y), false),
+            DirectedAction::UseNorth => (Position::new(position.x, position.y - 1), true),
+            DirectedAction::UseEast => (Position::new(position.x + 1, position.y), true),
+            DirectedAction::UseSouth => (Position::new(position.x, position.y + 1), true),
+            DirectedAction::UseWest => (Position::new(position.x - 1, position.y), true),
+        };
+
+        if is_use {
+            self.apply_use(player_idx, target, progress)
+        } else {
+            self.apply_move(player_idx, target, progress)
+        }
+    }
+
+    fn apply_move(
+        &mut self,
+        player_idx: usize,
+        target: Position,
+        progress: &mut bool,
+    ) -> ActResult {
+        if !self.contains(target)
+            || self.player_at(target).is_some()
+            || self.enemy_at(target).is_some()
+            || self.terrain(target) == Tile::Wall
+            || self.is_closed_door(target)
+        {
+            return ActResult::MoveNotAllowed;
+        }
+
+        let inventory = self.players[player_idx].inventory;
+        match self.items.get(&target).copied() {
+            Some(Tile::Boulder) => return ActResult::MoveNotAllowed,
+            Some(item @ (Tile::KeyRed | Tile::KeyGreen | Tile::KeyBlue | Tile::Treasure)) => {
+                if inventory != Inventory::None {
+                    return ActResult::InventoryFull;
+                }
+                self.items.remove(&target);
+                self.players[player_idx].inventory = match item {
+                    Tile::KeyRed => Inventory::KeyRed,
+                    Tile::KeyGreen => Inventory::KeyGreen,
+                    Tile::KeyBlue => Inventory::KeyBlue,
+                    _ => Inventory::Treasure,
+                };
+                *progress = true;
+            }
+            Some(Tile::Sword) if !self.players[player_idx].has_sword => {
+                self.items.remove(&target);
+                self.players[player_idx].has_sword = true;
+                *progress = true;
+            }
+            Some(Tile::Health) => {
+                self.items.remove(&target);
+                self.players[player_idx].health += self.config.health_pickup;
+                *progress = true;
+            }
+            _ => {}
+        }
+
+        if self.terrain(target) == Tile::Exit {
+            let carrying_treasure = self.players[player_idx].inventory == Inventory::Treasure;
+            if !matches!(self.players[player_idx].inventory, Inventory::None | Inventory::Treasure)
+                || (self.treasure_required && !self.treasure_delivered && !carrying_treasure)
+            {
+                return ActResult::MoveNotAllowed;
+            }
+            if carrying_treasure {
+                self.treasure_delivered = true;
+                self.players[player_idx].inventory = Inventory::None;
+            }
+            self.players[player_idx].exited = true;
+            *progress = true;
+        }
+
+        self.players[player_idx].position = target;
+        ActResult::Ok
+    }
+
+    fn apply_use(&mut self, player_idx: usize, target: Position, progress: &mut bool) -> ActResult {
+        if !self.contains(target) || self.player_at(target).is_some() {
+            return ActResult::UseNotAllowed;
+        }
+
+        if let Some(enemy_idx) = self.enemy_at(target) {
+            if !self.players[player_idx].has_sword {
+                return ActResult::NoSword;
+            }
+            self.enemies[enemy_idx].health -= self.config.sword_damage;
+            if self.enemies[enemy_idx].health <= 0 {
+                let enemy = self.enemies.remove(enemy_idx);
+                debug!("Enemy at {:?} killed", enemy.position);
+                if enemy.is_boss {
+                    self.items.insert(enemy.position, Tile::Treasure);
+                }
+            }
+            *progress = true;
+            return ActResult::Ok;
+        }
+
+        let inventory = self.players[player_idx].inventory;
+
+        if self.items.get(&target) == Some(&Tile::Boulder) {
+            if inventory != Inventory::None {
+                return ActResult::InventoryFull;
+            }
+            self.items.remove(&target);
+            self.players[player_idx].inventory = Inventory::Boulder;
+            *progress = true;
+            return ActResult::Ok;
+        }
+
+        let terrain = self.terrain(target);
+        if let Some(door_color) = Self::door_color(terrain) {
+            if Self::key_color(inventory) != Some(door_color) {
+                return ActResult::UseNotAllowed;
+            }
+            self.set_terrain(target, Tile::Empty);
+            self.players[player_idx].inventory = Inventory::None;
+            *progress = true;
+            return ActResult::Ok;
+        }
+
+        let is_free_floor = (terrain == Tile::Empty || Self::plate_color(terrain).is_some())
+            && !self.items.contains_key(&target);
+        match inventory {
+            Inventory::Boulder if is_free_floor => {
+                self.items.insert(target, Tile::Boulder);
+                self.players[player_idx].inventory = Inventory::None;
+                *progress = true;
+                ActResult::Ok
+            }
+            Inventory::None => ActResult::InventoryEmpty,
+            _ => ActResult::UseNotAllowed,
+        }
+    }
+
+    /// Players standing in a door that is now closed are crushed
+    fn crush_players_in_closed_doors(&mut self) {
+        let crushed: Vec<usize> = (0..self.players.len())
+            .filter(|&idx| {
+                let player = &self.players[idx];
+                !player.exited && self.is_closed_door(player.position)
+            })
+            .collect();
+        for idx in crushed {
+            debug!("Player {} crushed by closing door", idx + 1);
+            self.players[idx].health = 0;
+        }
+    }
+
+    // ========================================================================
+    // Enemies
+    // ========================================================================
+
+    fn is_enemy_walkable(&self, pos: Position) -> bool {
+        let terrain = self.terrain(pos);
+        self.contains(pos)
+            && (terrain == Tile::Empty
+                || Self::plate_color(terrain).is_some()
+                || (Self::door_color(terrain).is_some() && !self.is_closed_door(pos)))
+            && !self.items.contains_key(&pos)
+            && self.player_at(pos).is_none()
+            && self.enemy_at(pos).is_none()
+    }
+
+    fn move_enemies(&mut self) {
+        for enemy_idx in 0..self.enemies.len() {
+            let enemy_pos = self.enemies[enemy_idx].position;
+
+            // Attack the first adjacent player
+            if let Some(player_idx) = (0..self.players.len()).find(|&idx| {
+                let player = &self.players[idx];
+                !player.exited && player.health > 0 && player.position.is_adjacent(&enemy_pos)
+            }) {
+                self.players[player_idx].health -= self.enemies[enemy_idx].damage;
+                debug!(
+                    "Enemy at {:?} hits player {} (health {})",
+                    enemy_pos,
+                    player_idx + 1,
+                    self.players[player_idx].health
+                );
+                continue;
+            }
+
+            // Chase the closest player in sight
+            let target = self
+                .players
+                .iter()
+                .filter(|p| !p.exited && self.is_visible_from(enemy_pos, p.position))
+                .min_by_key(|p| p.position.distance(&enemy_pos))
+                .map(|p| p.position);
+
+            if let Some(target) = target
+                && let Some(step) = self.first_step_towards(enemy_pos, target)
+            {
+                self.enemies[enemy_idx].position = step;
+            }
+        }
+    }
+
+    /// First step of a shortest path from `from` to any tile adjacent to `target`
+    fn first_step_towards(&self, from: Position, target: Position) -> Option<Position> {
+        let mut came_from: HashMap<Position, Position> = HashMap::new();
+        let mut queue = VecDeque::new();
+        queue.push_back(from);
+        came_from.insert(from, from);
+
+        while let Some(current) = queue.pop_front() {
+            if current.is_adjacent(&target) {
+                let mut step = current;
+                while came_from[&step] != from {
+                    step = came_from[&step];
+                }
+                return if step == from { None } else { Some(step) };
+            }
+            for neighbor in current.neighbors() {
+                if !came_from.contains_key(&neighbor) && self.is_enemy_walkable(neighbor) {
+                    came_from.insert(neighbor, current);
+                    queue.push_back(neighbor);
+                }
+            }
+        }
+        None
+    }
+
+    // ========================================================================
+    // Game status
+    // ========================================================================
+
+    fn update_status(&mut self) {
+        if self.players.first().is_some_and(|p| p.health <= 0) {
+            self.status = GameStatus::FinishedPlayerDied;
+        } else if self.players.get(1).is_some_and(|p| p.health <= 0) {
+            self.status = GameStatus::FinishedPlayer2Died;
+        } else if self.players.iter().all(|p| p.exited) {
+            self.status = GameStatus::FinishedSuccess;
+        } else if self.tick >= self.config.max_ticks {
+            self.status = GameStatus::FinishedTimeout;
+        } else if self.tick - self.last_progress_tick >= self.config.no_progress_ticks {
+            self.status = GameStatus::FinishedNoProgress;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::state::WorldState;
+
+    fn simulator(text: &str, level: i32) -> Simulator {
+        Simulator::new(&Level::parse(level, text).unwrap(), SimConfig::default())
+    }
+
+    #[test]
+    fn test_key_and_door() {
+        let mut sim = simulator(
+            "\
+#######
+#1r#  #
+#  R E#
+#######",
+            2,
+        );
+        assert_eq!(sim.act(DirectedAction::MoveEast, None), ActResult::Ok);
+        assert_eq!(sim.state().player_state.unwrap().inventory, Some(Inventory::KeyRed as i32));
+        assert_eq!(sim.act(DirectedAction::MoveSouth, None), ActResult::Ok);
+        assert_eq!(sim.act(DirectedAction::MoveEast, None), ActResult::MoveNotAllowed);
+        assert_eq!(sim.act(DirectedAction::UseEast, None), ActResult::Ok);
+        assert_eq!(sim.act(DirectedAction::MoveEast, None), ActResult::Ok);
+        assert_eq!(sim.act(DirectedAction::MoveEast, None), ActResult::Ok);
+        assert_eq!(sim.act(DirectedAction::MoveEast, None), ActResult::Ok);
+        assert_eq!(sim.status(), GameStatus::FinishedSuccess);
+        assert_eq!(sim.act(DirectedAction::MoveEast, None), ActResult::GameFinished);
+    }
+
+    #[test]
+    fn test_boulder_on_plate_opens_door() {
+        let mut sim = simulator(
+            "\
+########
+#1o▫ R #
+#      E
+########",
+            7,
+        );
+        assert_eq!(sim.act(DirectedAction::UseEast, None), ActResult::Ok);
+        assert_eq!(sim.state().player_state.unwrap().inventory, Some(Inventory::Boulder as i32));
+        assert_eq!(sim.act(DirectedAction::MoveEast, None), ActResult::Ok);
+        assert!(sim.is_closed_door(Position::new(5, 1)));
+        assert_eq!(sim.act(DirectedAction::UseEast, None), ActResult::Ok);
+        assert!(!sim.is_closed_door(Position::new(5, 1)));
+        assert_eq!(sim.visible_tile(Position::new(3, 1)), Tile::Boulder);
+        assert_eq!(sim.visible_tile(Position::new(5, 1)), Tile::Empty);
+    }
+
+    #[test]
+    fn test_enemy_chases_and_sword_kills() {
+        let mut sim = simulator(
+            "\
+#######
+#1s  e#
+#######",
+            10,
+        );
+        assert_eq!(sim.act(DirectedAction::UseEast, None), ActResult::InventoryEmpty);
+        assert_eq!(sim.act(DirectedAction::MoveEast, None), ActResult::Ok);
+        assert!(sim.players[0].has_sword);
+        // Enemy steps towards the player until adjacent
+        assert_eq!(sim.enemies[0].position, Position::new(4, 1));
+        assert_eq!(sim.act(DirectedAction::None, None), ActResult::Ok);
+        assert_eq!(sim.enemies[0].position, Position::new(3, 1));
+        for _ in 0..3 {
+            assert_eq!(sim.act(DirectedAction::UseEast, None), ActResult::Ok);
+        }
+        assert!(sim.enemies.is_empty());
+        assert!(sim.players[0].health < 5);
+    }
+
+    #[test]
+    fn test_surroundings_feed_world_state() {
+        let sim = simulator(
+            "\
+#########
+#1  #  E#
+#########",
+            1,
+        );
+        let state = sim.state();
+        let size = (sim.visibility_range() * 2 + 1) as usize;
+        assert_eq!(state.player_state.as_ref().unwrap().surroundings.len(), size * size);
+
+        let mut world = WorldState::new(sim.map_width(), sim.map_height(), sim.visibility_range());
+        world.update(&state);
+        assert_eq!(world.players[0].position, Position::new(1, 1));
+        assert_eq!(world.map.get(&Position::new(3, 1)), Some(&Tile::Empty));
+        assert_eq!(world.map.get(&Position::new(4, 1)), Some(&Tile::Wall));
+        // Behind the wall is out of sight
+        assert_eq!(world.map.get(&Position::new(5, 1)), Some(&Tile::Unknown));
+        assert_eq!(world.exit_position, None);
+    }
+
+    #[test]
+    fn test_two_players_and_crushing_door() {
+        let mut sim = simulator(
+            "\
+########
+#1▫ R  #
+#2     E
+########",
+            12,
+        );
+        assert_eq!(sim.num_players(), 2);
+        assert!(sim.state().player2_state.is_some());
+        // Player 1 steps on the plate, player 2 walks below the door
+        assert_eq!(
+            sim.act(DirectedAction::MoveEast, Some(DirectedAction::MoveEast)),
+            ActResult::Ok
+        );
+        assert!(!sim.is_closed_door(Position::new(4, 1)));
+        assert_eq!(sim.act(DirectedAction::None, Some(DirectedAction::MoveEast)), ActResult::Ok);
+        assert_eq!(sim.act(DirectedAction::None, Some(DirectedAction::MoveEast)), ActResult::Ok);
+        assert_eq!(sim.act(DirectedAction::None, Some(DirectedAction::MoveNorth)), ActResult::Ok);
+        // Player 1 leaves the plate while player 2 stands in the door
+        assert_eq!(sim.act(DirectedAction::MoveWest, None), ActResult::Ok);
+        assert_eq!(sim.status(), GameStatus::FinishedPlayer2Died);
+    }
+
+    #[test]
+    fn test_boss_drops_treasure_required_for_exit() {
+        let mut sim = simulator(
+            "\
+#######
+#1  X #
+#E    #
+#######",
+            22,
+        );
+        sim.players[0].has_sword = true;
+        sim.players[0].health = 100;
+        // Exit is locked until the treasure is carried out
+        assert_eq!(sim.act(DirectedAction::MoveSouth, None), ActResult::MoveNotAllowed);
+
+        for _ in 0..20 {
+            let Some(boss) = sim.enemies.first().map(|e| e.position) else {
+                break;
+            };
+            let player = sim.players[0].position;
+            let action = if boss.is_adjacent(&player) {
+                crate::infra::use_direction(player, boss)
+            } else {
+                DirectedAction::None
+            };
+            assert_eq!(sim.act(action, None), ActResult::Ok);
+        }
+        assert!(sim.enemies.is_empty());
+        let treasure = *sim
+            .items
+            .iter()
+            .find(|(_, t)| **t == Tile::Treasure)
+            .unwrap()
+            .0;
+        assert!(treasure.is_adjacent(&sim.players[0].position));
+    }
+
+    #[test]
+    fn test_invalid_player2_action() {
+        let mut sim = simulator("#1 E#", 1);
+        assert_eq!(
+            sim.act(DirectedAction::MoveEast, Some(DirectedAction::MoveEast)),
+            ActResult::Player2NotPresent
+        );
+        assert_eq!(sim.tick(), 0);
+    }
+}
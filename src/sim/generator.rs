@@ -0,0 +1,724 @@
+//! Seeded procedural level generator
+//!
+//! Carves a maze of corridors, then closes the path from the players to the exit
+//! with gates: doors opened by a key, doors held open by a boulder on a plate and
+//! coop gates, where a plate on either side lets the players take turns. Everything
+//! a gate needs lies in a dead end before it, so picking it up never blocks the way.
+//! Enemies, swords, health and the boss room follow the level tiers of the game.
+//! Every generated level passes `is_solvable` with the default simulator rules.
+
+use rand::rngs::StdRng;
+use rand::seq::SliceRandom;
+use rand::{Rng, SeedableRng};
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::error::Error;
+use std::fmt;
+
+use crate::infra::{Color, Position};
+use crate::sim::{Level, SimConfig};
+use crate::swoq_interface::Tile;
+
+/// Layouts tried before giving up on a configuration
+const MAX_ATTEMPTS: usize = 100;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum GeneratorError {
+    /// Maps need room for at least a 2 by 2 maze
+    TooSmall { width: i32, height: i32 },
+    /// There are three colors, one per gate
+    TooManyGates { gates: usize },
+    /// No layout fitted every gate and item
+    NoLayout { attempts: usize },
+}
+
+impl fmt::Display for GeneratorError {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            GeneratorError::TooSmall { width, height } => {
+                write!(formatter, "Map of {}x{} is too small for a maze", width, height)
+            }
+            GeneratorError::TooManyGates { gates } => {
+                write!(formatter, "{} gates do not fit in three colors", gates)
+            }
+            GeneratorError::NoLayout { attempts } => {
+                write!(formatter, "No layout fits the configuration after {} attempts", attempts)
+            }
+        }
+    }
+}
+
+impl Error for GeneratorError {}
+
+/// What a generated level contains
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GeneratorConfig {
+    /// Map size in tiles, walls included
+    pub width: i32,
+    pub height: i32,
+    pub two_players: bool,
+    /// Doors opened with a key of their color
+    pub key_doors: usize,
+    /// Doors held open by a boulder on a plate of their color
+    pub plate_doors: usize,
+    /// Doors with a plate on both sides, passed by two players taking turns
+    pub coop_gates: usize,
+    /// Boulders besides the ones the plate doors need
+    pub boulders: usize,
+    pub enemies: usize,
+    pub swords: usize,
+    pub health: usize,
+    /// Boss behind the last gate, its treasure must be carried out
+    pub boss: bool,
+}
+
+impl GeneratorConfig {
+    /// Mechanics arrive with the level tiers of the game: keys at 2, boulders at 6,
+    /// plates at 7, enemies at 8, swords at 10, two players at 12 and the boss at 22.
+    /// Boss levels have enough health to win the fight with the default `SimConfig`.
+    /// Maps grow with the level number.
+    pub fn for_level(number: i32) -> Self {
+        let number = number.max(0);
+        let tier = number.min(24);
+        let two_players = number >= 12;
+        let boss = number >= 22;
+        Self {
+            width: 15 + 2 * (tier / 4),
+            height: 9 + 2 * (tier / 6),
+            two_players,
+            key_doors: match number {
+                0..2 => 0,
+                2..4 => 1,
+                4..7 => 2,
+                7..12 => 1,
+                _ => 0,
+            },
+            plate_doors: usize::from(number >= 7),
+            coop_gates: usize::from(two_players),
+            boulders: usize::from(number >= 6),
+            enemies: match number {
+                0..8 => 0,
+                8..16 => 1,
+                _ => 2,
+            } + usize::from(boss),
+            swords: usize::from(number >= 10) + usize::from(two_players && number >= 16),
+            health: usize::from(number >= 8) + 3 * usize::from(boss),
+            boss,
+        }
+    }
+
+    fn gates(&self) -> usize {
+        self.key_doors + self.plate_doors + self.coop_gates
+    }
+}
+
+/// Generate the level of the given number with its tier's configuration
+pub fn generate_level(number: i32, seed: u64) -> Result<Level, GeneratorError> {
+    generate(number, &GeneratorConfig::for_level(number), seed)
+}
+
+/// Generate a level solvable with the default `SimConfig`, the same seed and
+/// configuration give the same level
+pub fn generate(number: i32, config: &GeneratorConfig, seed: u64) -> Result<Level, GeneratorError> {
+    if config.width < 5 || config.height < 5 {
+        return Err(GeneratorError::TooSmall {
+            width: config.width,
+            height: config.height,
+        });
+    }
+    if config.gates() > 3 {
+        return Err(GeneratorError::TooManyGates {
+            gates: config.gates(),
+        });
+    }
+
+    let mut rng = StdRng::seed_from_u64(seed);
+    for _ in 0..MAX_ATTEMPTS {
+        if let Some(level) = Layout::carve(number, config, &mut rng).furnish(config, &mut rng)
+            && is_solvable(&level, &SimConfig::default())
+        {
+            return Ok(level);
+        }
+    }
+    Err(GeneratorError::NoLayout {
+        attempts: MAX_ATTEMPTS,
+    })
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum GateKind {
+    Key,
+    Plate,
+    Coop,
+}
+
+/// A maze being furnished
+struct Layout {
+    level: Level,
+    start: Position,
+    /// Tiles from the start to the exit, both included
+    main_path: Vec<Position>,
+    /// Maze tree parent of every open tile, towards the start
+    parents: HashMap<Position, Position>,
+}
+
+impl Layout {
+    /// Carve a maze without loops with a randomized depth-first search over the
+    /// odd tiles, and put the exit at the dead end farthest from the start
+    fn carve(number: i32, config: &GeneratorConfig, rng: &mut StdRng) -> Self {
+        let mut level = Level::new(number, config.width, config.height);
+        for y in 0..config.height {
+            for x in 0..config.width {
+                level.set_tile(Position::new(x, y), Tile::Wall);
+            }
+        }
+
+        let cells_x = (config.width - 1) / 2;
+        let cells_y = (config.height - 1) / 2;
+        let cell = |cx: i32, cy: i32| Position::new(2 * cx + 1, 2 * cy + 1);
+        let start_cell = (rng.random_range(0..cells_x), rng.random_range(0..cells_y));
+
+        let mut visited = HashSet::from([start_cell]);
+        let mut stack = vec![start_cell];
+        level.set_tile(cell(start_cell.0, start_cell.1), Tile::Empty);
+        while let Some(&(cx, cy)) = stack.last() {
+            let mut next: Vec<(i32, i32)> =
+                [(cx + 1, cy), (cx - 1, cy), (cx, cy + 1), (cx, cy - 1)]
+                    .into_iter()
+                    .filter(|&(nx, ny)| {
+                        (0..cells_x).contains(&nx)
+                            && (0..cells_y).contains(&ny)
+                            && !visited.contains(&(nx, ny))
+                    })
+                    .collect();
+            if next.is_empty() {
+                stack.pop();
+                continue;
+            }
+            next.shuffle(rng);
+            let (nx, ny) = next[0];
+            level.set_tile(Position::new(cx + nx + 1, cy + ny + 1), Tile::Empty);
+            level.set_tile(cell(nx, ny), Tile::Empty);
+            visited.insert((nx, ny));
+            stack.push((nx, ny));
+        }
+
+        let start = cell(start_cell.0, start_cell.1);
+        let parents = tree_parents(&level, start);
+        let exit = parents
+            .keys()
+            .copied()
+            .max_by_key(|&pos| (depth(&parents, pos), pos.y, pos.x))
+            .unwrap_or(start);
+        let mut main_path = vec![exit];
+        while let Some(&parent) = parents.get(main_path.last().unwrap()) {
+            main_path.push(parent);
+        }
+        main_path.reverse();
+        level.set_tile(exit, Tile::Exit);
+
+        Self {
+            level,
+            start,
+            main_path,
+            parents,
+        }
+    }
+
+    /// Place the players, gates and items, or None when the maze has too few dead ends
+    fn furnish(mut self, config: &GeneratorConfig, rng: &mut StdRng) -> Option<Level> {
+        let mut starts = vec![self.start];
+        if config.two_players {
+            let second = self
+                .start
+                .neighbors()
+                .into_iter()
+                .find(|&pos| self.level.tile(pos) == Tile::Empty)?;
+            starts.push(second);
+        }
+
+        // Gates sit in corridors between two tiles of the main path, spread along it
+        let mut kinds = [
+            vec![GateKind::Key; config.key_doors],
+            vec![GateKind::Plate; config.plate_doors],
+            vec![GateKind::Coop; config.coop_gates],
+        ]
+        .concat();
+        kinds.shuffle(rng);
+        let mut colors = [Color::Red, Color::Green, Color::Blue];
+        colors.shuffle(rng);
+
+        let inner = &self.main_path[1..self.main_path.len() - 1];
+        let corridors: Vec<Position> = inner
+            .iter()
+            .copied()
+            .filter(|pos| (pos.x + pos.y) % 2 == 1 && !starts.contains(pos))
+            .collect();
+        if corridors.len() < kinds.len() {
+            return None;
+        }
+        let mut gates = Vec::new();
+        for index in 0..kinds.len() {
+            let slot = corridors.len() * (index + 1) / (kinds.len() + 1);
+            let jitter = rng.random_range(0..=corridors.len() / (2 * (kinds.len() + 1)));
+            let pos = corridors[(slot + jitter).min(corridors.len() - 1)];
+            if gates.contains(&pos) {
+                return None;
+            }
+            gates.push(pos);
+        }
+        gates.sort_by_key(|pos| self.main_path.iter().position(|p| p == pos));
+
+        // Dead ends off the main path by the number of gates between them and the start.
+        // Maze dead ends come first, then nooks: wall tiles next to a single open tile,
+        // no two of them adjacent so carving them never joins two corridors.
+        let region_of = |pos: Position| {
+            let mut region = 0;
+            let mut current = pos;
+            while let Some(&parent) = self.parents.get(&current) {
+                region += usize::from(gates.contains(&parent));
+                current = parent;
+            }
+            region
+        };
+        let open_neighbors = |pos: Position| -> Vec<Position> {
+            pos.neighbors()
+                .into_iter()
+                .filter(|&n| self.level.tile(n) != Tile::Wall)
+                .collect()
+        };
+        let mut maze_ends: Vec<Vec<Position>> = vec![Vec::new(); gates.len() + 1];
+        let mut nooks: Vec<Vec<Position>> = vec![Vec::new(); gates.len() + 1];
+        let mut tiles: Vec<Position> = (1..self.level.height - 1)
+            .flat_map(|y| (1..self.level.width - 1).map(move |x| Position::new(x, y)))
+            .collect();
+        tiles.shuffle(rng);
+        let mut carved: Vec<Position> = Vec::new();
+        for pos in tiles {
+            let open = open_neighbors(pos);
+            if self.level.tile(pos) != Tile::Wall {
+                if open.len() == 1 && !self.main_path.contains(&pos) && !starts.contains(&pos) {
+                    maze_ends[region_of(pos)].push(pos);
+                }
+            } else if let [entrance] = open[..]
+                && !gates.contains(&entrance)
+                && self.level.tile(entrance) != Tile::Exit
+                && !carved.iter().any(|nook| nook.is_adjacent(&pos))
+            {
+                carved.push(pos);
+                nooks[region_of(entrance)].push(pos);
+            }
+        }
+        let mut dead_ends: Vec<Vec<Position>> = nooks
+            .into_iter()
+            .zip(maze_ends)
+            .map(|(nooks, maze_ends)| [nooks, maze_ends].concat())
+            .collect();
+        let mut take = |regions: std::ops::RangeInclusive<usize>, rng: &mut StdRng| {
+            let candidates: Vec<usize> = regions.filter(|&r| !dead_ends[r].is_empty()).collect();
+            let region = candidates.get(rng.random_range(0..candidates.len().max(1)))?;
+            dead_ends[*region].pop()
+        };
+
+        let mut items: Vec<(Position, Tile)> = Vec::new();
+        for (index, (&gate, &kind)) in gates.iter().zip(&kinds).enumerate() {
+            let color = colors[index];
+            let (key, door, plate) = color_tiles(color);
+            items.push((gate, door));
+            match kind {
+                GateKind::Key => items.push((take(0..=index, rng)?, key)),
+                GateKind::Plate => {
+                    items.push((take(0..=index, rng)?, plate));
+                    items.push((take(0..=index, rng)?, Tile::Boulder));
+                }
+                GateKind::Coop => {
+                    items.push((take(0..=index, rng)?, plate));
+                    items.push((take(index + 1..=index + 1, rng)?, plate));
+                }
+            }
+        }
+
+        // Swords lie before the first gate, enemies anywhere, the boss behind the last gate
+        let last = gates.len();
+        for _ in 0..config.swords {
+            items.push((take(0..=0, rng)?, Tile::Sword));
+        }
+        if config.boss {
+            items.push((take(last..=last, rng)?, Tile::Boss));
+        }
+        for _ in 0..config.enemies {
+            items.push((take(0..=last, rng)?, Tile::Enemy));
+        }
+        for _ in 0..config.boulders {
+            items.push((take(0..=last, rng)?, Tile::Boulder));
+        }
+        for _ in 0..config.health {
+            items.push((take(0..=last, rng)?, Tile::Health));
+        }
+
+        for (pos, tile) in items {
+            self.level.set_tile(pos, tile);
+        }
+        self.level.set_player_starts(starts);
+        Some(self.level)
+    }
+}
+
+/// Parent of every open tile on its way to `root` in a maze without loops
+fn tree_parents(level: &Level, root: Position) -> HashMap<Position, Position> {
+    let mut parents = HashMap::new();
+    let mut visited = HashSet::from([root]);
+    let mut queue = VecDeque::from([root]);
+    while let Some(current) = queue.pop_front() {
+        for neighbor in current.neighbors() {
+            if level.tile(neighbor) != Tile::Wall && visited.insert(neighbor) {
+                parents.insert(neighbor, current);
+                queue.push_back(neighbor);
+            }
+        }
+    }
+    parents
+}
+
+fn depth(parents: &HashMap<Position, Position>, mut pos: Position) -> usize {
+    let mut depth = 0;
+    while let Some(&parent) = parents.get(&pos) {
+        depth += 1;
+        pos = parent;
+    }
+    depth
+}
+
+/// Key, door and plate of a color
+fn color_tiles(color: Color) -> (Tile, Tile, Tile) {
+    match color {
+        Color::Red => (Tile::KeyRed, Tile::DoorRed, Tile::PressurePlateRed),
+        Color::Green => (Tile::KeyGreen, Tile::DoorGreen, Tile::PressurePlateGreen),
+        Color::Blue => (Tile::KeyBlue, Tile::DoorBlue, Tile::PressurePlateBlue),
+    }
+}
+
+fn tile_color(tile: Tile) -> Option<Color> {
+    [Color::Red, Color::Green, Color::Blue]
+        .into_iter()
+        .find(|&color| {
+            let (key, door, plate) = color_tiles(color);
+            tile == key || tile == door || tile == plate
+        })
+}
+
+fn is_plate(tile: Tile) -> bool {
+    matches!(tile, Tile::PressurePlateRed | Tile::PressurePlateGreen | Tile::PressurePlateBlue)
+}
+
+/// Whether the players can reach the exit, with the boss's treasure if there is one.
+///
+/// A static check that grows the area the players reach until nothing changes:
+/// keys next to it are picked up and open a door of their color, two players
+/// pass a door leading to another plate of its color, a plate in the area holds
+/// its doors open with a free boulder, and a sword lets the players through
+/// enemies and the boss. The boss must fall before the players run out of health
+/// under `rules`, and the treasure must reach the exit from where it drops.
+/// Enemy movement and fights with other enemies are not simulated.
+pub fn is_solvable(level: &Level, rules: &SimConfig) -> bool {
+    let starts = level.player_starts();
+    let treasures: Vec<Position> = [Tile::Boss, Tile::Treasure]
+        .into_iter()
+        .flat_map(|tile| level.positions_of(tile))
+        .collect();
+    let next_to_exit = |area: &HashSet<Position>| {
+        area.iter()
+            .flat_map(|pos| pos.neighbors())
+            .any(|pos| level.tile(pos) == Tile::Exit)
+    };
+    // Keys picked up and doors opened
+    let mut cleared: HashSet<Position> = HashSet::new();
+    let mut keys: HashMap<Color, usize> = HashMap::new();
+    // Colors whose plate has a boulder on it, the plate holds all their doors open
+    let mut held: HashSet<Color> = HashSet::new();
+    let mut has_sword = false;
+
+    loop {
+        let area = flood(starts, |pos| is_passable(level, pos, &cleared, has_sword));
+        let border: HashSet<Position> = area
+            .iter()
+            .flat_map(|pos| pos.neighbors())
+            .filter(|pos| !area.contains(pos))
+            .collect();
+
+        let carried_out = || {
+            treasures.is_empty()
+                || next_to_exit(&flood(&treasures, |pos| {
+                    is_passable(level, pos, &cleared, has_sword)
+                }))
+        };
+        if treasures.iter().all(|pos| area.contains(pos))
+            && next_to_exit(&area)
+            && wins_boss_fights(level, &area, rules)
+            && carried_out()
+        {
+            return true;
+        }
+
+        let mut changed = false;
+        if !has_sword && area.iter().any(|&pos| level.tile(pos) == Tile::Sword) {
+            has_sword = true;
+            changed = true;
+        }
+        for &pos in &border {
+            let tile = level.tile(pos);
+            if matches!(tile, Tile::KeyRed | Tile::KeyGreen | Tile::KeyBlue) && cleared.insert(pos)
+            {
+                *keys.entry(tile_color(tile).unwrap()).or_default() += 1;
+                changed = true;
+            }
+        }
+
+        let boulders = area
+            .iter()
+            .filter(|&&pos| level.tile(pos) == Tile::Boulder)
+            .count();
+        let mut doors: Vec<Position> = border
+            .iter()
+            .copied()
+            .filter(|&pos| {
+                matches!(level.tile(pos), Tile::DoorRed | Tile::DoorGreen | Tile::DoorBlue)
+            })
+            .collect();
+        doors.sort_by_key(|pos| (pos.y, pos.x));
+        for door in doors {
+            let color = tile_color(level.tile(door)).unwrap();
+            let plates_in = |area: &HashSet<Position>| {
+                area.iter()
+                    .filter(|&&pos| {
+                        is_plate(level.tile(pos)) && tile_color(level.tile(pos)) == Some(color)
+                    })
+                    .count()
+            };
+            let passes_in_turns = || {
+                let mut with_door = cleared.clone();
+                with_door.insert(door);
+                let beyond = flood(starts, |pos| is_passable(level, pos, &with_door, has_sword));
+                plates_in(&beyond) > plates_in(&area)
+            };
+            let key = keys.entry(color).or_default();
+            let opens = if held.contains(&color) {
+                true
+            } else if *key > 0 {
+                *key -= 1;
+                true
+            } else if plates_in(&area) == 0 {
+                false
+            } else if starts.len() > 1 && passes_in_turns() {
+                true
+            } else if boulders > held.len() {
+                held.insert(color);
+                true
+            } else {
+                false
+            };
+            if opens {
+                cleared.insert(door);
+                changed = true;
+            }
+        }
+
+        if !changed {
+            return false;
+        }
+    }
+}
+
+/// Whether the players outlast every boss in the area. The boss hits the player
+/// next to it once per sword hit it needs, every player keeps one health point
+/// and shares the health pickups of the area.
+fn wins_boss_fights(level: &Level, area: &HashSet<Position>, rules: &SimConfig) -> bool {
+    let bosses = level.positions_of(Tile::Boss).len() as i32;
+    if bosses == 0 {
+        return true;
+    }
+    if rules.sword_damage <= 0 {
+        return false;
+    }
+    let hits = (rules.boss_health + rules.sword_damage - 1) / rules.sword_damage;
+    let players = level.player_starts().len() as i32;
+    let pickups = area
+        .iter()
+        .filter(|&&pos| level.tile(pos) == Tile::Health)
+        .count() as i32;
+    let health = players * (rules.player_health - 1) + pickups * rules.health_pickup;
+    health >= bosses * hits * rules.boss_damage
+}
+
+fn is_passable(level: &Level, pos: Position, cleared: &HashSet<Position>, has_sword: bool) -> bool {
+    match level.tile(pos) {
+        Tile::Empty | Tile::Sword | Tile::Health | Tile::Boulder | Tile::Treasure => true,
+        tile if is_plate(tile) => true,
+        Tile::KeyRed | Tile::KeyGreen | Tile::KeyBlue => cleared.contains(&pos),
+        Tile::DoorRed | Tile::DoorGreen | Tile::DoorBlue => cleared.contains(&pos),
+        Tile::Enemy | Tile::Boss => has_sword,
+        _ => false,
+    }
+}
+
+/// Tiles reachable from the starts through passable tiles
+fn flood(starts: &[Position], passable: impl Fn(Position) -> bool) -> HashSet<Position> {
+    let mut area: HashSet<Position> = starts.iter().copied().collect();
+    let mut queue: VecDeque<Position> = starts.iter().copied().collect();
+    while let Some(current) = queue.pop_front() {
+        for neighbor in current.neighbors() {
+            if !area.contains(&neighbor) && passable(neighbor) {
+                area.insert(neighbor);
+                queue.push_back(neighbor);
+            }
+        }
+    }
+    area
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::planners::Planner;
+    use crate::planners::heuristic::HeuristicPlanner;
+    use crate::sim::{SimConfig, Simulator, load_levels};
+    use crate::state::WorldState;
+    use crate::swoq_interface::GameStatus;
+    use std::path::Path;
+
+    fn count(level: &Level, tiles: &[Tile]) -> usize {
+        tiles
+            .iter()
+            .map(|&tile| level.positions_of(tile).len())
+            .sum()
+    }
+
+    #[test]
+    fn test_generated_levels_are_solvable_and_follow_the_tiers() {
+        let rules = SimConfig::default();
+        for number in 0..=24 {
+            for seed in 0..4 {
+                let level = generate_level(number, seed).unwrap();
+                let config = GeneratorConfig::for_level(number);
+                assert!(is_solvable(&level, &rules), "level {} seed {}", number, seed);
+                assert_eq!((level.width, level.height), (config.width, config.height));
+                assert_eq!(level.positions_of(Tile::Exit).len(), 1);
+                assert_eq!(level.player_starts().len(), 1 + usize::from(config.two_players));
+                assert_eq!(
+                    count(&level, &[Tile::DoorRed, Tile::DoorGreen, Tile::DoorBlue]),
+                    config.gates()
+                );
+                assert_eq!(
+                    count(&level, &[Tile::KeyRed, Tile::KeyGreen, Tile::KeyBlue]),
+                    config.key_doors
+                );
+                assert_eq!(level.positions_of(Tile::Boss).len(), usize::from(config.boss));
+                assert_eq!(level.positions_of(Tile::Sword).len(), config.swords);
+            }
+        }
+        let tier = |number| GeneratorConfig::for_level(number);
+        assert_eq!(tier(0).gates(), 0);
+        assert_eq!(tier(2).key_doors, 1);
+        assert_eq!((tier(7).plate_doors, tier(7).boulders), (1, 1));
+        assert!(tier(8).enemies > 0 && tier(10).swords > 0);
+        assert_eq!((tier(12).coop_gates, tier(12).two_players), (1, true));
+        assert!(tier(22).boss && !tier(21).boss);
+    }
+
+    #[test]
+    fn test_same_seed_gives_the_same_level() {
+        let text = generate_level(9, 42).unwrap().to_text();
+        assert_eq!(generate_level(9, 42).unwrap().to_text(), text);
+        assert_ne!(generate_level(9, 43).unwrap().to_text(), text);
+
+        let parsed = Level::parse(9, &text).unwrap();
+        assert_eq!(parsed.to_text(), text);
+        assert!(is_solvable(&parsed, &SimConfig::default()));
+    }
+
+    #[test]
+    fn test_unsolvable_levels_are_rejected() {
+        let rules = SimConfig::default();
+        let solvable = |text: &str| is_solvable(&Level::parse(1, text).unwrap(), &rules);
+        assert!(solvable("#######\n#1 r R E#\n#######"));
+        assert!(!solvable("#######\n#1 R r E#\n#######"));
+        // One boulder holds one plate door, a second player can pass by the plates
+        assert!(solvable("#########\n#1o▫ R R E#\n#########"));
+        assert!(!solvable("#########\n#1o▫R▪G E#\n#########"));
+        assert!(!solvable("######\n#1▫R E#\n######"));
+        assert!(solvable("#######\n#12▫R▫E#\n#######"));
+        assert!(!solvable("######\n#12▫R E#\n######"));
+        // The boss needs a sword
+        assert!(!solvable("######\n#1 X E#\n######"));
+        let levels = load_levels(&Path::new(env!("CARGO_MANIFEST_DIR")).join("levels")).unwrap();
+        assert!(levels.iter().all(|level| is_solvable(level, &rules)));
+    }
+
+    #[test]
+    fn test_boss_must_be_killable_and_its_treasure_carried_out() {
+        let boss_level = Level::parse(22, "#######\n#1s X E#\n#######").unwrap();
+        // Ten sword hits while the boss hits back for 2, five health is not enough
+        assert!(!is_solvable(&boss_level, &SimConfig::default()));
+        let strong_sword = SimConfig {
+            sword_damage: 5,
+            ..Default::default()
+        };
+        assert!(is_solvable(&boss_level, &strong_sword));
+        // Health pickups carry the fight: 4 + 6 * 3 covers the 20 damage
+        let with_health = Level::parse(22, "#############\n#1s++++++X E#\n#############").unwrap();
+        assert!(is_solvable(&with_health, &SimConfig::default()));
+
+        // Player 1 kills the boss in a closed room, the treasure never reaches the exit
+        let walled_in = Level::parse(22, "#########\n#1sX#2 E#\n#########").unwrap();
+        assert!(!is_solvable(&walled_in, &strong_sword));
+        let treasure = Level::parse(22, "#######\n#1 $ E#\n#######").unwrap();
+        assert!(is_solvable(&treasure, &SimConfig::default()));
+    }
+
+    #[test]
+    fn test_heuristic_planner_finishes_generated_key_levels() {
+        for number in 0..=5 {
+            let level = generate_level(number, 7).unwrap();
+            let mut simulator = Simulator::new(&level, SimConfig::default());
+            let mut world = WorldState::new(
+                simulator.map_width(),
+                simulator.map_height(),
+                simulator.visibility_range(),
+            );
+            world.update(&simulator.state());
+            let mut planner = HeuristicPlanner::new();
+            planner.on_level_start(&world);
+            while simulator.status() == GameStatus::Active {
+                let (action, action2) = planner.decide(&mut world);
+                simulator.act(action, action2);
+                world.update(&simulator.state());
+            }
+            assert_eq!(simulator.status(), GameStatus::FinishedSuccess, "level {}", number);
+        }
+    }
+
+    #[test]
+    fn test_configurations_that_do_not_fit() {
+        let config = GeneratorConfig {
+            width: 3,
+            ..GeneratorConfig::for_level(0)
+        };
+        assert!(matches!(generate(0, &config, 1), Err(GeneratorError::TooSmall { .. })));
+
+        let config = GeneratorConfig {
+            key_doors: 4,
+            ..GeneratorConfig::for_level(0)
+        };
+        assert!(matches!(generate(0, &config, 1), Err(GeneratorError::TooManyGates { .. })));
+
+        let config = GeneratorConfig {
+            width: 5,
+            height: 5,
+            enemies: 5,
+            ..GeneratorConfig::for_level(0)
+        };
+        assert_eq!(
+            generate(0, &config, 1).unwrap_err(),
+            GeneratorError::NoLayout { attempts: 100 }
+        );
+    }
+}
@@ -0,0 +1,287 @@
+use std::error::Error;
+use std::fmt;
+use std::fs;
+use std::path::Path;
+
+use crate::infra::{Position, tile_from_glyph, tile_to_glyph};
+use crate::swoq_interface::Tile;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum LevelError {
+    Empty,
+    UnknownGlyph { glyph: char, pos: Position },
+    UnseenTile { pos: Position },
+    MissingPlayer,
+    DuplicatePlayer { player: usize },
+    MissingLevelNumber { file: String },
+    DuplicateLevel { number: i32 },
+}
+
+impl fmt::Display for LevelError {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            LevelError::Empty => write!(formatter, "Level map is empty"),
+            LevelError::UnknownGlyph { glyph, pos } => {
+                write!(formatter, "Unknown glyph {:?} at ({}, {})", glyph, pos.x, pos.y)
+            }
+            LevelError::UnseenTile { pos } => {
+                write!(formatter, "Unseen tile at ({}, {}) in a full level map", pos.x, pos.y)
+            }
+            LevelError::MissingPlayer => write!(formatter, "Level has no player 1 start"),
+            LevelError::DuplicatePlayer { player } => {
+                write!(formatter, "Player {} start appears more than once", player)
+            }
+            LevelError::MissingLevelNumber { file } => {
+                write!(formatter, "Level file name {:?} does not end in a level number", file)
+            }
+            LevelError::DuplicateLevel { number } => {
+                write!(formatter, "Level {} is defined more than once", number)
+            }
+        }
+    }
+}
+
+impl Error for LevelError {}
+
+/// Fully known level layout (no fog of war), as the server sees it.
+/// Players are stored as start positions; every other tile, including
+/// enemies, items and the boss, lives in the tile grid.
+#[derive(Debug, Clone)]
+pub struct Level {
+    pub number: i32,
+    pub width: i32,
+    pub height: i32,
+    tiles: Vec<Tile>,
+    player_starts: Vec<Position>,
+}
+
+impl Level {
+    /// Create an all-empty level with a single player start at (0, 0)
+    pub fn new(number: i32, width: i32, height: i32) -> Self {
+        Self {
+            number,
+            width,
+            height,
+            tiles: vec![Tile::Empty; (width * height) as usize],
+            player_starts: vec![Position::new(0, 0)],
+        }
+    }
+
+    /// Parse a level from its ASCII representation.
+    /// Uses the glyphs of `WorldState::draw_ascii_map`; `1` and `2` mark the player starts.
+    /// Rows shorter than the widest row are padded with walls.
+    pub fn parse(number: i32, text: &str) -> Result<Self, LevelError> {
+        let rows: Vec<Vec<char>> = text
+            .lines()
+            .map(|line| line.chars().collect::<Vec<char>>())
+            .skip_while(|row| row.is_empty())
+            .collect();
+        let rows: Vec<Vec<char>> = {
+            let last = rows.iter().rposition(|row| !row.is_empty());
+            match last {
+                Some(last) => rows.into_iter().take(last + 1).collect(),
+                None => return Err(LevelError::Empty),
+            }
+        };
+
+        let height = rows.len() as i32;
+        let width = rows.iter().map(|row| row.len()).max().unwrap_or(0) as i32;
+        let mut tiles = vec![Tile::Wall; (width * height) as usize];
+        let mut player_starts: Vec<Option<Position>> = vec![None, None];
+
+        for (y, row) in rows.iter().enumerate() {
+            for (x, &glyph) in row.iter().enumerate() {
+                let pos = Position::new(x as i32, y as i32);
+                let tile = match tile_from_glyph(glyph) {
+                    Some(Some(tile)) => tile,
+                    Some(None) => return Err(LevelError::UnseenTile { pos }),
+                    None => return Err(LevelError::UnknownGlyph { glyph, pos }),
+                };
+
+                if tile == Tile::Player {
+                    let player = if glyph == '1' { 0 } else { 1 };
+                    if player_starts[player].is_some() {
+                        return Err(LevelError::DuplicatePlayer { player: player + 1 });
+                    }
+                    player_starts[player] = Some(pos);
+                    tiles[y * width as usize + x] = Tile::Empty;
+                } else {
+                    tiles[y * width as usize + x] = tile;
+                }
+            }
+        }
+
+        let Some(p1) = player_starts[0] else {
+            return Err(LevelError::MissingPlayer);
+        };
+        let mut starts = vec![p1];
+        starts.extend(player_starts[1]);
+
+        Ok(Self {
+            number,
+            width,
+            height,
+            tiles,
+            player_starts: starts,
+        })
+    }
+
+    /// Render the level in the same format accepted by `Level::parse`
+    pub fn to_text(&self) -> String {
+        let mut output = String::new();
+        for y in 0..self.height {
+            for x in 0..self.width {
+                let pos = Position::new(x, y);
+                if let Some(idx) = self.player_starts.iter().position(|p| *p == pos) {
+                    output.push(if idx == 0 { '1' } else { '2' });
+                } else {
+                    output.push(tile_to_glyph(Some(self.tile(pos))));
+                }
+            }
+            output.push('\n');
+        }
+        output
+    }
+
+    pub fn contains(&self, pos: Position) -> bool {
+        pos.x >= 0 && pos.x < self.width && pos.y >= 0 && pos.y < self.height
+    }
+
+    /// Tile at a position; out-of-bounds positions are walls
+    pub fn tile(&self, pos: Position) -> Tile {
+        if self.contains(pos) {
+            self.tiles[(pos.y * self.width + pos.x) as usize]
+        } else {
+            Tile::Wall
+        }
+    }
+
+    pub fn set_tile(&mut self, pos: Position, tile: Tile) {
+        if self.contains(pos) {
+            self.tiles[(pos.y * self.width + pos.x) as usize] = tile;
+        }
+    }
+
+    pub fn player_starts(&self) -> &[Position] {
+        &self.player_starts
+    }
+
+    pub fn set_player_starts(&mut self, starts: Vec<Position>) {
+        self.player_starts = starts;
+    }
+
+    /// All positions holding the given tile
+    pub fn positions_of(&self, tile: Tile) -> Vec<Position> {
+        (0..self.height)
+            .flat_map(|y| (0..self.width).map(move |x| Position::new(x, y)))
+            .filter(|&pos| self.tile(pos) == tile)
+            .collect()
+    }
+}
+
+/// Load a single level file or every `.txt` level file in a directory, sorted by level number.
+/// The level number is taken from the trailing digits of the file name (`level-03.txt` is level 3).
+pub fn load_levels(path: &Path) -> Result<Vec<Level>, Box<dyn Error>> {
+    let files = if path.is_dir() {
+        let mut files = Vec::new();
+        for entry in fs::read_dir(path)? {
+            let file = entry?.path();
+            if file.extension().is_some_and(|ext| ext == "txt") {
+                files.push(file);
+            }
+        }
+        files
+    } else {
+        vec![path.to_path_buf()]
+    };
+
+    let mut levels: Vec<Level> = Vec::new();
+    for file in files {
+        let stem = file
+            .file_stem()
+            .map(|stem| stem.to_string_lossy().into_owned())
+            .unwrap_or_default();
+        let digits = stem.len() - stem.trim_end_matches(|c: char| c.is_ascii_digit()).len();
+        let number: i32 =
+            stem[stem.len() - digits..]
+                .parse()
+                .map_err(|_| LevelError::MissingLevelNumber {
+                    file: file.display().to_string(),
+                })?;
+        if levels.iter().any(|level| level.number == number) {
+            return Err(Box::new(LevelError::DuplicateLevel { number }));
+        }
+
+        let text = fs::read_to_string(&file)?;
+        let level =
+            Level::parse(number, &text).map_err(|e| format!("{}: {}", file.display(), e))?;
+        levels.push(level);
+    }
+
+    levels.sort_by_key(|level| level.number);
+    Ok(levels)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_round_trip() {
+        let text = "\
+█████
+█1 r█
+█ R E
+█████
+";
+        let level = Level::parse(2, text).unwrap();
+        assert_eq!(level.width, 5);
+        assert_eq!(level.height, 4);
+        assert_eq!(level.player_starts(), &[Position::new(1, 1)]);
+        assert_eq!(level.tile(Position::new(3, 1)), Tile::KeyRed);
+        assert_eq!(level.tile(Position::new(2, 2)), Tile::DoorRed);
+        assert_eq!(level.tile(Position::new(1, 1)), Tile::Empty);
+        assert_eq!(level.to_text(), text);
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        assert_eq!(Level::parse(1, "\n\n").unwrap_err(), LevelError::Empty);
+        assert_eq!(Level::parse(1, "# E #").unwrap_err(), LevelError::MissingPlayer);
+        assert!(matches!(Level::parse(1, "#1?#").unwrap_err(), LevelError::UnseenTile { .. }));
+        assert!(matches!(
+            Level::parse(1, "#1~#").unwrap_err(),
+            LevelError::UnknownGlyph { glyph: '~', .. }
+        ));
+    }
+
+    #[test]
+    fn test_load_levels_from_directory() {
+        let dir = std::env::temp_dir().join(format!("robbot-levels-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(dir.join("level-10.txt"), "#1E#\n").unwrap();
+        fs::write(dir.join("level-2.txt"), "#1 E#\n").unwrap();
+        fs::write(dir.join("notes.md"), "not a level").unwrap();
+
+        let levels = load_levels(&dir).unwrap();
+        assert_eq!(levels.iter().map(|l| l.number).collect::<Vec<_>>(), vec![2, 10]);
+        assert_eq!(levels[0].width, 5);
+
+        fs::write(dir.join("extra.txt"), "#1E#\n").unwrap();
+        assert!(load_levels(&dir).is_err());
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_sample_levels_share_map_size() {
+        // Quests only continue across levels of the same size
+        let levels = load_levels(&Path::new(env!("CARGO_MANIFEST_DIR")).join("levels")).unwrap();
+        assert!(!levels.is_empty());
+        assert!(
+            levels
+                .iter()
+                .all(|level| level.width == levels[0].width && level.height == levels[0].height)
+        );
+    }
+}
@@ -0,0 +1,17 @@
+//! Offline SWOQ game simulator
+//!
+//! Implements the server rule set on a fully known `Level` so planners and RL
+//! training can run without a game server. The simulator consumes the same
+//! `DirectedAction`s and produces the same `State` messages (with windowed,
+//! line-of-sight limited surroundings) as the real `GameService`. Levels come from
+//! text files or from the seeded generator.
+
+mod engine;
+mod generator;
+mod level;
+mod server;
+
+pub use engine::{SimConfig, Simulator};
+pub use generator::{GeneratorConfig, GeneratorError, generate, generate_level, is_solvable};
+pub use level::{Level, LevelError, load_levels};
+pub use server::{ServerConfig, SimServer};
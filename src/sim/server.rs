@@ -0,0 +1,373 @@
+use std::collections::{HashMap, HashSet};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use tonic::{Request, Response, Status};
+use tracing::{debug, info};
+
+use crate::sim::{Level, SimConfig, Simulator};
+use crate::swoq_interface::game_service_server::GameService;
+use crate::swoq_interface::{
+    ActRequest, ActResponse, ActResult, DirectedAction, GameStatus, StartRequest, StartResponse,
+    StartResult,
+};
+
+/// Limits and access rules for the local server
+#[derive(Debug, Clone)]
+pub struct ServerConfig {
+    pub sim: SimConfig,
+    /// Users allowed to start games (`None` accepts every non-empty user id)
+    pub allowed_users: Option<HashSet<String>>,
+    /// `Start` answers `QuestQueued` while this many games are active
+    pub max_active_games: usize,
+    /// `Start` answers `NotAllowed` when the user already has this many active games
+    pub max_games_per_user: usize,
+    /// Active games without an action for this long are canceled, the client is assumed gone
+    pub idle_timeout: Duration,
+}
+
+impl Default for ServerConfig {
+    fn default() -> Self {
+        Self {
+            sim: SimConfig::default(),
+            allowed_users: None,
+            max_active_games: 16,
+            max_games_per_user: 4,
+            idle_timeout: Duration::from_secs(60),
+        }
+    }
+}
+
+struct ServerGame {
+    user_id: String,
+    simulator: Simulator,
+    /// Quest games continue with the next level after finishing one
+    quest: bool,
+    last_active: Instant,
+}
+
+/// Local stand-in for the SWOQ `GameService`, backed by the offline simulator.
+///
+/// `Start` with a level plays that single level, `Start` without a level starts a
+/// quest through all loaded levels. A quest only continues to the next level when
+/// it has the same map size, because clients keep the size from the `StartResponse`.
+/// Games are dropped once they end. Games of clients that stopped acting are canceled,
+/// the next `Act` on them gets the canceled state.
+pub struct SimServer {
+    config: ServerConfig,
+    levels: Vec<Level>,
+    games: Mutex<HashMap<String, ServerGame>>,
+}
+
+impl SimServer {
+    pub fn new(levels: Vec<Level>, config: ServerConfig) -> Self {
+        Self {
+            config,
+            levels,
+            games: Mutex::new(HashMap::new()),
+        }
+    }
+
+    fn find_level(&self, number: i32) -> Option<&Level> {
+        self.levels.iter().find(|level| level.number == number)
+    }
+
+    /// Next quest level after `current`, if it fits the map size the client already knows
+    fn next_quest_level(&self, current: &Simulator) -> Option<&Level> {
+        self.levels
+            .iter()
+            .find(|level| level.number > current.level())
+            .filter(|level| {
+                level.width == current.map_width() && level.height == current.map_height()
+            })
+    }
+
+    /// Cancel active games that have been idle too long and drop ended ones nobody came back for
+    fn cancel_idle_games(&self, games: &mut HashMap<String, ServerGame>) {
+        games.retain(|game_id, game| {
+            if game.last_active.elapsed() < self.config.idle_timeout {
+                return true;
+            }
+            if game.simulator.status() != GameStatus::Active {
+                return false;
+            }
+            info!("Canceled game {} after {:?} without actions", game_id, self.config.idle_timeout);
+            game.simulator.cancel();
+            game.last_active = Instant::now();
+            true
+        });
+    }
+
+    fn start_game(&self, request: &StartRequest) -> Result<StartResponse, StartResult> {
+        let user_allowed = self
+            .config
+            .allowed_users
+            .as_ref()
+            .is_none_or(|users| users.contains(&request.user_id));
+        if request.user_id.is_empty() || !user_allowed {
+            return Err(StartResult::UnknownUser);
+        }
+
+        let level = match request.level {
+            Some(number) => self.find_level(number),
+            None => self.levels.first(),
+        }
+        .ok_or(StartResult::InvalidLevel)?;
+
+        let mut games = self.games.lock().map_err(|_| StartResult::InternalError)?;
+        self.cancel_idle_games(&mut games);
+        let active_games: Vec<&ServerGame> = games
+            .values()
+            .filter(|game| game.simulator.status() == GameStatus::Active)
+            .collect();
+        let user_games = active_games
+            .iter()
+            .filter(|game| game.user_id == request.user_id)
+            .count();
+        if user_games >= self.config.max_games_per_user {
+            return Err(StartResult::NotAllowed);
+        }
+        if active_games.len() >= self.config.max_active_games {
+            return Err(StartResult::QuestQueued);
+        }
+
+        let game_id = format!("{:016x}", rand::random::<u64>());
+        let seed = request
+            .seed
+            .unwrap_or_else(|| rand::random_range(0..i32::MAX));
+        let simulator = Simulator::new(level, self.config.sim.clone());
+
+        let response = StartResponse {
+            result: StartResult::Ok as i32,
+            game_id: Some(game_id.clone()),
+            map_width: Some(simulator.map_width()),
+            map_height: Some(simulator.map_height()),
+            visibility_range: Some(simulator.visibility_range()),
+            state: Some(simulator.state()),
+            seed: Some(seed),
+        };
+
+        info!("Started game {} on level {} for {}", game_id, level.number, request.user_name);
+        games.insert(
+            game_id,
+            ServerGame {
+                user_id: request.user_id.clone(),
+                simulator,
+                quest: request.level.is_none(),
+                last_active: Instant::now(),
+            },
+        );
+        Ok(response)
+    }
+
+    fn act_game(&self, request: &ActRequest) -> ActResponse {
+        let Ok(mut games) = self.games.lock() else {
+            return ActResponse {
+                result: ActResult::InternalError as i32,
+                state: None,
+            };
+        };
+        self.cancel_idle_games(&mut games);
+        let Some(game) = games.get_mut(&request.game_id) else {
+            return ActResponse {
+                result: ActResult::UnknownGameId as i32,
+                state: None,
+            };
+        };
+
+        let action = request.action.map(DirectedAction::try_from).transpose();
+        let action2 = request.action2.map(DirectedAction::try_from).transpose();
+        let result = match (action, action2) {
+            (Ok(action), Ok(action2)) => game
+                .simulator
+                .act(action.unwrap_or(DirectedAction::None), action2),
+            _ => ActResult::UnknownAction,
+        };
+
+        if result == ActResult::Ok
+            && game.quest
+            && game.simulator.status() == GameStatus::FinishedSuccess
+            && let Some(next) = self.next_quest_level(&game.simulator)
+        {
+            debug!("Game {} continues with level {}", request.game_id, next.number);
+            let tick = game.simulator.tick();
+            game.simulator = Simulator::new(next, self.config.sim.clone());
+            game.simulator.continue_from_tick(tick);
+        }
+        game.last_active = Instant::now();
+
+        let response = ActResponse {
+            result: result as i32,
+            state: Some(game.simulator.state()),
+        };
+        if game.simulator.status() != GameStatus::Active {
+            debug!("Game {} ended: {:?}", request.game_id, game.simulator.status());
+            games.remove(&request.game_id);
+        }
+        response
+    }
+}
+
+#[tonic::async_trait]
+impl GameService for SimServer {
+    async fn start(
+        &self,
+        request: Request<StartRequest>,
+    ) -> Result<Response<StartResponse>, Status> {
+        let request = request.into_inner();
+        let response = self.start_game(&request).unwrap_or_else(|result| {
+            debug!("Start for {:?} refused: {:?}", request.user_name, result);
+            StartResponse {
+                result: result as i32,
+                ..Default::default()
+            }
+        });
+        Ok(Response::new(response))
+    }
+
+    async fn act(&self, request: Request<ActRequest>) -> Result<Response<ActResponse>, Status> {
+        Ok(Response::new(self.act_game(&request.into_inner())))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn server(config: ServerConfig) -> SimServer {
+        let levels = vec![
+            Level::parse(0, "#####\n#1 E#\n#####").unwrap(),
+            Level::parse(1, "#####\n#E 1#\n#####").unwrap(),
+            Level::parse(2, "######\n#1  E#\n######").unwrap(),
+        ];
+        SimServer::new(levels, config)
+    }
+
+    fn start_request(user_id: &str, level: Option<i32>) -> StartRequest {
+        StartRequest {
+            user_id: user_id.to_string(),
+            user_name: "test".to_string(),
+            level,
+            seed: None,
+        }
+    }
+
+    fn act_request(game_id: &str, action: DirectedAction) -> ActRequest {
+        ActRequest {
+            game_id: game_id.to_string(),
+            action: Some(action as i32),
+            action2: None,
+        }
+    }
+
+    #[test]
+    fn test_start_results() {
+        let mut config = ServerConfig {
+            max_games_per_user: 1,
+            max_active_games: 2,
+            ..Default::default()
+        };
+        config.allowed_users = Some(["alice", "bob", "carol"].map(String::from).into());
+        let server = server(config);
+
+        assert_eq!(
+            server.start_game(&start_request("", None)).unwrap_err(),
+            StartResult::UnknownUser
+        );
+        assert_eq!(
+            server
+                .start_game(&start_request("mallory", None))
+                .unwrap_err(),
+            StartResult::UnknownUser
+        );
+        assert_eq!(
+            server
+                .start_game(&start_request("alice", Some(7)))
+                .unwrap_err(),
+            StartResult::InvalidLevel
+        );
+
+        let response = server.start_game(&start_request("alice", Some(2))).unwrap();
+        assert_eq!(response.map_width, Some(6));
+        assert_eq!(response.state.unwrap().level, 2);
+        assert_eq!(
+            server
+                .start_game(&start_request("alice", None))
+                .unwrap_err(),
+            StartResult::NotAllowed
+        );
+
+        server.start_game(&start_request("bob", None)).unwrap();
+        assert_eq!(
+            server
+                .start_game(&start_request("carol", None))
+                .unwrap_err(),
+            StartResult::QuestQueued
+        );
+    }
+
+    #[test]
+    fn test_act_results_and_quest_progression() {
+        let server = server(ServerConfig::default());
+        let game_id = server
+            .start_game(&start_request("alice", None))
+            .unwrap()
+            .game_id
+            .unwrap();
+
+        let response = server.act_game(&act_request("missing", DirectedAction::None));
+        assert_eq!(response.result, ActResult::UnknownGameId as i32);
+
+        let mut request = act_request(&game_id, DirectedAction::None);
+        request.action = Some(42);
+        assert_eq!(server.act_game(&request).result, ActResult::UnknownAction as i32);
+
+        let response = server.act_game(&act_request(&game_id, DirectedAction::MoveWest));
+        assert_eq!(response.result, ActResult::MoveNotAllowed as i32);
+
+        // Level 0 is finished after two moves, the quest continues on level 1 (same size)
+        server.act_game(&act_request(&game_id, DirectedAction::MoveEast));
+        let response = server.act_game(&act_request(&game_id, DirectedAction::MoveEast));
+        let state = response.state.unwrap();
+        assert_eq!(response.result, ActResult::Ok as i32);
+        assert_eq!(state.level, 1);
+        assert_eq!(state.tick, 2);
+        assert_eq!(state.status, GameStatus::Active as i32);
+
+        // Level 2 has a different size, so finishing level 1 ends the quest
+        server.act_game(&act_request(&game_id, DirectedAction::MoveWest));
+        let response = server.act_game(&act_request(&game_id, DirectedAction::MoveWest));
+        assert_eq!(response.state.unwrap().status, GameStatus::FinishedSuccess as i32);
+
+        // Finished games are dropped
+        let response = server.act_game(&act_request(&game_id, DirectedAction::MoveWest));
+        assert_eq!(response.result, ActResult::UnknownGameId as i32);
+        assert!(server.games.lock().unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_idle_games_are_canceled_and_dropped() {
+        let server = server(ServerConfig {
+            idle_timeout: Duration::ZERO,
+            ..Default::default()
+        });
+        let game_id = server
+            .start_game(&start_request("alice", Some(0)))
+            .unwrap()
+            .game_id
+            .unwrap();
+
+        let response = server.act_game(&act_request(&game_id, DirectedAction::MoveEast));
+        assert_eq!(response.result, ActResult::GameFinished as i32);
+        assert_eq!(response.state.unwrap().status, GameStatus::FinishedCanceled as i32);
+        assert!(server.games.lock().unwrap().is_empty());
+
+        // A canceled game the client never asks about again is dropped as well
+        server.start_game(&start_request("alice", Some(0))).unwrap();
+        server.start_game(&start_request("bob", Some(0))).unwrap();
+        server.act_game(&act_request("missing", DirectedAction::None));
+        let games = server.games.lock().unwrap();
+        assert_eq!(games.len(), 1);
+        assert!(games.values().all(|game| game.user_id == "bob"));
+    }
+}
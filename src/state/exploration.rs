@@ -0,0 +1,490 @@
+//! Frontier exploration by information gain
+//!
+//! The frontier of a player is split into clusters of touching tiles. A cluster is scored
+//! by the unknown tiles that come into view when standing on its best tile, per step of the
+//! real path there. A short detour into a large dark area then beats the nearest dead end,
+//! and a frontier tile on the other side of a wall is no longer mistaken for a close one.
+//! With two players the clusters are split between them once per tick, and the split is
+//! kept on the world state as a `FrontierAllocation`.
+
+use std::cmp::Ordering;
+use std::collections::{HashMap, HashSet, VecDeque};
+
+use crate::infra::{Bounds, Position};
+use crate::state::WorldState;
+use crate::swoq_interface::Tile;
+
+/// Frontier tiles that touch each other (diagonals included)
+#[derive(Debug, Clone, PartialEq)]
+pub struct FrontierCluster {
+    /// Tile to walk to
+    pub target: Position,
+    /// Path length from the player to `target`
+    pub distance: i32,
+    /// Unknown tiles within visibility range of `target`
+    pub gain: i32,
+    /// All reachable tiles of the cluster, `target` first and the others nearest first
+    pub tiles: Vec<Position>,
+}
+
+impl FrontierCluster {
+    /// Expected newly revealed tiles per path step
+    pub fn score(&self) -> f32 {
+        self.gain as f32 / self.distance.max(1) as f32
+    }
+}
+
+/// A cluster only changes owner when another player is this many steps closer to it
+const OWNER_MARGIN: i32 = 3;
+
+/// Frontier clusters of a player, best scoring first.
+/// Frontier tiles the player cannot walk to right now are left out.
+pub fn frontier_clusters(world: &WorldState, player_index: usize) -> Vec<FrontierCluster> {
+    let player = &world.players[player_index];
+    let distances = frontier_distances(world, player.position, &player.unexplored_frontier);
+
+    let mut clusters: Vec<FrontierCluster> = group_tiles(distances.keys().copied().collect())
+        .into_iter()
+        .map(|tiles| cluster(world, tiles, &distances))
+        .collect();
+    clusters.sort_by(compare_clusters);
+    clusters
+}
+
+/// Split the frontier clusters between the active players, best first per player.
+///
+/// A cluster goes to the player with the shortest path to it (Voronoi over path distance),
+/// and a player left without one takes its best cluster from a player that has several,
+/// other than the one that player is heading for.
+/// Every cluster is seen from the player it went to. The split is computed from the
+/// current map, so it rebalances as the map is revealed, but a cluster holding a tile of
+/// a player's share in `world.frontier_allocation` stays with that player unless another
+/// one is `OWNER_MARGIN` steps closer.
+pub fn allocate_frontier(world: &WorldState) -> Vec<Vec<FrontierCluster>> {
+    let distances: Vec<HashMap<Position, i32>> = world
+        .players
+        .iter()
+        .map(|player| {
+            if player.is_active {
+                frontier_distances(world, player.position, &player.unexplored_frontier)
+            } else {
+                HashMap::new()
+            }
+        })
+        .collect();
+    let tiles: HashSet<Position> = distances.iter().flat_map(|d| d.keys().copied()).collect();
+    let previous_owners: HashMap<Position, usize> = world
+        .frontier_allocation
+        .shares
+        .iter()
+        .enumerate()
+        .flat_map(|(owner, share)| {
+            share
+                .iter()
+                .flat_map(move |cluster| cluster.tiles.iter().map(move |&pos| (pos, owner)))
+        })
+        .collect();
+
+    // Each cluster as seen by every player that can reach it, with the nearest as owner
+    let mut candidates: Vec<(usize, Vec<Option<FrontierCluster>>)> = group_tiles(tiles)
+        .into_iter()
+        .map(|group| {
+            let views: Vec<Option<FrontierCluster>> = distances
+                .iter()
+                .map(|distances| {
+                    let reachable: Vec<Position> = group
+                        .iter()
+                        .copied()
+                        .filter(|pos| distances.contains_key(pos))
+                        .collect();
+                    (!reachable.is_empty()).then(|| cluster(world, reachable, distances))
+                })
+                .collect();
+            let (distance, nearest) = views
+                .iter()
+                .enumerate()
+                .filter_map(|(index, view)| view.as_ref().map(|c| (c.distance, index)))
+                .min()
+                .expect("clusters are reachable by some player");
+            let kept = group
+                .iter()
+                .find_map(|pos| previous_owners.get(pos).copied())
+                .filter(|&owner| {
+                    views
+                        .get(owner)
+                        .and_then(Option::as_ref)
+                        .is_some_and(|c| c.distance <= distance + OWNER_MARGIN)
+                });
+            (kept.unwrap_or(nearest), views)
+        })
+        .collect();
+
+    for player_index in 0..world.players.len() {
+        let owned = |candidates: &[(usize, Vec<Option<FrontierCluster>>)], player: usize| {
+            candidates
+                .iter()
+                .filter(|(owner, _)| *owner == player)
+                .count()
+        };
+        if owned(&candidates, player_index) > 0 {
+            continue;
+        }
+        // The donor keeps the cluster it is heading for
+        let heading = |owner: usize| {
+            candidates
+                .iter()
+                .enumerate()
+                .filter(|(_, (o, _))| *o == owner)
+                .filter_map(|(index, (_, views))| views[owner].as_ref().map(|c| (index, c)))
+                .min_by(|a, b| compare_clusters(a.1, b.1))
+                .map(|(index, _)| index)
+        };
+        let best = candidates
+            .iter()
+            .enumerate()
+            .filter(|(index, (owner, _))| {
+                owned(&candidates, *owner) > 1 && heading(*owner) != Some(*index)
+            })
+            .filter_map(|(index, (_, views))| views[player_index].as_ref().map(|c| (index, c)))
+            .min_by(|a, b| compare_clusters(a.1, b.1))
+            .map(|(index, _)| index);
+        if let Some(index) = best {
+            candidates[index].0 = player_index;
+        }
+    }
+
+    let mut shares = vec![Vec::new(); world.players.len()];
+    for (owner, mut views) in candidates {
+        if let Some(cluster) = views[owner].take() {
+            shares[owner].push(cluster);
+        }
+    }
+    for share in &mut shares {
+        share.sort_by(compare_clusters);
+    }
+    shares
+}
+
+/// Frontier split of a tick, `WorldState::update` computes it once after every state
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct FrontierAllocation {
+    /// Share of every player, see `allocate_frontier`
+    pub shares: Vec<Vec<FrontierCluster>>,
+    /// Clusters every player should explore, see `exploration_clusters`
+    pub clusters: Vec<Vec<FrontierCluster>>,
+}
+
+impl FrontierAllocation {
+    pub fn new(world: &WorldState) -> Self {
+        let shares = allocate_frontier(world);
+        let two_players = world
+            .players
+            .iter()
+            .filter(|player| player.is_active)
+            .count()
+            >= 2;
+        let clusters = (0..world.players.len())
+            .map(|player_index| {
+                let clusters = frontier_clusters(world, player_index);
+                if !two_players {
+                    return clusters;
+                }
+                let mut share = shares[player_index].clone();
+                let claimed: HashSet<Position> =
+                    share.iter().flat_map(|c| c.tiles.iter().copied()).collect();
+                share.extend(
+                    clusters
+                        .into_iter()
+                        .filter(|cluster| !cluster.tiles.iter().any(|pos| claimed.contains(pos))),
+                );
+                share
+            })
+            .collect();
+        Self { shares, clusters }
+    }
+
+    /// Player whose share holds the frontier tile `pos`
+    pub fn owner(&self, pos: Position) -> Option<usize> {
+        self.shares
+            .iter()
+            .position(|share| share.iter().any(|cluster| cluster.tiles.contains(&pos)))
+    }
+}
+
+/// Player whose share of the frontier holds the frontier tile `pos`
+pub fn frontier_owner(world: &WorldState, pos: Position) -> Option<usize> {
+    world.frontier_allocation.owner(pos)
+}
+
+/// Frontier clusters a player should explore, best first. With two active players the
+/// player's share of `allocate_frontier` comes first, and the rest of its frontier after
+/// it, so that a player whose share is out of reach still has somewhere to go.
+/// Computed on the last `WorldState::update`.
+pub fn exploration_clusters(world: &WorldState, player_index: usize) -> &[FrontierCluster] {
+    world
+        .frontier_allocation
+        .clusters
+        .get(player_index)
+        .map_or(&[], Vec::as_slice)
+}
+
+/// Frontier tiles of a player in the order to try them as exploration targets:
+/// the tiles of the best cluster of `exploration_clusters` first, starting with its target.
+pub fn ranked_frontier(world: &WorldState, player_index: usize) -> Vec<Position> {
+    exploration_clusters(world, player_index)
+        .iter()
+        .flat_map(|cluster| cluster.tiles.iter().copied())
+        .collect()
+}
+
+/// Path lengths from `start` to the tiles of `frontier` it can reach.
+/// The path crosses tiles `WorldState::is_walkable` accepts, and ends on the frontier tile.
+pub fn frontier_distances(
+    world: &WorldState,
+    start: Position,
+    frontier: &HashSet<Position>,
+) -> HashMap<Position, i32> {
+    let mut distances = HashMap::new();
+    let mut visited = HashSet::from([start]);
+    let mut queue = VecDeque::from([(start, 0)]);
+
+    while let Some((current, distance)) = queue.pop_front() {
+        for neighbor in current.neighbors() {
+            if frontier.contains(&neighbor) {
+                distances.entry(neighbor).or_insert(distance + 1);
+            } else if world.is_walkable(&neighbor, None) && visited.insert(neighbor) {
+                queue.push_back((neighbor, distance + 1));
+            }
+        }
+    }
+    distances
+}
+
+/// Unknown tiles within visibility range of `pos`, which standing there would reveal
+pub fn expected_gain(world: &WorldState, pos: Position) -> i32 {
+    let bounds = Bounds::from_center_and_range(pos, world.visibility_range);
+    let mut gain = 0;
+    for y in bounds.min_y.max(0)..=bounds.max_y.min(world.map.height - 1) {
+        for x in bounds.min_x.max(0)..=bounds.max_x.min(world.map.width - 1) {
+            if matches!(world.map.get(&Position::new(x, y)), Some(Tile::Unknown) | None) {
+                gain += 1;
+            }
+        }
+    }
+    gain
+}
+
+/// Split tiles into groups that touch, diagonals included
+fn group_tiles(mut tiles: HashSet<Position>) -> Vec<Vec<Position>> {
+    let mut groups = Vec::new();
+    while let Some(&seed) = tiles.iter().next() {
+        tiles.remove(&seed);
+        let mut group = vec![seed];
+        let mut index = 0;
+        while index < group.len() {
+            let pos = group[index];
+            for dy in -1..=1 {
+                for dx in -1..=1 {
+                    let neighbor = Position::new(pos.x + dx, pos.y + dy);
+                    if tiles.remove(&neighbor) {
+                        group.push(neighbor);
+                    }
+                }
+            }
+            index += 1;
+        }
+        groups.push(group);
+    }
+    groups
+}
+
+fn cluster(
+    world: &WorldState,
+    mut tiles: Vec<Position>,
+    distances: &HashMap<Position, i32>,
+) -> FrontierCluster {
+    tiles.sort_by(|a, b| distances[a].cmp(&distances[b]).then(position_order(a, b)));
+
+    // The nearest tiles come first, so on equal scores the nearer one wins
+    let (target, gain) = tiles
+        .iter()
+        .map(|&pos| (pos, expected_gain(world, pos)))
+        .reduce(|best, (pos, gain)| {
+            match compare_gain(gain, distances[&pos], best.1, distances[&best.0]) {
+                Ordering::Greater => (pos, gain),
+                _ => best,
+            }
+        })
+        .expect("clusters are not empty");
+
+    let index = tiles.iter().position(|pos| *pos == target).unwrap_or(0);
+    let target = tiles.remove(index);
+    tiles.insert(0, target);
+    FrontierCluster {
+        target,
+        distance: distances[&target],
+        gain,
+        tiles,
+    }
+}
+
+/// Best scoring cluster first, then the nearest
+fn compare_clusters(a: &FrontierCluster, b: &FrontierCluster) -> Ordering {
+    compare_gain(b.gain, b.distance, a.gain, a.distance)
+        .then(a.distance.cmp(&b.distance))
+        .then(position_order(&a.target, &b.target))
+}
+
+/// Compare `gain_a / distance_a` with `gain_b / distance_b` without rounding
+fn compare_gain(gain_a: i32, distance_a: i32, gain_b: i32, distance_b: i32) -> Ordering {
+    (gain_a as i64 * distance_b.max(1) as i64).cmp(&(gain_b as i64 * distance_a.max(1) as i64))
+}
+
+/// Row-major order, so that ties break the same way on every run
+fn position_order(a: &Position, b: &Position) -> Ordering {
+    (a.y, a.x).cmp(&(b.y, b.x))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use super::*;
+    use crate::state::WorldStateBuilder;
+
+    #[test]
+    fn test_distance_follows_walls() {
+        // The frontier right of the player is behind a wall, the one below is around it
+        let world = WorldStateBuilder::new(
+            "\
+#######
+#1#???#
+# #####
+#   ???
+#######
+",
+        )
+        .visibility_range(1)
+        .build();
+        let clusters = frontier_clusters(&world, 0);
+
+        assert_eq!(clusters.len(), 1);
+        assert_eq!(clusters[0].target, Position::new(4, 3));
+        assert_eq!(clusters[0].distance, 5);
+        assert_eq!(clusters[0].tiles.len(), 1);
+    }
+
+    #[test]
+    fn test_large_unknown_area_beats_nearest_dead_end() {
+        // One step left is a single unknown tile in a pocket, four steps right is a dark room
+        let world = WorldStateBuilder::new(
+            "\
+###########
+##########?
+#?  1   ???
+##########?
+##########?
+",
+        )
+        .visibility_range(2)
+        .build();
+        let clusters = frontier_clusters(&world, 0);
+
+        assert_eq!(clusters.len(), 2);
+        assert_eq!(clusters[0].target, Position::new(8, 2));
+        assert_eq!(clusters[0].distance, 4);
+        assert_eq!(clusters[1].target, Position::new(1, 2));
+        assert!(clusters[0].score() > clusters[1].score());
+        assert_eq!(ranked_frontier(&world, 0)[0], Position::new(8, 2));
+    }
+
+    #[test]
+    fn test_players_explore_the_nearest_side() {
+        let world = WorldStateBuilder::new(
+            "\
+#############
+?   1   2   ?
+#############
+",
+        )
+        .build();
+        let shares = allocate_frontier(&world);
+
+        assert_eq!(shares[0].len(), 1);
+        assert_eq!(shares[0][0].target, Position::new(0, 1));
+        assert_eq!(shares[1].len(), 1);
+        assert_eq!(shares[1][0].target, Position::new(12, 1));
+        assert_eq!(frontier_owner(&world, Position::new(12, 1)), Some(1));
+
+        // Each player falls back to the other side once its own side is done
+        let clusters = exploration_clusters(&world, 0);
+        assert_eq!(clusters.len(), 2);
+        assert_eq!(clusters[0].target, Position::new(0, 1));
+        assert_eq!(clusters[1].target, Position::new(12, 1));
+    }
+
+    #[test]
+    fn test_player_without_nearest_cluster_gets_one() {
+        // Player 2 is nearest to both clusters, player 1 takes one of them
+        let world = WorldStateBuilder::new(
+            "\
+##########
+#12     ?#
+####### ##
+#######?##
+",
+        )
+        .build();
+        let shares = allocate_frontier(&world);
+
+        assert_eq!(shares[0].len(), 1);
+        assert_eq!(shares[1].len(), 1);
+        assert_ne!(shares[0][0].target, shares[1][0].target);
+        assert_eq!(ranked_frontier(&world, 0)[0], shares[0][0].target);
+        assert_eq!(ranked_frontier(&world, 1)[0], shares[1][0].target);
+    }
+
+    #[test]
+    fn test_cluster_keeps_its_owner_until_another_player_is_clearly_closer() {
+        let mut world = WorldStateBuilder::new(
+            "\
+#################
+#  1   ?       2#
+#################
+",
+        )
+        .build();
+        let frontier = Position::new(7, 1);
+        assert_eq!(frontier_owner(&world, frontier), Some(0));
+
+        // Player 2 is nearer now, but not by enough to take the cluster over
+        world.players[1].position = Position::new(9, 1);
+        let mut fresh = world.clone();
+        fresh.frontier_allocation = Arc::default();
+        assert_eq!(FrontierAllocation::new(&fresh).owner(frontier), Some(1));
+        world.frontier_allocation = Arc::new(FrontierAllocation::new(&world));
+        assert_eq!(frontier_owner(&world, frontier), Some(0));
+
+        // Player 1 walks away, player 2 is now four steps closer
+        world.players[0].position = Position::new(1, 1);
+        world.frontier_allocation = Arc::new(FrontierAllocation::new(&world));
+        assert_eq!(frontier_owner(&world, frontier), Some(1));
+    }
+
+    #[test]
+    fn test_single_player_gets_every_cluster() {
+        let world = WorldStateBuilder::new("#########\n?   1   ?\n#########").build();
+        let shares = allocate_frontier(&world);
+
+        assert_eq!(shares.len(), 1);
+        assert_eq!(shares[0].len(), 2);
+        assert_eq!(exploration_clusters(&world, 0), frontier_clusters(&world, 0));
+    }
+
+    #[test]
+    fn test_no_frontier_no_clusters() {
+        let world = WorldStateBuilder::new("#####\n#1  #\n#####").build();
+        assert!(frontier_clusters(&world, 0).is_empty());
+        assert!(ranked_frontier(&world, 0).is_empty());
+    }
+}
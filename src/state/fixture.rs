@@ -0,0 +1,315 @@
+//! Text fixtures for `WorldState`
+//!
+//! A fixture is a map drawn with the glyphs of `WorldState::draw_ascii_map`, without
+//! the colors: `1` and `2` are the players, `?` was never seen, `·` is out of sight,
+//! `O` is a boulder that never moved and `o` one that was dropped. The builder shows
+//! the whole fixture to the world state as a single server state, so the map, the
+//! item and enemy trackers and the frontiers are filled in as during a game.
+
+use std::sync::Arc;
+
+use crate::infra::{Position, tile_to_glyph};
+use crate::sim::{Level, LevelError};
+use crate::state::WorldState;
+use crate::state::exploration::FrontierAllocation;
+use crate::swoq_interface::{self, GameStatus, Inventory, State, Tile};
+
+#[derive(Debug, Clone, Copy)]
+struct FixturePlayer {
+    health: i32,
+    inventory: Inventory,
+    has_sword: bool,
+}
+
+impl Default for FixturePlayer {
+    fn default() -> Self {
+        Self {
+            health: 5,
+            inventory: Inventory::None,
+            has_sword: false,
+        }
+    }
+}
+
+/// Builds a `WorldState` from a fixture, with the player details the map cannot show
+#[derive(Debug, Clone)]
+pub struct WorldStateBuilder {
+    fixture: String,
+    level: i32,
+    tick: i32,
+    visibility_range: i32,
+    players: [FixturePlayer; 2],
+}
+
+impl WorldStateBuilder {
+    pub fn new(fixture: &str) -> Self {
+        Self {
+            fixture: fixture.to_string(),
+            level: 0,
+            tick: 0,
+            visibility_range: 4,
+            players: [FixturePlayer::default(); 2],
+        }
+    }
+
+    pub fn level(mut self, level: i32) -> Self {
+        self.level = level;
+        self
+    }
+
+    pub fn tick(mut self, tick: i32) -> Self {
+        self.tick = tick;
+        self
+    }
+
+    pub fn visibility_range(mut self, visibility_range: i32) -> Self {
+        self.visibility_range = visibility_range;
+        self
+    }
+
+    /// Health of player `player_index` (0 or 1)
+    pub fn health(mut self, player_index: usize, health: i32) -> Self {
+        self.players[player_index].health = health;
+        self
+    }
+
+    pub fn inventory(mut self, player_index: usize, inventory: Inventory) -> Self {
+        self.players[player_index].inventory = inventory;
+        self
+    }
+
+    pub fn sword(mut self, player_index: usize) -> Self {
+        self.players[player_index].has_sword = true;
+        self
+    }
+
+    pub fn try_build(self) -> Result<WorldState, LevelError> {
+        let fixture = Fixture::parse(&self.fixture)?;
+
+        // A visibility range covering the map shows every seen tile from any position
+        let range = fixture.width.max(fixture.height);
+        let mut world = WorldState::new(fixture.width, fixture.height, range);
+        let player_state = |player_index: usize| {
+            let position = *fixture.players.get(player_index)?;
+            let player = self.players[player_index];
+            Some(swoq_interface::PlayerState {
+                position: Some(swoq_interface::Position {
+                    x: position.x,
+                    y: position.y,
+                }),
+                surroundings: fixture.surroundings(position, range),
+                inventory: Some(player.inventory as i32),
+                health: Some(player.health),
+                has_sword: Some(player.has_sword),
+            })
+        };
+        world.update(&State {
+            tick: self.tick,
+            level: self.level,
+            status: GameStatus::Active as i32,
+            player_state: player_state(0),
+            player2_state: player_state(1),
+        });
+
+        world.visibility_range = self.visibility_range;
+        for &(pos, has_moved) in &fixture.boulders {
+            world.boulders.add_boulder(pos, has_moved);
+        }
+        // The frontier gains depend on the visibility range
+        world.frontier_allocation = Arc::new(FrontierAllocation::new(&world));
+        Ok(world)
+    }
+
+    /// Build the world state, panicking on an invalid fixture
+    pub fn build(self) -> WorldState {
+        self.try_build()
+            .unwrap_or_else(|e| panic!("Invalid fixture: {}", e))
+    }
+}
+
+impl WorldState {
+    /// World state of a fixture with default player details, see `WorldStateBuilder`
+    pub fn from_fixture(fixture: &str) -> Self {
+        WorldStateBuilder::new(fixture).build()
+    }
+
+    /// Render the known map as a fixture: `draw_ascii_map` without the colors
+    pub fn to_fixture(&self) -> String {
+        let mut output = String::new();
+        for y in 0..self.map.height {
+            for x in 0..self.map.width {
+                let pos = Position::new(x, y);
+                let tile = self.map.get(&pos).copied();
+                let glyph = match self.players.iter().position(|p| p.position == pos) {
+                    Some(0) => '1',
+                    Some(_) => '2',
+                    None if tile == Some(Tile::Boulder) && !self.boulders.has_moved(&pos) => 'O',
+                    None => tile_to_glyph(tile),
+                };
+                output.push(glyph);
+            }
+            output.push('\n');
+        }
+        output
+    }
+}
+
+/// Parsed fixture map
+struct Fixture {
+    width: i32,
+    height: i32,
+    /// `None` where the map was never seen
+    tiles: Vec<Option<Tile>>,
+    players: Vec<Position>,
+    /// Boulder positions and whether they were moved
+    boulders: Vec<(Position, bool)>,
+}
+
+impl Fixture {
+    /// Rows shorter than the widest row are padded with never seen tiles.
+    /// `Level::parse` reads the tiles and players, with the never seen tiles passed as
+    /// out of sight; only the never seen tiles and the boulder glyphs are read here.
+    fn parse(text: &str) -> Result<Self, LevelError> {
+        let rows: Vec<Vec<char>> = text.lines().map(|line| line.chars().collect()).collect();
+        let first = rows.iter().position(|row| !row.is_empty());
+        let last = rows.iter().rposition(|row| !row.is_empty());
+        let (Some(first), Some(last)) = (first, last) else {
+            return Err(LevelError::Empty);
+        };
+        let rows = &rows[first..=last];
+        let width = rows.iter().map(|row| row.len()).max().unwrap_or(0);
+        let rows: Vec<Vec<char>> = rows
+            .iter()
+            .map(|row| {
+                let padding = std::iter::repeat_n('?', width - row.len());
+                row.iter().copied().chain(padding).collect()
+            })
+            .collect();
+
+        let unknown = tile_to_glyph(Some(Tile::Unknown));
+        let seen: String = rows
+            .iter()
+            .flat_map(|row| row.iter().chain(&['\n']))
+            .map(|&glyph| if glyph == '?' { unknown } else { glyph })
+            .collect();
+        let level = Level::parse(0, &seen)?;
+
+        let mut tiles: Vec<Option<Tile>> = (0..level.height)
+            .flat_map(|y| (0..level.width).map(move |x| Position::new(x, y)))
+            .map(|pos| Some(level.tile(pos)))
+            .collect();
+        let mut boulders = Vec::new();
+        for (y, row) in rows.iter().enumerate() {
+            for (x, &glyph) in row.iter().enumerate() {
+                let pos = Position::new(x as i32, y as i32);
+                match glyph {
+                    '?' => tiles[y * width + x] = None,
+                    'o' | 'O' => boulders.push((pos, glyph == 'o')),
+                    _ => {}
+                }
+            }
+        }
+        for pos in level.player_starts() {
+            tiles[(pos.y * level.width + pos.x) as usize] = Some(Tile::Player);
+        }
+
+        Ok(Self {
+            width: level.width,
+            height: level.height,
+            tiles,
+            players: level.player_starts().to_vec(),
+            boulders,
+        })
+    }
+
+    /// Surroundings of a server state centered on `center`. Tiles that were never
+    /// seen get a value outside `Tile`, which the world state skips.
+    fn surroundings(&self, center: Position, range: i32) -> Vec<i32> {
+        let mut surroundings = Vec::with_capacity(((range * 2 + 1) * (range * 2 + 1)) as usize);
+        for dy in -range..=range {
+            for dx in -range..=range {
+                let pos = Position::new(center.x + dx, center.y + dy);
+                let inside = (0..self.width).contains(&pos.x) && (0..self.height).contains(&pos.y);
+                let tile = if inside {
+                    self.tiles[(pos.y * self.width + pos.x) as usize]
+                } else {
+                    Some(Tile::Unknown)
+                };
+                surroundings.push(tile.map_or(-1, |tile| tile as i32));
+            }
+        }
+        surroundings
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::infra::Color;
+
+    const FIXTURE: &str = "\
+██████████
+█1 r █O  ?
+█  o R  E?
+█+ s █2e·?
+██████████
+";
+
+    #[test]
+    fn test_builder_fills_map_trackers_and_players() {
+        let world = WorldStateBuilder::new(FIXTURE)
+            .level(12)
+            .tick(30)
+            .inventory(0, Inventory::KeyBlue)
+            .health(1, 3)
+            .sword(1)
+            .build();
+
+        assert_eq!((world.level, world.tick, world.visibility_range), (12, 30, 4));
+        assert_eq!((world.map.width, world.map.height), (10, 5));
+        assert!(world.is_two_player_mode());
+        assert_eq!(world.players[0].position, Position::new(1, 1));
+        assert_eq!(world.players[0].inventory, Inventory::KeyBlue);
+        assert_eq!(world.players[1].position, Position::new(6, 3));
+        assert_eq!((world.players[1].health, world.players[1].has_sword), (3, true));
+
+        assert_eq!(world.keys.get_positions(Color::Red), Some(&[Position::new(3, 1)][..]));
+        assert_eq!(world.doors.get_positions(Color::Red), Some(&[Position::new(5, 2)][..]));
+        assert_eq!(world.exit_position, Some(Position::new(8, 2)));
+        assert_eq!(world.swords.get_positions(), &[Position::new(3, 3)]);
+        assert_eq!(world.health.get_positions(), &[Position::new(1, 3)]);
+        assert_eq!(world.enemy_tracker.enemies().len(), 1);
+        assert!(world.boulders.has_moved(&Position::new(3, 2)));
+        assert!(!world.boulders.has_moved(&Position::new(6, 1)));
+
+        assert_eq!(world.map.get(&Position::new(9, 1)), None);
+        assert_eq!(world.map.get(&Position::new(8, 3)), Some(&Tile::Unknown));
+        assert!(world.players[0].unexplored_frontier.is_empty());
+        assert!(!world.players[1].unexplored_frontier.is_empty());
+    }
+
+    #[test]
+    fn test_fixture_round_trip() {
+        let world = WorldState::from_fixture(FIXTURE);
+        assert_eq!(world.to_fixture(), FIXTURE);
+    }
+
+    #[test]
+    fn test_scenario_in_a_few_lines() {
+        let world = WorldStateBuilder::new("#######\n#1 r R E\n#######")
+            .inventory(0, Inventory::KeyRed)
+            .build();
+        assert!(world.has_key(&world.players[0], Color::Red));
+        let path = world.find_path(world.players[0].position, Position::new(3, 1));
+        assert_eq!(path.map(|path| path.len()), Some(3));
+    }
+
+    #[test]
+    fn test_invalid_fixtures() {
+        let error = |text: &str| WorldStateBuilder::new(text).try_build().unwrap_err();
+        assert_eq!(error("\n\n"), LevelError::Empty);
+        assert_eq!(error("# E #"), LevelError::MissingPlayer);
+        assert_eq!(error("#1 1#"), LevelError::DuplicatePlayer { player: 1 });
+        assert!(matches!(error("#1~#"), LevelError::UnknownGlyph { glyph: '~', .. }));
+    }
+}
@@ -1,13 +1,24 @@
 use std::collections::HashMap;
+use std::sync::Arc;
 
 use crate::infra::Position;
 use crate::swoq_interface::Tile;
 
+/// Overlay size at which a shared map copies its base and folds the overlay into it
+const MAX_OVERLAY: usize = 256;
+
+/// Known tiles of the level.
+///
+/// Cloning is cheap: clones share the tiles as a base and keep their own changes in a
+/// small overlay, so the GOAP search can clone world states per node. A map that owns
+/// its base alone (the live world state) writes straight into it.
 #[derive(Clone, Debug)]
 pub struct Map {
     pub width: i32,
     pub height: i32,
-    tiles: HashMap<Position, Tile>,
+    base: Arc<HashMap<Position, Tile>>,
+    /// Tiles written on top of `base` while it is shared
+    overlay: HashMap<Position, Tile>,
 }
 
 impl Map {
@@ -15,32 +26,47 @@ impl Map {
         Self {
             width,
             height,
-            tiles: HashMap::new(),
+            base: Arc::new(HashMap::new()),
+            overlay: HashMap::new(),
         }
     }
 
     pub fn get(&self, pos: &Position) -> Option<&Tile> {
-        self.tiles.get(pos)
+        if self.overlay.is_empty() {
+            return self.base.get(pos);
+        }
+        self.overlay.get(pos).or_else(|| self.base.get(pos))
     }
 
     pub fn insert(&mut self, pos: Position, tile: Tile) -> Option<Tile> {
-        self.tiles.insert(pos, tile)
+        if let Some(base) = Arc::get_mut(&mut self.base) {
+            let overlaid = self.overlay.remove(&pos);
+            let previous = base.insert(pos, tile);
+            return overlaid.or(previous);
+        }
+        let previous = self.get(&pos).copied();
+        self.overlay.insert(pos, tile);
+        if self.overlay.len() > MAX_OVERLAY {
+            self.flatten();
+        }
+        previous
     }
 
     pub fn retain<F>(&mut self, f: F)
     where
         F: FnMut(&Position, &mut Tile) -> bool,
     {
-        self.tiles.retain(f);
-    }
-
-    #[allow(dead_code)]
-    pub fn tiles(&self) -> &HashMap<Position, Tile> {
-        &self.tiles
+        self.flatten();
+        Arc::make_mut(&mut self.base).retain(f);
     }
 
     pub fn len(&self) -> usize {
-        self.tiles.len()
+        let added = self
+            .overlay
+            .keys()
+            .filter(|pos| !self.base.contains_key(pos))
+            .count();
+        self.base.len() + added
     }
 
     #[must_use]
@@ -49,6 +75,85 @@ impl Map {
     }
 
     pub fn iter(&self) -> impl Iterator<Item = (&Position, &Tile)> {
-        self.tiles.iter()
+        let base = self
+            .base
+            .iter()
+            .filter(|(pos, _)| !self.overlay.contains_key(pos));
+        base.chain(self.overlay.iter())
+    }
+
+    /// Fold the overlay into the base, copying the base if it is shared
+    fn flatten(&mut self) {
+        if self.overlay.is_empty() {
+            return;
+        }
+        Arc::make_mut(&mut self.base).extend(self.overlay.drain());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn map() -> Map {
+        let mut map = Map::new(3, 1);
+        map.insert(Position::new(0, 0), Tile::Wall);
+        map.insert(Position::new(1, 0), Tile::Empty);
+        map
+    }
+
+    #[test]
+    fn test_clone_keeps_changes_apart() {
+        let original = map();
+        let mut clone = original.clone();
+
+        assert_eq!(clone.insert(Position::new(1, 0), Tile::Boulder), Some(Tile::Empty));
+        assert_eq!(clone.insert(Position::new(2, 0), Tile::Exit), None);
+
+        assert_eq!(clone.get(&Position::new(1, 0)), Some(&Tile::Boulder));
+        assert_eq!(clone.get(&Position::new(2, 0)), Some(&Tile::Exit));
+        assert_eq!(clone.len(), 3);
+        assert_eq!(original.get(&Position::new(1, 0)), Some(&Tile::Empty));
+        assert_eq!(original.get(&Position::new(2, 0)), None);
+        assert_eq!(original.len(), 2);
+        assert_eq!(clone.overlay.len(), 2);
+        assert!(Arc::ptr_eq(&original.base, &clone.base));
+    }
+
+    #[test]
+    fn test_iter_and_retain_see_the_overlay() {
+        let original = map();
+        let mut clone = original.clone();
+        clone.insert(Position::new(2, 0), Tile::Exit);
+
+        let mut tiles: Vec<(Position, Tile)> = clone.iter().map(|(p, t)| (*p, *t)).collect();
+        tiles.sort_by_key(|(pos, _)| pos.x);
+        assert_eq!(
+            tiles,
+            vec![
+                (Position::new(0, 0), Tile::Wall),
+                (Position::new(1, 0), Tile::Empty),
+                (Position::new(2, 0), Tile::Exit),
+            ]
+        );
+
+        clone.retain(|_, tile| *tile != Tile::Empty);
+        assert_eq!(clone.len(), 2);
+        assert_eq!(clone.get(&Position::new(1, 0)), None);
+        assert_eq!(original.get(&Position::new(1, 0)), Some(&Tile::Empty));
+    }
+
+    #[test]
+    fn test_large_overlay_is_folded_into_a_copy() {
+        let original = map();
+        let mut clone = original.clone();
+        for x in 0..=MAX_OVERLAY as i32 {
+            clone.insert(Position::new(x, 1), Tile::Empty);
+        }
+
+        assert!(clone.overlay.is_empty());
+        assert!(!Arc::ptr_eq(&original.base, &clone.base));
+        assert_eq!(clone.len(), MAX_OVERLAY + 3);
+        assert_eq!(original.len(), 2);
     }
 }
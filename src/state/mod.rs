@@ -1,7 +1,10 @@
+pub mod exploration;
+mod fixture;
 mod map;
 mod player_state;
 mod world_state;
 
+pub use fixture::WorldStateBuilder;
 pub use map::Map;
 pub use player_state::PlayerState;
-pub use world_state::WorldState;
+pub use world_state::{BOSS_DAMAGE, BOSS_FIGHT_MIN_HEALTH, BOSS_LEVEL, WorldState};
@@ -1,4 +1,5 @@
 use std::collections::HashSet;
+use std::sync::Arc;
 
 use crate::swoq_interface::Inventory;
 use crate::infra::Position;
@@ -12,7 +13,8 @@ pub struct PlayerState {
     pub is_active: bool,
     pub current_destination: Option<Position>,
     pub current_path: Option<Vec<Position>>,
-    pub unexplored_frontier: HashSet<Position>,
+    /// Replaced as a whole on every update, so clones share it
+    pub unexplored_frontier: Arc<HashSet<Position>>,
     /// For coop door coordination: the target position this player is trying to reach
     /// Set by PassThroughDoorWithPlateAction, read by WaitOnPlateAction on other player
     pub coop_door_target: Option<Position>,
@@ -28,15 +30,8 @@ impl PlayerState {
             is_active: true,
             current_destination: None,
             current_path: None,
-            unexplored_frontier: HashSet::new(),
+            unexplored_frontier: Arc::default(),
             coop_door_target: None,
         }
     }
-
-    #[tracing::instrument(level = "trace", skip(self))]
-    pub fn sorted_unexplored(&self) -> Vec<Position> {
-        let mut frontier: Vec<Position> = self.unexplored_frontier.iter().copied().collect();
-        frontier.sort_by_key(|pos| self.position.distance(pos));
-        frontier
-    }
 }
@@ -1,10 +1,13 @@
 use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
 
 use tracing::{debug, warn};
 
 use crate::infra::{
-    AStar, Agent, BoulderTracker, Bounds, CBS, Color, ColoredItemTracker, ItemTracker, Position,
+    AStar, Agent, BoulderTracker, Bounds, CBS, CbsLimits, Color, ColoredItemTracker, EnemyTracker,
+    ItemTracker, Position, ReservationTable, ThreatMap, path_length,
 };
+use crate::state::exploration::FrontierAllocation;
 use crate::state::{Map, PlayerState};
 use crate::swoq_interface::{Inventory, State, Tile};
 
@@ -23,8 +26,18 @@ struct SeenItems {
     swords: Vec<Position>,
     health: Vec<Position>,
     enemies: Vec<Position>,
+    boss: Option<Position>,
 }
 
+/// First level that can contain a boss guarding the treasure
+pub const BOSS_LEVEL: i32 = 22;
+
+/// Minimum health to engage the boss, it hits harder and takes longer to kill than an enemy
+pub const BOSS_FIGHT_MIN_HEALTH: i32 = 5;
+
+/// Health a player loses to one hit of the boss
+pub const BOSS_DAMAGE: i32 = 2;
+
 #[derive(Clone, Debug)]
 pub struct WorldState {
     pub level: i32,
@@ -47,11 +60,28 @@ pub struct WorldState {
     pub pressure_plates: ColoredItemTracker,
     pub exit_position: Option<Position>,
     pub boss_position: Option<Position>,
-    pub potential_enemy_locations: HashSet<Position>,
+    // Only changed by `update`, copied on write so that plan nodes share it
+    pub potential_enemy_locations: Arc<HashSet<Position>>,
     pub treasure_position: Option<Position>,
 
+    // Enemy identities and movement, and where they may be over the next ticks.
+    // Predictions are only replaced, never changed, so clones share them.
+    pub enemy_tracker: Arc<EnemyTracker>,
+    pub threats: Arc<ThreatMap>,
+    pub enemy_reservations: Arc<ReservationTable>,
+
+    // Frontier split between the players for this tick, replaced on every update
+    pub frontier_allocation: Arc<FrontierAllocation>,
+
+    // Boss levels: the treasure must be carried out before anyone can exit
+    pub treasure_required: bool,
+    pub treasure_delivered: bool,
+
     // Track which pressure plate colors have been touched (for TouchPlate action)
-    pub plates_touched: HashSet<Color>,
+    pub plates_touched: Arc<HashSet<Color>>,
+
+    // Search effort for multi-player paths
+    pub cbs_limits: CbsLimits,
 }
 
 impl WorldState {
@@ -72,8 +102,15 @@ impl WorldState {
             pressure_plates: ColoredItemTracker::new(),
             boss_position: None,
             treasure_position: None,
-            potential_enemy_locations: HashSet::new(),
-            plates_touched: HashSet::new(),
+            treasure_required: false,
+            treasure_delivered: false,
+            potential_enemy_locations: Arc::default(),
+            enemy_tracker: Arc::default(),
+            threats: Arc::default(),
+            enemy_reservations: Arc::default(),
+            frontier_allocation: Arc::default(),
+            plates_touched: Arc::default(),
+            cbs_limits: CbsLimits::default(),
         }
     }
 
@@ -83,6 +120,9 @@ impl WorldState {
         self.tick = state.tick;
 
         let mut all_surroundings = Vec::new();
+        let treasure_carriers: Vec<usize> = (0..self.players.len())
+            .filter(|&i| self.players[i].inventory == Inventory::Treasure)
+            .collect();
 
         // Update player 1
         if let Some(p1) = self.players.get_mut(0) {
@@ -123,6 +163,7 @@ impl WorldState {
         }
 
         self.integrate_surroundings(all_surroundings);
+        self.update_treasure(&treasure_carriers);
 
         // Update frontier for each player, considering door states
         for i in 0..self.players.len() {
@@ -133,8 +174,9 @@ impl WorldState {
             // Filter to only keep positions that are actually Unknown or None
             frontier.retain(|pos| matches!(self.map.get(pos), Some(Tile::Unknown) | None));
 
-            self.players[i].unexplored_frontier = frontier;
+            self.players[i].unexplored_frontier = Arc::new(frontier);
         }
+        self.frontier_allocation = Arc::new(FrontierAllocation::new(self));
     }
 
     fn update_player_state_fields(
@@ -190,13 +232,16 @@ impl WorldState {
             if *tile == Tile::Unknown {
                 // Keep Unknown tiles only if they're in any player's current visibility range
                 combined_bounds.iter().any(|b| b.contains(pos))
+            } else if *tile == Tile::Boss {
+                // The boss moves like any enemy, its last known position is kept in boss_position
+                combined_bounds.iter().any(|b| b.contains(pos))
             } else if *tile == Tile::Enemy {
                 // Remove enemy tiles that are no longer in any player's visibility range
                 // When enemies move out of sight, we should remove them from the map
                 let is_visible = combined_bounds.iter().any(|b| b.contains(pos));
                 if !is_visible {
                     debug!("Enemy at {:?} is no longer visible, removing from map and adding to potential locations", pos);
-                    self.potential_enemy_locations.insert(*pos);
+                    Arc::make_mut(&mut self.potential_enemy_locations).insert(*pos);
                 }
                 is_visible
             } else {
@@ -213,10 +258,37 @@ impl WorldState {
 
         // Process merged surroundings once
         self.process_surroundings(&merged_surroundings, &mut seen_items);
+        self.forget_stale_boss_and_treasure(&merged_surroundings);
+
+        let player_positions: Vec<Position> = self
+            .players
+            .iter()
+            .filter(|p| p.is_active)
+            .map(|p| p.position)
+            .collect();
+        Arc::make_mut(&mut self.enemy_tracker).update(
+            self.tick,
+            &seen_items.enemies,
+            seen_items.boss,
+            &player_positions,
+            &combined_bounds,
+        );
 
         // Update item trackers with collected items
         // Pass all bounds so items are validated if visible to ANY player
         self.update_item_trackers(&combined_bounds, seen_items);
+
+        // Predict enemy movement on the updated map
+        self.threats = Arc::new(self.enemy_tracker.predict(
+            &self.map,
+            self.tick,
+            &player_positions,
+        ));
+        self.enemy_reservations = Arc::new(self.enemy_tracker.reservations(
+            &self.map,
+            self.tick,
+            &player_positions,
+        ));
     }
 
     #[tracing::instrument(level = "trace", skip(self, all_surroundings))]
@@ -281,14 +353,18 @@ impl WorldState {
                 );
                 self.map.insert(tile_position, Tile::Empty);
                 // Add to potential enemy locations - the enemy may have moved nearby
-                self.potential_enemy_locations.insert(tile_position);
+                Arc::make_mut(&mut self.potential_enemy_locations).insert(tile_position);
+                continue;
+            }
+            if tile == Tile::Unknown && matches!(self.map.get(&tile_position), Some(Tile::Boss)) {
+                self.map.insert(tile_position, Tile::Empty);
                 continue;
             }
 
             // If we see a known tile at a potential enemy location, remove it from potential list
             if tile != Tile::Unknown
                 && tile != Tile::Enemy
-                && self.potential_enemy_locations.remove(&tile_position)
+                && Arc::make_mut(&mut self.potential_enemy_locations).remove(&tile_position)
             {
                 debug!(
                     "Position {:?} is now known as {:?}, removing from potential enemy locations",
@@ -329,7 +405,10 @@ impl WorldState {
             if tile != Tile::Unknown {
                 match tile {
                     Tile::Exit => self.exit_position = Some(tile_position),
-                    Tile::Boss => self.boss_position = Some(tile_position),
+                    Tile::Boss => {
+                        self.boss_position = Some(tile_position);
+                        seen_items.boss = Some(tile_position);
+                    }
                     Tile::Treasure => self.treasure_position = Some(tile_position),
                     Tile::KeyRed => {
                         seen_items
@@ -404,6 +483,45 @@ impl WorldState {
         }
     }
 
+    /// Clear the boss and treasure positions when their tile is in view but shows something else
+    fn forget_stale_boss_and_treasure(&mut self, merged_surroundings: &HashMap<Position, Tile>) {
+        let is_stale = |pos: Option<Position>, expected: Tile| {
+            pos.and_then(|pos| merged_surroundings.get(&pos))
+                .is_some_and(|&tile| tile != expected && tile != Tile::Unknown)
+        };
+        if is_stale(self.boss_position, Tile::Boss) {
+            debug!("Boss is no longer at {:?}", self.boss_position);
+            self.boss_position = None;
+        }
+        if is_stale(self.treasure_position, Tile::Treasure) {
+            debug!("Treasure is no longer at {:?}", self.treasure_position);
+            self.treasure_position = None;
+        }
+    }
+
+    /// Track whether the treasure still has to be carried out.
+    /// `previous_carriers` are the players that held the treasure before this update.
+    fn update_treasure(&mut self, previous_carriers: &[usize]) {
+        if self.boss_position.is_some() || self.treasure_position.is_some() {
+            self.treasure_required = true;
+        }
+        if self
+            .players
+            .iter()
+            .any(|p| p.inventory == Inventory::Treasure)
+        {
+            self.treasure_required = true;
+            self.treasure_position = None;
+        }
+        if previous_carriers
+            .iter()
+            .any(|&i| !self.players[i].is_active)
+        {
+            debug!("Treasure has been carried out");
+            self.treasure_delivered = true;
+        }
+    }
+
     fn update_item_trackers(&mut self, all_bounds: &[Bounds], seen_items: SeenItems) {
         // Update key positions using ColoredItemTracker
         self.keys.update(
@@ -572,6 +690,24 @@ impl WorldState {
             .copied()
     }
 
+    /// True while the treasure still has to be carried out before players may exit
+    pub fn must_deliver_treasure(&self) -> bool {
+        self.treasure_required && !self.treasure_delivered
+    }
+
+    /// True on boss levels where the boss and treasure have not been seen yet
+    /// and there is still unexplored territory that could hide them
+    pub fn boss_may_be_unexplored(&self) -> bool {
+        self.level >= BOSS_LEVEL && !self.treasure_required && self.any_player_has_frontier()
+    }
+
+    /// Check if the treasure rules allow the player to exit: the treasure has to be carried
+    /// out first, and boss levels are explored until the boss or treasure has been found
+    pub fn treasure_allows_exit(&self, player: &PlayerState) -> bool {
+        player.inventory == Inventory::Treasure
+            || (!self.must_deliver_treasure() && !self.boss_may_be_unexplored())
+    }
+
     pub fn closest_sword(&self, player: &PlayerState) -> Option<Position> {
         self.swords.closest_to(player.position)
     }
@@ -618,7 +754,8 @@ impl WorldState {
 
     /// Get the actual path distance between two positions, returns None if unreachable
     pub fn path_distance(&self, from: Position, to: Position) -> Option<i32> {
-        self.find_path(from, to).map(|path| path.len() as i32 - 1)
+        self.find_path(from, to)
+            .map(|path| path_length(&path) as i32 - 1)
     }
 
     /// Get the path distance to an enemy position.
@@ -795,7 +932,6 @@ impl WorldState {
 
         // Check if any boulder position is not on a plate
         self.map
-            .tiles()
             .iter()
             .any(|(pos, tile)| matches!(tile, Tile::Boulder) && !all_plate_positions.contains(pos))
     }
@@ -896,12 +1032,12 @@ impl WorldState {
         false
     }
 
-    /// Check if a position is adjacent to any enemy
+    /// Check if a position is adjacent to any enemy or the boss
     pub fn is_adjacent_to_enemy(&self, pos: &Position) -> bool {
         let enemy_positions = self.enemies.get_positions();
-        pos.neighbors()
-            .iter()
-            .any(|neighbor| enemy_positions.contains(neighbor))
+        pos.neighbors().iter().any(|neighbor| {
+            enemy_positions.contains(neighbor) || self.boss_position == Some(*neighbor)
+        })
     }
 
     /// Get the movement cost for a position, with higher cost for positions adjacent to enemies
@@ -916,16 +1052,45 @@ impl WorldState {
         }
     }
 
-    /// Find a path from start to goal, avoiding tiles adjacent to enemies
-    /// Uses weighted pathfinding to make enemy-adjacent tiles more expensive
+    /// Get the cost of entering a position `ticks_ahead` ticks from now on the way to `goal`.
+    /// Besides known enemies, tiles next to where an enemy is predicted to be are expensive,
+    /// unless the goal is to walk up to an enemy (to attack it).
+    pub fn path_cost(&self, pos: &Position, goal: Position, ticks_ahead: i32) -> i32 {
+        let cost = self.movement_cost(pos);
+        if cost == 1 && !self.is_enemy_target(&goal) && self.threats.is_threatened(pos, ticks_ahead)
+        {
+            10
+        } else {
+            cost
+        }
+    }
+
+    /// Check if a goal is an enemy or the boss, or a tile next to one
+    pub fn is_enemy_target(&self, goal: &Position) -> bool {
+        self.is_adjacent_to_enemy(goal)
+            || self.enemies.get_positions().contains(goal)
+            || self.boss_position == Some(*goal)
+    }
+
+    /// Find a path from start to goal, avoiding tiles adjacent to current and predicted enemies
+    /// Uses weighted pathfinding to make enemy-adjacent tiles more expensive.
+    /// While enemy positions are predicted with certainty the path is timed: it steps around
+    /// them or waits for them to pass (a repeated position in the path).
     pub fn find_path(&self, start: Position, goal: Position) -> Option<Vec<Position>> {
-        AStar::find_path_with_cost(
-            &self.map,
-            start,
-            goal,
-            |pos, goal_pos, _tick| self.is_walkable(pos, Some(goal_pos)),
-            |pos, _goal_pos, _tick| self.movement_cost(pos),
-        )
+        let is_walkable = |pos: &Position, goal_pos, _tick| self.is_walkable(pos, Some(goal_pos));
+        let cost = |pos: &Position, goal_pos, tick| self.path_cost(pos, goal_pos, tick);
+        if self.enemy_reservations.is_empty() || self.is_enemy_target(&goal) {
+            AStar::find_path_with_cost(&self.map, start, goal, is_walkable, cost)
+        } else {
+            AStar::find_timed_path(
+                &self.map,
+                start,
+                goal,
+                is_walkable,
+                cost,
+                &self.enemy_reservations,
+            )
+        }
     }
 
     /// Compute all reachable positions from start, considering door states.
@@ -1059,8 +1224,9 @@ impl WorldState {
             .map(|agent| (agent.id, &self.players[agent.id]))
             .collect();
 
-        // Run CBS to find collision-free paths with per-agent walkability
-        match CBS::find_paths(&self.map, &agents, |pos, agent_id, goal| {
+        // Run CBS to find collision-free paths with per-agent walkability,
+        // steering clear of current and predicted enemy positions
+        let is_walkable = |pos: &Position, agent_id: usize, goal: Position| {
             // Get the player for this agent
             let player = agent_to_player
                 .get(&agent_id)
@@ -1096,7 +1262,16 @@ impl WorldState {
                 // This includes: Keys, Sword, Health, Exit, Enemy, Boss, Boulder, etc.
                 _ => *pos == goal,
             }
-        }) {
+        };
+        let step_cost = |pos: &Position, agent_id: usize, tick: i32| {
+            let goal = agents
+                .iter()
+                .find(|agent| agent.id == agent_id)
+                .map_or(*pos, |agent| agent.goal);
+            self.path_cost(pos, goal, tick)
+        };
+        match CBS::find_paths_with_cost(&self.map, &agents, is_walkable, step_cost, self.cbs_limits)
+        {
             Some(paths) => {
                 debug!("CBS found paths for {} agents", paths.len());
 
@@ -1118,3 +1293,65 @@ impl WorldState {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::infra::{path_to_action, use_direction};
+    use crate::sim::{Level, SimConfig, Simulator};
+    use crate::swoq_interface::{ActResult, DirectedAction};
+
+    #[test]
+    fn test_world_state_tracks_boss_and_treasure() {
+        let level = Level::parse(
+            22,
+            "\
+#######
+#1s X #
+#E    #
+#######",
+        )
+        .unwrap();
+        let config = SimConfig {
+            player_health: 100,
+            ..SimConfig::default()
+        };
+        let mut sim = Simulator::new(&level, config);
+
+        let mut world = WorldState::new(sim.map_width(), sim.map_height(), sim.visibility_range());
+        world.update(&sim.state());
+        assert!(world.boss_position.is_some());
+        assert!(world.must_deliver_treasure());
+        assert!(!world.treasure_allows_exit(&world.players[0]));
+
+        // Pick up the sword, then hit the boss whenever it is next to the player
+        assert_eq!(sim.act(DirectedAction::MoveEast, None), ActResult::Ok);
+        world.update(&sim.state());
+        assert!(world.players[0].has_sword);
+        for _ in 0..30 {
+            let Some(boss) = world.boss_position else {
+                break;
+            };
+            let player = world.players[0].position;
+            let action = if boss.is_adjacent(&player) {
+                use_direction(player, boss)
+            } else {
+                DirectedAction::None
+            };
+            assert_eq!(sim.act(action, None), ActResult::Ok);
+            world.update(&sim.state());
+        }
+        assert_eq!(world.boss_position, None);
+        let treasure = world.treasure_position.unwrap();
+        assert_eq!(world.map.get(&treasure), Some(&Tile::Treasure));
+
+        // Stepping onto the treasure picks it up
+        let player = world.players[0].position;
+        let action = path_to_action(player, &[player, treasure]).unwrap();
+        assert_eq!(sim.act(action, None), ActResult::Ok);
+        world.update(&sim.state());
+        assert_eq!(world.players[0].inventory, Inventory::Treasure);
+        assert_eq!(world.treasure_position, None);
+        assert!(world.treasure_allows_exit(&world.players[0]));
+    }
+}
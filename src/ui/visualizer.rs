@@ -431,7 +431,7 @@ fn render_world_state(
     }
 
     // Render unexplored frontier for Player 1 (positions that can be reached but haven't been explored yet)
-    for pos in &world_state.players[0].unexplored_frontier {
+    for pos in world_state.players[0].unexplored_frontier.iter() {
         let x = center_x + (pos.x as f32 * TILE_SIZE);
         let y = center_y - (pos.y as f32 * TILE_SIZE);
 
@@ -449,7 +449,7 @@ fn render_world_state(
 
     // Render unexplored frontier for Player 2 if available
     if world_state.players.len() > 1 {
-        for pos in &world_state.players[1].unexplored_frontier {
+        for pos in world_state.players[1].unexplored_frontier.iter() {
             let x = center_x + (pos.x as f32 * TILE_SIZE);
             let y = center_y - (pos.y as f32 * TILE_SIZE);
 
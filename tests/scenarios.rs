@@ -0,0 +1,251 @@
+//! Scenario regression tests for the planners
+//!
+//! Every scenario is a small level played in the offline simulator, and asserts that
+//! the heuristic planner (driving `StrategyPlanner`), the GOAP planner or both get every
+//! player out within the scenario's tick budget. The GOAP planner plays each scenario
+//! twice, blocking on every replan and with a search tick budget. Known planning hiccups
+//! are pinned with the outcome they have today, so fixing one fails its assertion and
+//! the pin moves to `assert_exits`.
+
+use robbot::config::{GoapConfig, RlConfig};
+use robbot::eval::PlannerKind;
+use robbot::infra::Position;
+use robbot::planners::Planner;
+use robbot::sim::{Level, SimConfig, Simulator};
+use robbot::state::WorldState;
+use robbot::swoq_interface::GameStatus;
+
+/// Tick budget of the anytime GOAP planner, as in `robbot.example.toml`
+const TICK_BUDGET_MS: u64 = 20;
+
+/// Planners of `kinds` as the eval and main binaries create them, with a name for the
+/// assertion messages. GOAP comes with the default configuration and with a tick budget.
+fn planners(kinds: &[PlannerKind]) -> Vec<(String, Box<dyn Planner>)> {
+    let blocking = GoapConfig::default();
+    let anytime = GoapConfig {
+        tick_budget_ms: Some(TICK_BUDGET_MS),
+        ..GoapConfig::default()
+    };
+    let rl = RlConfig::default();
+    let mut planners = Vec::new();
+    for &kind in kinds {
+        let create = |goap: &GoapConfig| kind.create(goap, &rl).unwrap();
+        planners.push((kind.name().to_string(), create(&blocking)));
+        if kind == PlannerKind::Goap {
+            planners.push((format!("{} anytime", kind.name()), create(&anytime)));
+        }
+    }
+    planners
+}
+
+const BOTH: &[PlannerKind] = &[PlannerKind::Heuristic, PlannerKind::Goap];
+const HEURISTIC: &[PlannerKind] = &[PlannerKind::Heuristic];
+const GOAP: &[PlannerKind] = &[PlannerKind::Goap];
+
+/// Start `map` as level `number`, with the world state of the first tick
+fn start(
+    planner: &mut dyn Planner,
+    number: i32,
+    map: &str,
+    tick_budget: i32,
+) -> (Simulator, WorldState) {
+    let level = Level::parse(number, map).unwrap();
+    let config = SimConfig {
+        max_ticks: tick_budget,
+        ..Default::default()
+    };
+    let simulator = Simulator::new(&level, config);
+    let mut world = WorldState::new(
+        simulator.map_width(),
+        simulator.map_height(),
+        simulator.visibility_range(),
+    );
+    world.update(&simulator.state());
+    planner.on_level_start(&world);
+    (simulator, world)
+}
+
+/// Play `map` as level `number` to the end, returns the final status and tick
+fn play(planner: &mut dyn Planner, number: i32, map: &str, tick_budget: i32) -> (GameStatus, i32) {
+    let (mut simulator, mut world) = start(planner, number, map, tick_budget);
+
+    let mut tick = 0;
+    while simulator.status() == GameStatus::Active {
+        let (action, action2) = planner.decide(&mut world);
+        simulator.act(action, action2);
+        world.update(&simulator.state());
+        tick += 1;
+    }
+    (simulator.status(), tick)
+}
+
+fn assert_exits(name: &str, kinds: &[PlannerKind], number: i32, map: &str, tick_budget: i32) {
+    assert_ends(name, kinds, number, map, tick_budget, GameStatus::FinishedSuccess);
+}
+
+fn assert_ends(
+    name: &str,
+    kinds: &[PlannerKind],
+    number: i32,
+    map: &str,
+    tick_budget: i32,
+    expected: GameStatus,
+) {
+    for (planner_name, mut planner) in planners(kinds) {
+        let (status, tick) = play(planner.as_mut(), number, map, tick_budget);
+        assert_eq!(
+            status, expected,
+            "{}: {} planner ended with {:?} after {} ticks",
+            name, planner_name, status, tick
+        );
+    }
+}
+
+#[test]
+fn key_behind_door() {
+    let map = "\
+###########
+#1   #    #
+#    #    #
+#  r R   E#
+###########
+";
+    assert_exits("key behind door", BOTH, 2, map, 60);
+}
+
+#[test]
+fn boulder_onto_plate_then_pass_door() {
+    let map = "\
+###########
+#1   #    #
+# O  R   E#
+#  ▫ #    #
+###########
+";
+    assert_exits("boulder onto plate then pass door", BOTH, 7, map, 80);
+}
+
+/// Player 2 holds the plate while player 1 fetches the key behind the door
+const COOP_DOOR: &str = "\
+############
+#1   #  r  #
+#2 ▫ R    E#
+#    #     #
+############
+";
+
+#[test]
+fn two_player_coop_door() {
+    assert_exits("two-player coop door", GOAP, 12, COOP_DOOR, 60);
+    // Hiccup: the heuristic players take turns on the plate and never bring the key back
+    let timeout = GameStatus::FinishedTimeout;
+    assert_ends("two-player coop door", HEURISTIC, 12, COOP_DOOR, 120, timeout);
+}
+
+/// Cave with the players side by side, both explore while the frontier is split between them
+const CAVE: &str = "\
+#########################
+#         ##      #   # #
+#  #  #   #    #       ##
+##  #   #       ##     ##
+#      #  # # # #   # ###
+#   #    #  12  #    #  #
+# #   # #     ##        #
+#     ##      #         #
+###  ##    #   #    #   #
+#  # ##        #   #   E#
+#########################
+";
+
+#[test]
+fn two_players_explore_without_swapping_targets() {
+    for (planner_name, mut planner) in planners(BOTH) {
+        let (mut simulator, mut world) = start(planner.as_mut(), 12, CAVE, 100);
+        while simulator.status() == GameStatus::Active {
+            let (action, action2) = planner.decide(&mut world);
+            // Frontier tiles the players now head for, when they are in their own share
+            let targets: Vec<Option<Position>> = (0..2)
+                .map(|player| {
+                    world.players[player].current_destination.filter(|target| {
+                        world.frontier_allocation.shares[player]
+                            .iter()
+                            .any(|cluster| cluster.tiles.contains(target))
+                    })
+                })
+                .collect();
+            simulator.act(action, action2);
+            world.update(&simulator.state());
+
+            for (player, target) in targets.iter().enumerate() {
+                let Some(target) = target else { continue };
+                let taken = world.frontier_allocation.shares[1 - player]
+                    .iter()
+                    .any(|cluster| cluster.tiles.contains(target));
+                assert!(
+                    !taken,
+                    "{} planner: player {} lost its target {:?} to the other player at tick {}",
+                    planner_name,
+                    player + 1,
+                    target,
+                    simulator.tick()
+                );
+            }
+        }
+    }
+}
+
+#[test]
+fn flee_enemy_without_sword() {
+    let map = "\
+###########
+#   1     #
+#e ###### #
+#      E  #
+###########
+";
+    assert_exits("flee enemy without sword", BOTH, 8, map, 40);
+}
+
+#[test]
+fn flee_enemy_onto_exit() {
+    let map = "\
+##########
+#e  1  E #
+##########
+";
+    assert_exits("flee enemy onto exit", GOAP, 8, map, 40);
+    // Hiccup: AvoidEnemy waits next to the exit instead of stepping onto it, until the
+    // enemy catches up
+    let died = GameStatus::FinishedPlayerDied;
+    assert_ends("flee enemy onto exit", HEURISTIC, 8, map, 40, died);
+}
+
+/// The enemy blocks the corridor between the sword and the exit
+const HUNT_CORRIDOR: &str = "\
+###############
+#1s  ##########
+#        e   E#
+###############
+";
+
+#[test]
+fn hunt_enemy_with_sword() {
+    assert_exits("hunt enemy with sword", HEURISTIC, 10, HUNT_CORRIDOR, 50);
+    // Hiccup: GOAP finds no plan next to the enemy after taking the sword and dies
+    let died = GameStatus::FinishedPlayerDied;
+    assert_ends("hunt enemy with sword", GOAP, 10, HUNT_CORRIDOR, 50, died);
+}
+
+/// Hiccup: once the boulder opens the door, AvoidEnemy steps back and forth in front of it
+#[test]
+fn boulder_near_enemy() {
+    let map = "\
+##############
+#1  O    #   #
+#        R  E#
+# ▫      #e  #
+##############
+";
+    let timeout = GameStatus::FinishedTimeout;
+    assert_ends("boulder near enemy", BOTH, 8, map, 120, timeout);
+}